@@ -0,0 +1,203 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{App, cli, conflict, risk};
+
+/// Line-based interface for terminal screen readers: no box drawing, no
+/// colour-only signals and no redraws. Every state change is announced as
+/// one plain line, and input is read a line at a time after a prompt.
+pub fn run(options: cli::TuiOptions) -> io::Result<()> {
+    let mut app = App::new(options);
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    say(&tr!("rip accessible mode. Type ? for commands."));
+    announce_message(&mut app);
+    announce_selection(&app);
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        match line.trim() {
+            "q" | "quit" => return Ok(()),
+            "" | "n" | "next" => {
+                app.next();
+                announce_selection(&app);
+            }
+            "p" | "previous" => {
+                app.previous();
+                announce_selection(&app);
+            }
+            "l" | "list" => {
+                for index in 0..app.processes.len() {
+                    say(&describe(&app, index));
+                }
+                if app.processes.is_empty() {
+                    say(&tr!("No listeners."));
+                }
+            }
+            "k" | "kill" => {
+                let Some(index) = app.list_state.selected() else {
+                    say(&tr!("Nothing selected."));
+                    continue;
+                };
+                let process = &app.processes[index];
+                if let Some(refusal) = app
+                    .config
+                    .never_kill_refusal(process)
+                    .or_else(|| app.safe_mode_refusal(process))
+                    .or_else(|| app.permission_refusal(process))
+                {
+                    say(&refusal);
+                    continue;
+                }
+                // As in the TUI, a protected process takes a capital Y.
+                let protection = risk::protection(process, &app.config);
+                match &protection {
+                    Some(reason) => print!(
+                        "{}",
+                        tr!(
+                            "Kill {} (PID: {}) on port {}? It is protected: {}. Type Y (capital) to go ahead: ",
+                            process.name,
+                            process.pid_label(),
+                            process.port,
+                            reason
+                        )
+                    ),
+                    None => print!(
+                        "{}",
+                        tr!(
+                            "Kill {} (PID: {}) on port {}? [y/N] ",
+                            process.name,
+                            process.pid_label(),
+                            process.port
+                        )
+                    ),
+                }
+                io::stdout().flush()?;
+                let mut answer = String::new();
+                input.read_line(&mut answer)?;
+                let answer = answer.trim();
+                let confirmed = match protection {
+                    Some(_) => answer == "Y",
+                    None => matches!(answer.to_lowercase().as_str(), "y" | "yes"),
+                };
+                if confirmed {
+                    app.kill_selected();
+                    announce_message(&mut app);
+                    announce_selection(&app);
+                } else {
+                    say(&tr!("Not killed."));
+                }
+            }
+            "o" | "open" => {
+                app.open_selected();
+                announce_message(&mut app);
+            }
+            "w" | "window" => {
+                app.focus_selected_window();
+                announce_message(&mut app);
+            }
+            "r" | "refresh" => {
+                app.refresh_processes();
+                announce_message(&mut app);
+                announce_selection(&app);
+            }
+            "i" | "ide" => {
+                app.toggle_ide_helpers();
+                announce_message(&mut app);
+                announce_selection(&app);
+            }
+            "x" | "hide" => {
+                app.hide_selected(false);
+                announce_message(&mut app);
+                announce_selection(&app);
+            }
+            "B" | "bundle" => {
+                app.save_diagnostics();
+                announce_message(&mut app);
+            }
+            "?" | "help" => say(&tr!(
+                "Commands: Enter or n next, p previous, a number selects that row, l list all, k kill, o open in browser, w focus window, r refresh, i toggle IDE helpers, x hide this process from now on, B save diagnostic bundle, q quit."
+            )),
+            other => match other.parse::<usize>() {
+                Ok(row) if (1..=app.processes.len()).contains(&row) => {
+                    app.list_state.select(Some(row - 1));
+                    announce_selection(&app);
+                }
+                _ => say(&tr!("Unknown command '{}'. Type ? for commands.", other)),
+            },
+        }
+    }
+}
+
+/// "Row 3 of 12: port 8080 TCP, node, PID 1234, ..." with every signal the
+/// TUI shows by colour or symbol spelled out.
+fn describe(app: &App, index: usize) -> String {
+    let process = &app.processes[index];
+    let mut text = tr!(
+        "Row {} of {}: port {} {}, {}, PID {}",
+        index + 1,
+        app.processes.len(),
+        process.port,
+        process.protocol,
+        process.name,
+        process.pid_label()
+    );
+    text.push_str(&if conflict::is_loopback(&process.address) {
+        tr!(", on {}, this machine only", process.address)
+    } else {
+        tr!(", on {}, reachable from the network", process.address)
+    });
+    if let Some(context) = &process.context {
+        text.push_str(&format!(", {}", context));
+    }
+    if let Some(nice) = process.nice.filter(|n| *n != 0) {
+        text.push_str(&tr!(", nice {}", nice));
+    }
+    if let Some(live) = process.connections.filter(|n| *n > 0) {
+        text.push_str(&tr!(", {} established connections", live));
+    }
+    if let Some(tag) = process.tag {
+        text.push_str(&tr!(", {} helper", tag));
+    }
+    if app.needs_escalation(process) {
+        text.push_str(&tr!(
+            ", owned by {}: killing needs sudo",
+            process.user.as_deref().unwrap_or_default()
+        ));
+    }
+    if let Some(risk) = app.risks.get(&process.pid).filter(|r| r.score > 0) {
+        text.push_str(&tr!(
+            ", kill risk {} ({})",
+            risk.score,
+            risk.reasons.join(", ")
+        ));
+    }
+    if let Some(conflict) = app.conflict_for(process) {
+        text.push_str(&tr!(", bind conflict: {}", conflict.explain()));
+    }
+    text
+}
+
+fn announce_selection(app: &App) {
+    match app.list_state.selected() {
+        Some(index) if index < app.processes.len() => say(&describe(app, index)),
+        _ => say(&tr!("No listeners.")),
+    }
+}
+
+fn announce_message(app: &mut App) {
+    if let Some(message) = app.message.take() {
+        app.log.record(Some(&message));
+        say(&message);
+    }
+}
+
+fn say(line: &str) {
+    println!("{}", line);
+}
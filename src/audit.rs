@@ -0,0 +1,107 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::clock::utc_timestamp;
+use crate::process::Signaller;
+use crate::{PortProcess, enrich};
+
+/// One signal rip sent, or tried to, or a command it ran to stop something
+/// (`signal` is then `run` and `name` the command).
+pub struct Entry<'a> {
+    pub pid: Option<u32>,
+    pub name: &'a str,
+    pub port: Option<u16>,
+    pub signal: &'a str,
+    /// Set when the signal went to this whole process group.
+    pub group: Option<u32>,
+    /// The part of rip that sent it: `tui`, `cli`, `pick`, `guard`,
+    /// `cleanup` or `serve`; `sudo` for one the TUI sent with `sudo kill`.
+    pub via: &'a str,
+}
+
+/// The append-only record of every signal rip has sent:
+/// `$XDG_DATA_HOME/rip/kills.log`, or `~/.local/share/rip/kills.log`.
+pub fn path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(base.join("rip").join("kills.log"))
+}
+
+/// Append a line for `entry` and how it went, e.g.
+///
+/// ```text
+/// 2026-03-01 14:02:11 UTC user=alice pid=4242 name="node" port=3000 signal=TERM via=tui result=ok
+/// ```
+///
+/// A log that cannot be written must not stop a kill, so failures are
+/// only reported on stderr, outside the TUI.
+pub fn record(entry: &Entry, result: &io::Result<()>) {
+    if let Err(e) = append(entry, result)
+        && entry.via != "tui"
+    {
+        eprintln!("{}", tr!("Could not write the kill log: {}", e));
+    }
+}
+
+fn append(entry: &Entry, result: &io::Result<()>) -> io::Result<()> {
+    let path = path().ok_or_else(|| io::Error::other(tr!("cannot find a home directory")))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let field = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let result = match result {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("{:?}", format!("failed: {}", e)),
+    };
+    let line = format!(
+        "{} UTC user={} pid={} name={:?} port={} signal={}{} via={} result={}\n",
+        utc_timestamp(),
+        user(),
+        field(entry.pid.map(|pid| pid.to_string())),
+        entry.name,
+        field(entry.port.map(|port| port.to_string())),
+        entry.signal,
+        entry
+            .group
+            .map(|pgid| format!(" group={}", pgid))
+            .unwrap_or_default(),
+        entry.via,
+        result
+    );
+    // One write per line, so lines from several rips never interleave.
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// SIGKILL `process` with `signaller` and wait for it to exit, like
+/// `process::kill`, and note it in the kill log.
+pub fn kill(signaller: &dyn Signaller, process: &PortProcess, via: &str) -> io::Result<()> {
+    let result = signaller.kill(process.pid);
+    let entry = Entry {
+        pid: Some(process.pid),
+        name: &process.name,
+        port: Some(process.port),
+        signal: "KILL",
+        group: None,
+        via,
+    };
+    record(&entry, &result);
+    result
+}
+
+/// Who ran rip, with the user behind sudo when there is one: `root(alice)`.
+fn user() -> String {
+    let me = enrich::current_user().unwrap_or_else(|| "?".to_string());
+    match std::env::var("SUDO_USER") {
+        Ok(sudo) if !sudo.is_empty() && sudo != me => format!("{}({})", me, sudo),
+        _ => me,
+    }
+}
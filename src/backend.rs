@@ -0,0 +1,756 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::scanner::{RawScan, ScanStats, scan_bind_probe, scan_lsof};
+use crate::{
+    PortProcess, UNKNOWN_PID, conflict, enrich, iphlpapi, json, libproc, netlink, portscan, procfs,
+    session, state,
+};
+
+/// A way of finding out which process holds which port.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    /// The operating system's own socket tables: the IP Helper API on
+    /// Windows, libproc on macOS.
+    Native,
+    Lsof,
+    /// `/proc/net/{tcp,udp}{,6}`, on Linux.
+    Proc,
+    /// iproute2's `ss -p`, on Linux.
+    Ss,
+    /// net-tools' `netstat -p`, on Linux systems without iproute2.
+    Netstat,
+    /// The kernel's socket diagnostics over netlink, on Linux.
+    Netlink,
+    /// Binding every port ourselves; finds occupied ports but not owners.
+    BindProbe,
+}
+
+/// In the order tried when nothing says which to use.
+pub const ALL: [Backend; 7] = [
+    Backend::Native,
+    Backend::Lsof,
+    Backend::Proc,
+    Backend::Ss,
+    Backend::Netstat,
+    Backend::Netlink,
+    Backend::BindProbe,
+];
+
+/// The backend in use, and why it was picked.
+static SELECTED: OnceLock<(Backend, String)> = OnceLock::new();
+
+impl Backend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Native => "native",
+            Backend::Lsof => "lsof",
+            Backend::Proc => "proc",
+            Backend::Ss => "ss",
+            Backend::Netstat => "netstat",
+            Backend::Netlink => "netlink",
+            Backend::BindProbe => "bind probe",
+        }
+    }
+
+    /// A `--backend` value, or a name recorded by `rip bench`.
+    pub fn parse(value: &str) -> Result<Backend, String> {
+        match value {
+            "probe" => Ok(Backend::BindProbe),
+            _ => ALL.into_iter().find(|b| b.name() == value).ok_or_else(|| {
+                tr!(
+                    "unknown backend '{}' (expected native, lsof, proc, ss, netstat, netlink or probe)",
+                    value
+                )
+            }),
+        }
+    }
+
+    /// Why this backend cannot run here, or `None` if it can.
+    pub fn unavailable(self) -> Option<String> {
+        if !self.supported() {
+            return Some(match self {
+                Backend::Native => tr!("Windows and macOS only"),
+                _ => tr!("Linux only"),
+            });
+        }
+        match self {
+            Backend::Native => None,
+            Backend::Lsof => (!on_path("lsof")).then(|| tr!("{} not found on PATH", "lsof")),
+            Backend::Proc => procfs::sockets().err().map(|e| e.to_string()),
+            Backend::Ss => (!on_path("ss")).then(|| tr!("{} not found on PATH", "ss")),
+            Backend::Netstat => {
+                (!on_path("netstat")).then(|| tr!("{} not found on PATH", "netstat"))
+            }
+            Backend::Netlink => netlink::dump().err().map(|e| e.to_string()),
+            Backend::BindProbe => None,
+        }
+    }
+
+    /// Whether this backend is built for the operating system rip runs on.
+    fn supported(self) -> bool {
+        match self {
+            Backend::Native => cfg!(any(windows, target_os = "macos")),
+            Backend::Proc | Backend::Ss | Backend::Netstat => cfg!(target_os = "linux"),
+            _ => true,
+        }
+    }
+
+    /// Whether rows from this backend say which process owns each port.
+    pub fn knows_owners(self) -> bool {
+        self != Backend::BindProbe
+    }
+
+    /// List listeners. `Err` only when the backend cannot run at all;
+    /// `range` is what the bind probe checks.
+    pub fn scan(self, range: &RangeInclusive<u16>) -> io::Result<(Vec<PortProcess>, ScanStats)> {
+        match self {
+            Backend::Native if cfg!(target_os = "macos") => scan_libproc().or_else(|e| {
+                let (processes, mut stats) = scan_lsof()?;
+                stats.failure = Some(tr!("{} failed: {}", "libproc", e));
+                Ok((processes, stats))
+            }),
+            Backend::Native => scan_native(),
+            Backend::Lsof => scan_lsof(),
+            Backend::Proc => scan_sockets(procfs::sockets, "proc", "/proc/net/{tcp,udp}{,6}"),
+            Backend::Ss => scan_ss(),
+            Backend::Netstat => scan_netstat(),
+            Backend::Netlink => scan_sockets(netlink::dump, "netlink", "netlink sock_diag"),
+            Backend::BindProbe => Ok(scan_bind_probe(range)),
+        }
+    }
+}
+
+/// Use the backend named by `--backend`, which must be able to run here.
+pub fn init(requested: Option<&str>) -> Result<(), String> {
+    let Some(requested) = requested else {
+        return Ok(());
+    };
+    let backend = Backend::parse(requested)?;
+    if let Some(reason) = backend.unavailable() {
+        return Err(tr!(
+            "the {} backend cannot run here: {}",
+            backend.name(),
+            reason
+        ));
+    }
+    let _ = SELECTED.set((backend, "--backend".to_string()));
+    Ok(())
+}
+
+/// The backend in use and why. Without `--backend` it is picked on first
+/// use: the one `rip bench` recorded if it can still run, else the first in
+/// `ALL` that can run and knows owners, else the bind probe.
+pub fn selected() -> &'static (Backend, String) {
+    SELECTED.get_or_init(|| {
+        let recorded = state::get("backend").and_then(|name| Backend::parse(&name).ok());
+        if let Some(backend) = recorded.filter(|b| b.unavailable().is_none()) {
+            return (backend, tr!("recorded by rip bench"));
+        }
+        let backend = ALL
+            .into_iter()
+            .find(|b| b.knows_owners() && b.unavailable().is_none())
+            .unwrap_or(Backend::BindProbe);
+        (backend, tr!("first available"))
+    })
+}
+
+/// Scan with the selected backend. When it cannot run, try the others that
+/// know owners in `ALL` order (lsof, then `/proc`, ss and netstat on Linux).
+/// Either way the reason ends up in `ScanStats::failure`, so the status bar
+/// says why the list looks the way it does. With the bind probe selected,
+/// or when nothing else can run, the list is empty and `backend` is
+/// `bind probe`: probing is up to the caller (`SystemScanner`).
+pub fn scan() -> (Vec<PortProcess>, ScanStats) {
+    let (selected, reason) = selected();
+    let selected = *selected;
+    let probe_only = || ScanStats {
+        backend: Backend::BindProbe.name(),
+        ..ScanStats::default()
+    };
+    // Asked for, rather than the last resort.
+    if selected == Backend::BindProbe && *reason != tr!("first available") {
+        return (Vec::new(), probe_only());
+    }
+    let mut errors = Vec::new();
+    let others = ALL
+        .into_iter()
+        .filter(|b| *b != selected && b.knows_owners() && b.supported());
+    for backend in std::iter::once(selected).chain(others) {
+        if backend == Backend::BindProbe {
+            continue;
+        }
+        if backend != selected
+            && let Some(reason) = backend.unavailable()
+        {
+            errors.push(format!("{}: {}", backend.name(), reason));
+            continue;
+        }
+        match backend.scan(&portscan::DEFAULT_RANGE) {
+            Ok((processes, mut stats)) => {
+                if !errors.is_empty() {
+                    errors.extend(stats.failure.take());
+                    stats.failure = Some(tr!("{}; using {}", errors.join("; "), backend.name()));
+                }
+                return (processes, stats);
+            }
+            Err(e) => errors.push(tr!("{} failed: {}", backend.name(), e)),
+        }
+    }
+    let mut stats = probe_only();
+    stats.failure = Some(tr!(
+        "no backend can tell which process holds a port ({}); showing occupied ports only",
+        errors.join("; ")
+    ));
+    (Vec::new(), stats)
+}
+
+/// Add a row for each port held by a socket whose owner rip may not see.
+/// An unprivileged lsof, ss or `/proc` walk skips other users' processes, so
+/// their ports would silently go missing; the kernel still reports the
+/// sockets themselves (with their uid) over netlink and in `/proc/net`.
+/// Linux only; returns how many rows were added.
+pub fn add_hidden(processes: &mut Vec<PortProcess>) -> usize {
+    if !cfg!(target_os = "linux") || is_root() {
+        return 0;
+    }
+    let Ok(sockets) = netlink::dump().or_else(|_| procfs::sockets()) else {
+        return 0;
+    };
+    let visible = procfs::socket_owners();
+    let users = procfs::user_names();
+    let mut added = 0;
+    for socket in sockets {
+        if socket.port == 0 || !socket.listening || visible.contains_key(&socket.inode) {
+            continue;
+        }
+        if processes
+            .iter()
+            .any(|p| p.port == socket.port && p.protocol == socket.protocol)
+        {
+            continue;
+        }
+        processes.push(PortProcess {
+            pid: UNKNOWN_PID,
+            port: socket.port,
+            protocol: socket.protocol.to_string(),
+            name: tr!("unknown (needs sudo)"),
+            address: socket.address,
+            listening: true,
+            user: Some(user_name(&users, socket.uid)),
+            nice: None,
+            connections: None,
+            context: None,
+            tag: None,
+        });
+        added += 1;
+    }
+    if added > 0 {
+        processes.sort_by_key(|p| (p.port, conflict::is_wildcard(&p.address)));
+    }
+    added
+}
+
+/// Ask for the sudo password (on the terminal) so that `scan_elevated` can
+/// run without prompting until sudo's credential cache expires.
+pub fn authenticate() -> io::Result<()> {
+    let status = Command::new("sudo").arg("-v").status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(tr!("sudo exited with {}", status)))
+    }
+}
+
+/// Scan as root: run this same binary under `sudo -n` with the selected
+/// backend and ask it for the list over `serve --stdio`. Fails rather than
+/// prompting once sudo's cached credentials have expired.
+pub fn scan_elevated() -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    let (backend, _) = selected();
+    let exe = std::env::current_exe()?;
+    let args = ["--backend", backend.name(), "serve", "--stdio"];
+
+    let started = Instant::now();
+    let mut child = Command::new("sudo")
+        .arg("-n")
+        .arg(&exe)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, r#"{{"jsonrpc":"2.0","id":1,"method":"list"}}"#)?;
+    }
+    let output = child.wait_with_output()?;
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        let reason = stderr.lines().next().unwrap_or_default();
+        return Err(io::Error::other(reason.to_string()));
+    }
+    let response = json::parse(stdout.lines().next().unwrap_or_default())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let Some(json::Value::Array(rows)) = response.get("result") else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr!("unexpected reply from sudo rip"),
+        ));
+    };
+    let processes = rows.iter().filter_map(session::parse_process).collect();
+
+    let stats = ScanStats {
+        backend: backend.name(),
+        command,
+        parse: started.elapsed(),
+        raw: RawScan {
+            command: format!("sudo -n {} {}", exe.display(), args.join(" ")),
+            status: output.status.to_string(),
+            stdout: stdout.into_owned(),
+            stderr: stderr.into_owned(),
+        },
+        ..ScanStats::default()
+    };
+    Ok((processes, stats))
+}
+
+#[cfg(unix)]
+fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_root() -> bool {
+    false
+}
+
+/// Whether `program` is an executable file in a `PATH` directory.
+fn on_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        dir.join(program).is_file()
+            || (cfg!(windows) && dir.join(format!("{}.exe", program)).is_file())
+    })
+}
+
+/// List listeners with `ss -tulpn`. Sockets of other users' processes have
+/// no owner unless rip runs as root, and are left out, as lsof does.
+fn scan_ss() -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    const SS_ARGS: [&str; 2] = ["-H", "-tulpn"];
+
+    let started = Instant::now();
+    let output = Command::new("ss").args(SS_ARGS).output()?;
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut rows = Vec::new();
+    let mut unparsed = 0;
+    let mut unowned = 0;
+    parse_ss(&stdout, &mut rows, &mut unparsed, &mut unowned);
+    let users = procfs::user_names();
+    for row in &mut rows {
+        row.user = procfs::process_uid(row.pid).map(|uid| user_name(&users, uid));
+    }
+    let parse = started.elapsed();
+
+    let failure = if !output.status.success() {
+        stderr.lines().next().map(str::to_string)
+    } else if unparsed > 0 {
+        Some(tr!("{} lines of ss output could not be parsed", unparsed))
+    } else {
+        None
+    };
+    let raw = RawScan {
+        command: format!("ss {}", SS_ARGS.join(" ")),
+        status: output.status.to_string(),
+        stdout: stdout.into_owned(),
+        stderr: stderr.into_owned(),
+    };
+    let (processes, mut stats) = finish(rows, "ss", command, parse, failure, raw);
+    stats.unowned = unowned;
+    Ok((processes, stats))
+}
+
+/// Add the sockets in `ss -H -tulpn` output to `rows`, one per owner,
+/// counting the lines that could not be read in `unparsed` and the sockets
+/// without a named owner in `unowned`. Users are left for the caller.
+pub fn parse_ss(
+    stdout: &str,
+    rows: &mut Vec<PortProcess>,
+    unparsed: &mut usize,
+    unowned: &mut usize,
+) {
+    for line in stdout.lines() {
+        // Netid State Recv-Q Send-Q Local Peer [Process]
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [netid, state, _, _, local, _, rest @ ..] = fields.as_slice() else {
+            *unparsed += 1;
+            continue;
+        };
+        let Some((host, port)) = local.rsplit_once(':') else {
+            *unparsed += 1;
+            continue;
+        };
+        let Ok(port) = port.parse::<u16>() else {
+            *unparsed += 1;
+            continue;
+        };
+        let host = without_scope(host);
+        let address = if conflict::is_wildcard(&host) {
+            "*".to_string()
+        } else {
+            host.to_string()
+        };
+        let owners = ss_users(&rest.join(" "));
+        if owners.is_empty() {
+            *unowned += 1;
+        }
+        for (pid, name) in owners {
+            rows.push(PortProcess {
+                pid,
+                port,
+                protocol: netid.to_uppercase(),
+                name,
+                address: address.clone(),
+                listening: *state != "ESTAB",
+                user: None,
+                nice: None,
+                connections: None,
+                context: None,
+                tag: None,
+            });
+        }
+    }
+}
+
+/// "127.0.0.53%lo" -> "127.0.0.53", "[fe80::1%eth0]" -> "[fe80::1]": the
+/// interface a socket is scoped or bound to is not part of its address.
+fn without_scope(host: &str) -> String {
+    match host.split_once('%') {
+        Some((address, scope)) if address.starts_with('[') && scope.ends_with(']') => {
+            format!("{}]", address)
+        }
+        Some((address, _)) => address.to_string(),
+        None => host.to_string(),
+    }
+}
+
+/// `users:(("nginx",pid=10,fd=6),("nginx",pid=11,fd=6))` -> each (pid, name).
+fn ss_users(process: &str) -> Vec<(u32, String)> {
+    let mut owners: Vec<(u32, String)> = Vec::new();
+    for entry in process.split("),(") {
+        let mut parts = entry.split(',');
+        let name = parts
+            .next()
+            .and_then(|name| name.split('"').nth(1))
+            .unwrap_or_default();
+        let pid = parts.find_map(|part| part.strip_prefix("pid=")?.parse().ok());
+        if let Some(pid) = pid.filter(|pid| !owners.iter().any(|(p, _)| p == pid)) {
+            owners.push((pid, name.to_string()));
+        }
+    }
+    owners
+}
+
+/// List listeners with `netstat -tulpn`, for systems that have net-tools
+/// but not iproute2. Like ss, it names only the current user's processes
+/// unless rip runs as root.
+fn scan_netstat() -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    const NETSTAT_ARGS: [&str; 1] = ["-tulpn"];
+
+    let started = Instant::now();
+    let output = Command::new("netstat").args(NETSTAT_ARGS).output()?;
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut rows = Vec::new();
+    let mut unparsed = 0;
+    let mut unowned = 0;
+    parse_netstat(&stdout, &mut rows, &mut unparsed, &mut unowned);
+    let users = procfs::user_names();
+    for row in &mut rows {
+        // netstat cuts names at 15 characters.
+        if let Some(name) = procfs::process_name(row.pid) {
+            row.name = name;
+        }
+        row.user = procfs::process_uid(row.pid).map(|uid| user_name(&users, uid));
+    }
+    let parse = started.elapsed();
+
+    let failure = if !output.status.success() {
+        stderr.lines().next().map(str::to_string)
+    } else if unparsed > 0 {
+        Some(tr!(
+            "{} lines of netstat output could not be parsed",
+            unparsed
+        ))
+    } else {
+        None
+    };
+    let raw = RawScan {
+        command: format!("netstat {}", NETSTAT_ARGS.join(" ")),
+        status: output.status.to_string(),
+        stdout: stdout.into_owned(),
+        stderr: stderr.into_owned(),
+    };
+    let (processes, mut stats) = finish(rows, "netstat", command, parse, failure, raw);
+    stats.unowned = unowned;
+    Ok((processes, stats))
+}
+
+/// Add the sockets in `netstat -tulpn` output to `rows`, counting the
+/// lines that could not be read in `unparsed` and the sockets of processes
+/// netstat would not name (`-`) in `unowned`. Names are netstat's, cut at
+/// 15 characters; users are left for the caller.
+pub fn parse_netstat(
+    stdout: &str,
+    rows: &mut Vec<PortProcess>,
+    unparsed: &mut usize,
+    unowned: &mut usize,
+) {
+    // Skip the "Active Internet connections" and column header lines.
+    for line in stdout
+        .lines()
+        .filter(|l| l.starts_with("tcp") || l.starts_with("udp"))
+    {
+        // Proto Recv-Q Send-Q Local Foreign [State] PID/Program
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [proto, _, _, local, _, rest @ ..] = fields.as_slice() else {
+            *unparsed += 1;
+            continue;
+        };
+        // UDP sockets have no state column.
+        let (state, owner) = match rest {
+            [owner] => ("", *owner),
+            [state, owner, ..] => (*state, *owner),
+            [] => {
+                *unparsed += 1;
+                continue;
+            }
+        };
+        let Some((host, port)) = local.rsplit_once(':') else {
+            *unparsed += 1;
+            continue;
+        };
+        let Ok(port) = port.parse::<u16>() else {
+            *unparsed += 1;
+            continue;
+        };
+        let host = without_scope(host);
+        let address = if conflict::is_wildcard(&host) {
+            "*".to_string()
+        } else if host.contains(':') {
+            format!("[{}]", host)
+        } else {
+            host.to_string()
+        };
+        // "1234/nginx: master", or "-" for another user's process.
+        let Some((Ok(pid), name)) = owner
+            .split_once('/')
+            .map(|(pid, name)| (pid.parse::<u32>(), name))
+        else {
+            *unowned += 1;
+            continue;
+        };
+        rows.push(PortProcess {
+            pid,
+            port,
+            protocol: proto.trim_end_matches('6').to_uppercase(),
+            name: name.to_string(),
+            address,
+            listening: state != "ESTABLISHED",
+            user: None,
+            nice: None,
+            connections: None,
+            context: None,
+            tag: None,
+        });
+    }
+}
+
+/// List listeners from the kernel's socket list (over netlink, or from
+/// `/proc/net`), mapping socket inodes to processes through `/proc/*/fd`.
+fn scan_sockets(
+    list: fn() -> io::Result<Vec<netlink::DiagSocket>>,
+    backend: &'static str,
+    source: &str,
+) -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    let started = Instant::now();
+    let sockets = list()?;
+    let owners = procfs::socket_owners();
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let users = procfs::user_names();
+    let sockets: Vec<_> = sockets
+        .into_iter()
+        .filter(|socket| socket.port > 0)
+        .collect();
+    let unowned = sockets
+        .iter()
+        .filter(|socket| !owners.contains_key(&socket.inode))
+        .count();
+    let rows = sockets
+        .into_iter()
+        .filter_map(|socket| {
+            let (pid, name) = owners.get(&socket.inode)?;
+            Some(PortProcess {
+                pid: *pid,
+                port: socket.port,
+                protocol: socket.protocol.to_string(),
+                name: name.clone(),
+                address: socket.address,
+                listening: socket.listening,
+                user: Some(user_name(&users, socket.uid)),
+                nice: None,
+                connections: None,
+                context: None,
+                tag: None,
+            })
+        })
+        .collect();
+    let parse = started.elapsed();
+
+    let raw = RawScan {
+        command: source.to_string(),
+        ..RawScan::default()
+    };
+    let (processes, mut stats) = finish(rows, backend, command, parse, None, raw);
+    stats.unowned = unowned;
+    Ok((processes, stats))
+}
+
+/// List listeners from the Windows IP Helper tables, which carry the owning
+/// PID of every socket.
+fn scan_native() -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    let started = Instant::now();
+    let sockets = iphlpapi::dump()?;
+    let names = iphlpapi::process_names();
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let rows = sockets
+        .into_iter()
+        .filter(|socket| socket.port > 0)
+        .map(|socket| PortProcess {
+            pid: socket.pid,
+            port: socket.port,
+            protocol: socket.protocol.to_string(),
+            name: names
+                .get(&socket.pid)
+                .cloned()
+                .unwrap_or_else(|| socket.pid.to_string()),
+            address: socket.address,
+            listening: true,
+            user: None,
+            nice: None,
+            connections: None,
+            context: None,
+            tag: None,
+        })
+        .collect();
+    let parse = started.elapsed();
+
+    let raw = RawScan {
+        command: "GetExtendedTcpTable/GetExtendedUdpTable".to_string(),
+        ..RawScan::default()
+    };
+    Ok(finish(rows, "native", command, parse, None, raw))
+}
+
+/// List listeners from every process's file table with libproc, on macOS.
+fn scan_libproc() -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    let started = Instant::now();
+    let sockets = libproc::sockets()?;
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let rows = sockets
+        .into_iter()
+        .map(|socket| PortProcess {
+            pid: socket.pid,
+            port: socket.port,
+            protocol: socket.protocol.to_string(),
+            name: socket.name,
+            address: socket.address,
+            listening: socket.listening,
+            user: socket.user,
+            nice: None,
+            connections: None,
+            context: None,
+            tag: None,
+        })
+        .collect();
+    let parse = started.elapsed();
+
+    let raw = RawScan {
+        command: "proc_listpids/proc_pidfdinfo".to_string(),
+        ..RawScan::default()
+    };
+    Ok(finish(rows, "native", command, parse, None, raw))
+}
+
+fn user_name(users: &HashMap<u32, String>, uid: u32) -> String {
+    users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
+}
+
+/// One row per port a process listens on, so IPv4 and IPv6 binds of the
+/// same port show once but a process on 3000 and 3001 shows twice. A
+/// process's outgoing sockets only show when it listens on nothing.
+pub fn one_row_per_port(mut rows: Vec<PortProcess>) -> Vec<PortProcess> {
+    rows.sort_by_key(|p| (p.pid, !p.listening, p.port));
+    let mut listening = HashSet::new();
+    let mut seen = HashSet::new();
+    rows.retain(|row| {
+        if row.listening {
+            listening.insert(row.pid);
+        } else if listening.contains(&row.pid) {
+            return false;
+        }
+        seen.insert((row.pid, row.port, row.protocol.clone()))
+    });
+    rows
+}
+
+/// Shape rows the way the lsof backend does: `one_row_per_port`, sorted by
+/// port with specific binds before wildcards; then enriched.
+fn finish(
+    mut rows: Vec<PortProcess>,
+    backend: &'static str,
+    command: Duration,
+    parse: Duration,
+    failure: Option<String>,
+    raw: RawScan,
+) -> (Vec<PortProcess>, ScanStats) {
+    let started = Instant::now();
+    rows.retain(|row| row.pid != UNKNOWN_PID);
+    let mut processes = one_row_per_port(rows);
+    processes.sort_by_key(|p| (p.port, conflict::is_wildcard(&p.address)));
+    let parse = parse + started.elapsed();
+
+    let started = Instant::now();
+    enrich::enrich(&mut processes);
+    let stats = ScanStats {
+        backend,
+        command,
+        parse,
+        enrich: started.elapsed(),
+        failure,
+        raw,
+        ..ScanStats::default()
+    };
+    (processes, stats)
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::backend::{self, Backend};
+use crate::cli::BenchOptions;
+use crate::{UNKNOWN_PID, lsof, state};
+
+struct Measurement {
+    backend: Backend,
+    times: Vec<Duration>,
+    listeners: BTreeSet<(u16, String)>,
+    knows_owners: bool,
+}
+
+/// Time every backend that works on this machine, compare what they found
+/// and record the fastest one that found everything in the state file.
+pub fn run(options: BenchOptions) -> io::Result<()> {
+    let mut results = Vec::new();
+    for backend in backend::ALL {
+        if let Some(reason) = backend.unavailable() {
+            println!("{:<12} {}", backend.name(), tr!("unavailable: {}", reason));
+            continue;
+        }
+        let mut times = Vec::new();
+        let mut last = Vec::new();
+        for _ in 0..options.runs {
+            let started = Instant::now();
+            match backend.scan(&options.scan_range) {
+                Ok((processes, _)) => last = processes,
+                Err(e) => {
+                    println!("{:<12} {}", backend.name(), tr!("unavailable: {}", e));
+                    break;
+                }
+            }
+            times.push(started.elapsed());
+        }
+        if times.len() < options.runs {
+            continue;
+        }
+        times.sort();
+        results.push(Measurement {
+            backend,
+            times,
+            listeners: last.iter().map(|p| (p.port, p.protocol.clone())).collect(),
+            knows_owners: backend.knows_owners() && last.iter().all(|p| p.pid != UNKNOWN_PID),
+        });
+    }
+
+    let everything: BTreeSet<(u16, String)> = results
+        .iter()
+        .flat_map(|r| r.listeners.iter().cloned())
+        .collect();
+
+    println!(
+        "{:<12} {:>10} {:>10} {:>9}  {}",
+        tr!("BACKEND"),
+        tr!("MIN"),
+        tr!("MEDIAN"),
+        tr!("FOUND"),
+        tr!("NOTES")
+    );
+    for result in &results {
+        let missing: Vec<String> = everything
+            .difference(&result.listeners)
+            .map(|(port, protocol)| format!(":{}/{}", port, protocol))
+            .collect();
+        let mut notes = Vec::new();
+        if !result.knows_owners {
+            notes.push(tr!("no owners"));
+        }
+        if !missing.is_empty() {
+            notes.push(tr!("missed {}", missing.join(" ")));
+        }
+        println!(
+            "{:<12} {:>10} {:>10} {:>9}  {}",
+            result.backend.name(),
+            ms(result.times[0]),
+            ms(result.times[result.times.len() / 2]),
+            result.listeners.len(),
+            notes.join("; ")
+        );
+    }
+
+    // Owners are what rip is for; among backends that know them, prefer
+    // completeness, then speed.
+    let best = results.iter().filter(|r| r.knows_owners).min_by_key(|r| {
+        (
+            everything.difference(&r.listeners).count(),
+            r.times[r.times.len() / 2],
+        )
+    });
+    match best {
+        Some(best) => {
+            let path = state::set("backend", best.backend.name())?;
+            println!();
+            println!(
+                "{}",
+                tr!(
+                    "Recommended backend: {} (recorded in {})",
+                    best.backend.name(),
+                    path.display()
+                )
+            );
+        }
+        None => println!(
+            "\n{}",
+            tr!("No backend could tell which process owns each port.")
+        ),
+    }
+    if Backend::Lsof.unavailable().is_none() {
+        bench_lsof(&options)?;
+    }
+    Ok(())
+}
+
+/// Time each way of invoking lsof and record the fastest one that found
+/// as many listeners as any other, for the lsof backend to use from then on.
+fn bench_lsof(options: &BenchOptions) -> io::Result<()> {
+    let mut results = Vec::new();
+    for variant in lsof::VARIANTS {
+        let mut times = Vec::new();
+        let mut found = 0;
+        for _ in 0..options.runs {
+            let started = Instant::now();
+            match crate::scanner::scan_lsof_with(variant) {
+                Ok((processes, stats)) if stats.failure.is_none() => found = processes.len(),
+                _ => break,
+            }
+            times.push(started.elapsed());
+        }
+        if times.len() < options.runs {
+            continue;
+        }
+        times.sort();
+        results.push((variant, times, found));
+    }
+
+    println!();
+    println!(
+        "{:<12} {:>10} {:>10} {:>9}  {}",
+        tr!("LSOF ARGS"),
+        tr!("MIN"),
+        tr!("MEDIAN"),
+        tr!("FOUND"),
+        tr!("NOTES")
+    );
+    for (variant, times, found) in &results {
+        let passes: Vec<String> = variant.passes.iter().map(|args| args.join(" ")).collect();
+        println!(
+            "{:<12} {:>10} {:>10} {:>9}  {}",
+            variant.name,
+            ms(times[0]),
+            ms(times[times.len() / 2]),
+            found,
+            passes.join("; ")
+        );
+    }
+
+    let most = results.iter().map(|(_, _, found)| *found).max();
+    let best = results
+        .iter()
+        .filter(|(_, _, found)| Some(*found) == most)
+        .min_by_key(|(_, times, _)| times[times.len() / 2]);
+    if let Some((variant, _, _)) = best {
+        let path = state::set("lsof_variant", variant.name)?;
+        println!();
+        println!(
+            "{}",
+            tr!(
+                "Recommended lsof invocation: {} (recorded in {})",
+                variant.name,
+                path.display()
+            )
+        );
+    }
+    Ok(())
+}
+
+fn ms(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
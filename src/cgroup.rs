@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// cgroup v1 reports "no limit" as a huge page-aligned number.
+const V1_UNLIMITED: u64 = 1 << 62;
+
+/// The resource limits that apply to a process through its cgroup. Limits
+/// are inherited, so each is the tightest one between the process's own
+/// group and the root.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// The process's cgroup, e.g. "/system.slice/nginx.service".
+    pub group: String,
+    pub memory_max: Option<u64>,
+    pub memory_current: Option<u64>,
+    /// CPU time allowed per period, as (quota, period) in microseconds.
+    pub cpu_max: Option<(u64, u64)>,
+}
+
+impl Limits {
+    /// "1.5 CPUs", from the quota and period.
+    pub fn cpus(&self) -> Option<f64> {
+        self.cpu_max
+            .map(|(quota, period)| quota as f64 / period as f64)
+    }
+}
+
+/// Read the cgroup limits of `pid`. `None` off Linux, or when the process
+/// or cgroup filesystem cannot be read.
+pub fn limits(pid: u32) -> Option<Limits> {
+    let membership = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+
+    // cgroup v2 has a single "0::/path" line; v1 has one line per
+    // controller ("4:memory:/path"). Hybrid hosts have both, and the v1
+    // controllers are the ones that enforce limits there.
+    let mut v2 = None;
+    let mut memory = None;
+    let mut cpu = None;
+    for line in membership.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+        if controllers.is_empty() {
+            v2 = Some(path);
+        }
+        for controller in controllers.split(',') {
+            match controller {
+                "memory" => memory = Some(path),
+                "cpu" => cpu = Some(path),
+                _ => {}
+            }
+        }
+    }
+
+    if memory.is_none() && cpu.is_none() {
+        let group = v2?;
+        let dir = group_dir(Path::new(CGROUP_ROOT), group);
+        return Some(Limits {
+            group: group.to_string(),
+            memory_max: tightest(&dir, CGROUP_ROOT, |d| read_u64(&d.join("memory.max"))),
+            memory_current: read_u64(&dir.join("memory.current")),
+            cpu_max: tightest_cpu(&dir, CGROUP_ROOT, |d| {
+                let text = fs::read_to_string(d.join("cpu.max")).ok()?;
+                let mut fields = text.split_whitespace();
+                Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+            }),
+        });
+    }
+
+    let memory_root = Path::new(CGROUP_ROOT).join("memory");
+    let cpu_root = Path::new(CGROUP_ROOT).join("cpu");
+    let memory_dir = memory.map(|group| group_dir(&memory_root, group));
+    let cpu_dir = cpu.map(|group| group_dir(&cpu_root, group));
+    Some(Limits {
+        group: memory.or(cpu).unwrap_or("/").to_string(),
+        memory_max: memory_dir.as_ref().and_then(|dir| {
+            tightest(dir, &memory_root, |d| {
+                read_u64(&d.join("memory.limit_in_bytes")).filter(|n| *n < V1_UNLIMITED)
+            })
+        }),
+        memory_current: memory_dir
+            .as_ref()
+            .and_then(|dir| read_u64(&dir.join("memory.usage_in_bytes"))),
+        cpu_max: cpu_dir.as_ref().and_then(|dir| {
+            tightest_cpu(dir, &cpu_root, |d| {
+                let quota = read_u64(&d.join("cpu.cfs_quota_us"))?;
+                Some((quota, read_u64(&d.join("cpu.cfs_period_us"))?))
+            })
+        }),
+    })
+}
+
+/// The directory for `group` under `root`. Inside a container the cgroup
+/// filesystem is usually mounted at the container's own group, so paths
+/// from `/proc/PID/cgroup` carry a prefix that does not exist here; drop
+/// leading components until the directory is found.
+fn group_dir(root: &Path, group: &str) -> PathBuf {
+    let parts: Vec<&str> = group.split('/').filter(|p| !p.is_empty()).collect();
+    (0..parts.len())
+        .map(|skip| {
+            parts[skip..]
+                .iter()
+                .fold(root.to_path_buf(), |dir, p| dir.join(p))
+        })
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| root.to_path_buf())
+}
+
+/// The smallest value `read` finds in `dir` or any ancestor up to `root`.
+fn tightest(
+    dir: &Path,
+    root: impl AsRef<Path>,
+    read: impl Fn(&Path) -> Option<u64>,
+) -> Option<u64> {
+    ancestors(dir, root.as_ref()).filter_map(&read).min()
+}
+
+/// Like `tightest`, comparing CPU limits by the share of a CPU they allow.
+fn tightest_cpu(
+    dir: &Path,
+    root: impl AsRef<Path>,
+    read: impl Fn(&Path) -> Option<(u64, u64)>,
+) -> Option<(u64, u64)> {
+    ancestors(dir, root.as_ref())
+        .filter_map(&read)
+        .filter(|(_, period)| *period > 0)
+        .min_by(|(q1, p1), (q2, p2)| {
+            (*q1 as f64 / *p1 as f64).total_cmp(&(*q2 as f64 / *p2 as f64))
+        })
+}
+
+fn ancestors<'a>(dir: &'a Path, root: &'a Path) -> impl Iterator<Item = &'a Path> {
+    dir.ancestors().take_while(move |d| d.starts_with(root))
+}
+
+/// A number from a cgroup file; "max" and "-1" (no limit) read as `None`.
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::cli::CleanupOptions;
+use crate::{PortProcess, UNKNOWN_PID, config, enrich, process, risk};
+
+/// One process to clean up, with every port it listens on.
+struct Candidate {
+    process: PortProcess,
+    ports: Vec<String>,
+    age: Duration,
+}
+
+/// `rip cleanup`: list the listeners matching `--match` that have been
+/// running longer than `--older-than`, then kill them after one question
+/// (none with `--yes`). Protected processes and the config's `never_kill`
+/// list are left alone; `--safe` only lists.
+pub fn run(options: CleanupOptions, safe: bool) -> io::Result<()> {
+    let config = config::load_or_exit();
+    let listeners: Vec<PortProcess> = crate::get_port_processes()
+        .into_iter()
+        .filter(|p| p.pid != UNKNOWN_PID)
+        .filter(|p| options.filter.as_ref().is_none_or(|f| f.matches(p)))
+        .collect();
+    let risks = risk::assess(&listeners, &config);
+
+    let mut by_pid: BTreeMap<u32, Candidate> = BTreeMap::new();
+    for p in &listeners {
+        if let Some(candidate) = by_pid.get_mut(&p.pid) {
+            candidate.ports.push(format!(":{}/{}", p.port, p.protocol));
+            continue;
+        }
+        let Some(age) = enrich::running_for(p.pid).filter(|age| *age >= options.older_than) else {
+            continue;
+        };
+        by_pid.insert(
+            p.pid,
+            Candidate {
+                process: p.clone(),
+                ports: vec![format!(":{}/{}", p.port, p.protocol)],
+                age,
+            },
+        );
+    }
+
+    let mut doomed = Vec::new();
+    for candidate in by_pid.into_values() {
+        let p = &candidate.process;
+        let kept = config.never_kill_refusal(p).or_else(|| {
+            risks
+                .get(&p.pid)
+                .filter(|risk| risk.protected)
+                .map(|risk| tr!("protected ({})", risk.reasons.join(", ")))
+        });
+        let line = format!(
+            "{:>8}  {} (PID: {}) {}",
+            age(candidate.age),
+            p.name,
+            p.pid,
+            candidate.ports.join(" ")
+        );
+        match kept {
+            Some(reason) => eprintln!("{}  [{}]", line, tr!("kept: {}", reason)),
+            None => {
+                eprintln!("{}", line);
+                doomed.push(candidate);
+            }
+        }
+    }
+    if doomed.is_empty() {
+        eprintln!(
+            "{}",
+            tr!(
+                "Nothing to clean up: no listener has been running longer than {}.",
+                age(options.older_than)
+            )
+        );
+        return Ok(());
+    }
+    if safe {
+        eprintln!(
+            "{}",
+            tr!("Safe mode: would kill these {} processes", doomed.len())
+        );
+        return Ok(());
+    }
+    if !options.assume_yes {
+        eprint!("{}", tr!("Kill these {} processes? [y/N] ", doomed.len()));
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            eprintln!("{}", tr!("Not killed."));
+            std::process::exit(1);
+        }
+    }
+
+    let mut failed = false;
+    for candidate in &doomed {
+        let p = &candidate.process;
+        match crate::audit::kill(&process::System, p, "cleanup") {
+            Ok(()) => println!(
+                "{}",
+                tr!(
+                    "Killed {} (PID: {}), up for {}",
+                    p.name,
+                    p.pid,
+                    age(candidate.age)
+                )
+            ),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    tr!("Could not kill {} (PID: {}): {}", p.name, p.pid, e)
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `3d4h`, `2h15m`, `40m` or `25s`: the two largest units.
+fn age(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d{}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h{}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
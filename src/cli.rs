@@ -0,0 +1,661 @@
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::{PortProcess, format, heatmap, portscan, session, uri};
+
+pub const USAGE: &str = "\
+Usage:
+  rip [OPTIONS]               Open the interactive TUI
+  rip --list [--format F]     Print every listener and exit, as text, json,
+                              yaml, csv or markdown (--json: --format json)
+  rip <PORT>                  Kill whatever listens on PORT
+  rip <PORT> --dry-run        Only show what holds PORT
+  rip <PORT> --ui             Open the TUI with PORT selected
+  rip pick [QUERY]            Pick a listener to kill from a numbered list,
+                              by number or fuzzy search, without the TUI
+  rip handle-uri <URI>        Open the TUI asking to kill what a
+                              rip://kill?port=PORT link names, in safe mode
+  rip handle-uri --register   Make rip the handler for rip:// links
+  rip guard <PORT> [OPTIONS]  Keep PORT free by killing anything that binds it
+  rip keepalive --port <PORT> [OPTIONS] -- <COMMAND>...
+                              Restart COMMAND whenever nothing listens on PORT
+  rip cleanup --older-than <AGE> [OPTIONS]
+                              Kill listeners that have been running longer
+                              than AGE, after listing them
+  rip serve --stdio           Speak JSON-RPC on stdin/stdout (list, watch, kill)
+  rip bench [OPTIONS]         Time each scanning backend and record the fastest
+  rip replay <FILE>           Step through a session saved with --record
+  rip doctor                  Show which scanning backends work here and which
+                              one is used
+  rip rules test              Show what the config's ignore, protect,
+                              never_kill and [graceful] rules would do to
+                              the current listeners, without doing it
+  rip monitor [OPTIONS]       Log which process takes and frees each port
+  rip monitor report --port <PORT> [--file <FILE>]
+                              Show when PORT was held, and by what
+
+TUI options:
+      --hide-ide              Start with IDE/debugger helper listeners hidden
+      --show-ephemeral        Include outgoing (connected) sockets on ephemeral
+                              ports, hidden by default (toggle with e)
+      --a11y                  Line-based interface for screen readers (no
+                              box drawing or colour-only cues)
+      --debug                 Show the timing overlay (toggle with F12)
+      --low-power             Poll less often and pause probes (default: on
+                              when running on battery)
+      --no-low-power          Never enter low-power mode automatically
+      --scan-range <START-END>
+                              Ports to probe when no backend can name their
+                              owners (default: 1-10000)
+      --heatmap-ranges <LIST> Ranges for the heatmap view, e.g.
+                              1-1024,3000-9000,ephemeral (the default)
+      --record <FILE>         Save every list shown, key pressed and message
+                              to FILE, for review with `rip replay`
+      --cwd <DIR>             Only show processes working under DIR (toggle
+                              with W); also narrows --list
+
+Guard options:
+  -y, --yes                   Kill without asking for confirmation
+  -i, --interval <SECONDS>    Seconds between checks (default: 1)
+
+Bench options:
+  -n, --runs <N>              Scans per backend (default: 3)
+      --scan-range <START-END>
+                              Ports for the bind probe backend (default: 1-10000)
+
+Monitor options:
+  -i, --interval <SECONDS>    Seconds between checks (default: 5)
+      --file <FILE>           Timeline file (default: `timeline` next to the
+                              state file in ~/.local/state/rip)
+      --alert-over <N>        Alert when more than N ports are listened on
+      --alert-match <TEXT>    Count only listeners whose program name contains
+                              TEXT, or whose port is in a START-END range
+      --alert-exec <COMMAND>  Run COMMAND through the shell to alert, with
+                              RIP_LISTENERS and RIP_THRESHOLD set (default: a
+                              desktop notification)
+      --notify-new            Send a desktop notification whenever a port
+                              starts being listened on from other machines
+
+Keepalive options:
+  -p, --port <PORT>           Port the command is expected to listen on
+  -i, --interval <SECONDS>    Seconds between checks (default: 1)
+  -g, --grace <SECONDS>       Startup time allowed before restarting (default: 5)
+
+Cleanup options:
+      --older-than <AGE>      Only processes running longer than AGE, e.g. 30m,
+                              2h or 1d (required)
+      --match <TEXT>          Only listeners whose program name contains TEXT,
+                              or whose port is in a START-END range
+  -y, --yes                   Kill without asking for confirmation
+
+      --safe                  Observe first, for production servers: never kill
+                              root-owned, system or supervised processes, ask
+                              before every kill, and only show what `rip
+                              <PORT>`, `pick`, `guard` and `serve` would kill
+      --lang <LANG>           Language for messages (en, de, es; default:
+                              from LANG/LC_MESSAGES)
+      --backend <NAME>        How to find listeners: native, lsof, proc, ss,
+                              netstat, netlink or probe (default: the one
+                              `rip bench` recorded, else the first that works)
+  -h, --help                  Show this help";
+
+pub enum Command {
+    Tui(TuiOptions),
+    Guard(GuardOptions),
+    Keepalive(KeepaliveOptions),
+    Cleanup(CleanupOptions),
+    Bench(BenchOptions),
+    Monitor(MonitorOptions),
+    MonitorReport(ReportOptions),
+    /// `--list`, optionally only processes working under a directory.
+    List(&'static dyn format::Formatter, Option<PathBuf>),
+    Free(u16),
+    Lookup(u16),
+    Pick(Option<String>),
+    RegisterUri,
+    Replay(PathBuf),
+    Doctor,
+    RulesTest,
+    ServeStdio,
+    Help,
+}
+
+pub struct TuiOptions {
+    /// Start with IDE/debugger helper listeners hidden.
+    pub hide_ide: bool,
+    /// Include outgoing sockets on ephemeral ports.
+    pub show_ephemeral: bool,
+    /// Ports to probe for occupancy when lsof is unavailable.
+    pub scan_range: RangeInclusive<u16>,
+    /// Force low-power mode on or off; `None` follows the battery state.
+    pub low_power: Option<bool>,
+    /// Start with the debug overlay visible.
+    pub debug: bool,
+    /// Port ranges drawn as strips in the heatmap view.
+    pub heatmap_ranges: Vec<RangeInclusive<u16>>,
+    /// Use the line-based screen-reader interface instead of the TUI.
+    pub a11y: bool,
+    /// Start with the listener on this port selected.
+    pub select_port: Option<u16>,
+    /// Ask to kill that listener straight away (`rip handle-uri`).
+    pub kill_selected: bool,
+    /// Record the session to this file.
+    pub record: Option<PathBuf>,
+    /// `--cwd`: start showing only processes working under this directory.
+    pub workspace: Option<PathBuf>,
+    /// Show this recorded session instead of scanning.
+    pub replay: Option<session::Replay>,
+    /// `--safe`: refuse to kill protected processes and always confirm.
+    pub safe: bool,
+}
+
+impl Default for TuiOptions {
+    fn default() -> Self {
+        TuiOptions {
+            hide_ide: false,
+            show_ephemeral: false,
+            scan_range: portscan::DEFAULT_RANGE,
+            low_power: None,
+            debug: false,
+            heatmap_ranges: heatmap::default_ranges(),
+            a11y: false,
+            select_port: None,
+            kill_selected: false,
+            record: None,
+            workspace: None,
+            replay: None,
+            safe: false,
+        }
+    }
+}
+
+pub struct GuardOptions {
+    pub port: u16,
+    pub assume_yes: bool,
+    pub interval: Duration,
+    /// Only log what would be evicted (`--safe`).
+    pub dry_run: bool,
+}
+
+pub struct BenchOptions {
+    pub runs: usize,
+    pub scan_range: RangeInclusive<u16>,
+}
+
+pub struct MonitorOptions {
+    pub interval: Duration,
+    /// Timeline file; `None` for the default next to the state file.
+    pub file: Option<PathBuf>,
+    pub alert: Option<Alert>,
+    /// Notify about each newly exposed listener.
+    pub notify_new: bool,
+}
+
+/// Raise an alert when more than `over` listening ports match `filter`.
+pub struct Alert {
+    pub over: usize,
+    pub filter: Option<ListenerFilter>,
+    /// Shell command to run instead of a desktop notification.
+    pub exec: Option<String>,
+}
+
+/// `--alert-match` and `--match`: which listeners count.
+pub enum ListenerFilter {
+    /// Case-insensitive substring of the program name.
+    Name(String),
+    Ports(RangeInclusive<u16>),
+}
+
+impl ListenerFilter {
+    /// A `START-END` port range, or else text to look for in names.
+    fn parse(value: &str) -> ListenerFilter {
+        match portscan::parse_range(value) {
+            Ok(range) => ListenerFilter::Ports(range),
+            Err(_) => ListenerFilter::Name(value.to_lowercase()),
+        }
+    }
+
+    pub fn matches(&self, process: &PortProcess) -> bool {
+        match self {
+            ListenerFilter::Name(text) => process.name.to_lowercase().contains(text),
+            ListenerFilter::Ports(range) => range.contains(&process.port),
+        }
+    }
+}
+
+pub struct CleanupOptions {
+    /// Only processes running at least this long are killed.
+    pub older_than: Duration,
+    pub filter: Option<ListenerFilter>,
+    /// Kill without asking first.
+    pub assume_yes: bool,
+}
+
+pub struct ReportOptions {
+    pub port: u16,
+    pub file: Option<PathBuf>,
+}
+
+pub struct KeepaliveOptions {
+    pub port: u16,
+    pub interval: Duration,
+    pub grace: Duration,
+    pub command: Vec<String>,
+}
+
+/// Remove a global option such as `--lang <LANG>` (or `--lang=LANG`) from
+/// `args`. Anything after a `--` belongs to another program and is left
+/// alone.
+pub fn take_global(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, String> {
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    let prefix = format!("{}=", flag);
+    let Some(i) = args[..end]
+        .iter()
+        .position(|a| a == flag || a.starts_with(&prefix))
+    else {
+        return Ok(None);
+    };
+
+    let arg = args.remove(i);
+    match arg.strip_prefix(&prefix) {
+        Some(value) => Ok(Some(value.to_string())),
+        None if i < end - 1 => Ok(Some(args.remove(i))),
+        None => Err(tr!("{} needs a value", flag)),
+    }
+}
+
+/// Remove a global switch such as `--safe` from `args`, returning whether it
+/// was there. Like `take_global`, it stops at `--`.
+pub fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    match args[..end].iter().position(|a| a == flag) {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+
+    if args.peek().is_none_or(|arg| arg.starts_with('-')) {
+        return parse_tui(args);
+    }
+    if let Some(port) = args.peek().and_then(|arg| parse_port(arg).ok()) {
+        args.next();
+        return parse_port_lookup(port, args);
+    }
+
+    match args.next().as_deref() {
+        Some("guard") => parse_guard(args),
+        Some("keepalive") => parse_keepalive(args),
+        Some("cleanup") => parse_cleanup(args),
+        Some("bench") => parse_bench(args),
+        Some("monitor") => parse_monitor(args),
+        Some("replay") => match (args.next(), args.next()) {
+            (Some(path), None) if path == "-h" || path == "--help" => Ok(Command::Help),
+            (Some(path), None) => Ok(Command::Replay(PathBuf::from(path))),
+            (Some(_), Some(other)) => Err(tr!("unexpected argument '{}'", other)),
+            (None, _) => Err(tr!("replay needs a file")),
+        },
+        Some("pick") => {
+            let words: Vec<String> = args.collect();
+            if words.iter().any(|w| w == "-h" || w == "--help") {
+                return Ok(Command::Help);
+            }
+            Ok(Command::Pick((!words.is_empty()).then(|| words.join(" "))))
+        }
+        Some("handle-uri") => match (args.next(), args.next()) {
+            (Some(arg), None) if arg == "-h" || arg == "--help" => Ok(Command::Help),
+            (Some(arg), None) if arg == "--register" => Ok(Command::RegisterUri),
+            // A link can come from any web page, so it only ever opens the
+            // confirmation, in safe mode.
+            (Some(link), None) => Ok(Command::Tui(TuiOptions {
+                select_port: Some(uri::parse(&link)?),
+                kill_selected: true,
+                safe: true,
+                ..TuiOptions::default()
+            })),
+            (Some(_), Some(other)) => Err(tr!("unexpected argument '{}'", other)),
+            (None, _) => Err(tr!("handle-uri needs a rip:// link or --register")),
+        },
+        Some("doctor") => match args.next() {
+            Some(arg) if arg == "-h" || arg == "--help" => Ok(Command::Help),
+            Some(arg) => Err(tr!("unexpected argument '{}'", arg)),
+            None => Ok(Command::Doctor),
+        },
+        Some("rules") => match (args.next().as_deref(), args.next()) {
+            (Some("-h" | "--help"), None) => Ok(Command::Help),
+            (Some("test"), None) => Ok(Command::RulesTest),
+            (Some("test"), Some(other)) => Err(tr!("unexpected argument '{}'", other)),
+            (Some(other), _) => Err(tr!("unknown rules command '{}'", other)),
+            (None, _) => Err(tr!("rules needs a command (test)")),
+        },
+        Some("serve") => match args.next().as_deref() {
+            Some("--stdio") => Ok(Command::ServeStdio),
+            Some(other) => Err(tr!("unexpected argument '{}'", other)),
+            None => Err(tr!("serve needs a transport (--stdio)")),
+        },
+        Some(other) => Err(tr!("unknown command '{}'", other)),
+        None => unreachable!("checked by peek above"),
+    }
+}
+
+fn parse_tui(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = TuiOptions::default();
+    let mut list = false;
+    let mut format = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => list = true,
+            "--json" => format = Some(format::by_name("json")?),
+            "--format" => {
+                let value = args.next().ok_or_else(|| tr!("--format needs a value"))?;
+                format = Some(format::by_name(&value)?);
+            }
+            "--hide-ide" => options.hide_ide = true,
+            "--show-ephemeral" => options.show_ephemeral = true,
+            "--debug" => options.debug = true,
+            "--a11y" => options.a11y = true,
+            "--low-power" => options.low_power = Some(true),
+            "--no-low-power" => options.low_power = Some(false),
+            "--scan-range" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--scan-range needs a value"))?;
+                options.scan_range = portscan::parse_range(&value)?;
+            }
+            "--heatmap-ranges" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--heatmap-ranges needs a value"))?;
+                options.heatmap_ranges = heatmap::parse_ranges(&value)?;
+            }
+            "--cwd" => {
+                let value = args.next().ok_or_else(|| tr!("--cwd needs a value"))?;
+                let dir =
+                    std::fs::canonicalize(&value).map_err(|e| tr!("--cwd {}: {}", value, e))?;
+                options.workspace = Some(dir);
+            }
+            "--record" => {
+                let value = args.next().ok_or_else(|| tr!("--record needs a value"))?;
+                options.record = Some(PathBuf::from(value));
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(tr!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    if list {
+        return Ok(Command::List(
+            format.unwrap_or(format::FORMATTERS[0]),
+            options.workspace,
+        ));
+    }
+    if format.is_some() {
+        return Err(tr!("--format and --json need --list"));
+    }
+    Ok(Command::Tui(options))
+}
+
+/// `rip <PORT>` kills what holds the port, `rip <PORT> --dry-run` only
+/// prints it and `rip <PORT> --ui` opens the TUI with that row selected.
+fn parse_port_lookup(port: u16, args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut rest: Vec<String> = args.collect();
+    let Some(ui) = rest.iter().position(|arg| arg == "--ui") else {
+        return match rest.first().map(String::as_str) {
+            None => Ok(Command::Free(port)),
+            Some("-n" | "--dry-run") if rest.len() == 1 => Ok(Command::Lookup(port)),
+            Some(arg) => Err(tr!("unexpected argument '{}'", arg)),
+        };
+    };
+    rest.remove(ui);
+
+    match parse_tui(rest.into_iter())? {
+        Command::Tui(mut options) => {
+            options.select_port = Some(port);
+            Ok(Command::Tui(options))
+        }
+        other => Ok(other),
+    }
+}
+
+fn parse_guard(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut port = None;
+    let mut assume_yes = false;
+    let mut interval = Duration::from_secs(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-y" | "--yes" => assume_yes = true,
+            "-i" | "--interval" => {
+                interval = parse_seconds(args.next(), "--interval")?;
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            _ if port.is_none() => port = Some(parse_port(&arg)?),
+            _ => return Err(tr!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(Command::Guard(GuardOptions {
+        port: port.ok_or_else(|| tr!("guard needs a port"))?,
+        assume_yes,
+        interval,
+        dry_run: false,
+    }))
+}
+
+fn parse_bench(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = BenchOptions {
+        runs: 3,
+        scan_range: portscan::DEFAULT_RANGE,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-n" | "--runs" => {
+                let value = args.next().ok_or_else(|| tr!("--runs needs a value"))?;
+                options.runs = value
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| tr!("invalid {} value '{}'", "--runs", value))?;
+            }
+            "--scan-range" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--scan-range needs a value"))?;
+                options.scan_range = portscan::parse_range(&value)?;
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(tr!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(Command::Bench(options))
+}
+
+fn parse_monitor(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.peekable();
+    let report = args.next_if(|arg| arg == "report").is_some();
+    let mut interval = Duration::from_secs(5);
+    let mut port = None;
+    let mut file = None;
+    let mut over = None;
+    let mut filter = None;
+    let mut exec = None;
+    let mut notify_new = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--interval" if !report => {
+                interval = parse_seconds(args.next(), "--interval")?;
+            }
+            "--alert-over" if !report => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--alert-over needs a value"))?;
+                over = Some(
+                    value
+                        .parse()
+                        .map_err(|_| tr!("invalid {} value '{}'", "--alert-over", value))?,
+                );
+            }
+            "--alert-match" if !report => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--alert-match needs a value"))?;
+                filter = Some(ListenerFilter::parse(&value));
+            }
+            "--alert-exec" if !report => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--alert-exec needs a value"))?;
+                exec = Some(value);
+            }
+            "--notify-new" if !report => notify_new = true,
+            "-p" | "--port" if report => {
+                let value = args.next().ok_or_else(|| tr!("--port needs a value"))?;
+                port = Some(parse_port(&value)?);
+            }
+            "--file" => {
+                let value = args.next().ok_or_else(|| tr!("--file needs a value"))?;
+                file = Some(PathBuf::from(value));
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(tr!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    if !report {
+        let alert = match over {
+            Some(over) => Some(Alert { over, filter, exec }),
+            None if filter.is_some() || exec.is_some() => {
+                return Err(tr!("--alert-match and --alert-exec need --alert-over"));
+            }
+            None => None,
+        };
+        return Ok(Command::Monitor(MonitorOptions {
+            interval,
+            file,
+            alert,
+            notify_new,
+        }));
+    }
+    Ok(Command::MonitorReport(ReportOptions {
+        port: port.ok_or_else(|| tr!("monitor report needs --port"))?,
+        file,
+    }))
+}
+
+fn parse_cleanup(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut older_than = None;
+    let mut filter = None;
+    let mut assume_yes = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--older-than" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| tr!("--older-than needs a value"))?;
+                older_than = Some(parse_age(&value)?);
+            }
+            "--match" => {
+                let value = args.next().ok_or_else(|| tr!("--match needs a value"))?;
+                filter = Some(ListenerFilter::parse(&value));
+            }
+            "-y" | "--yes" => assume_yes = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ => return Err(tr!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(Command::Cleanup(CleanupOptions {
+        older_than: older_than.ok_or_else(|| tr!("cleanup needs --older-than"))?,
+        filter,
+        assume_yes,
+    }))
+}
+
+/// `90s`, `30m`, `2h`, `1d` or a sum such as `1h30m`.
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let invalid = || tr!("invalid --older-than value '{}' (e.g. 30m, 2h, 1d)", value);
+    let mut secs = 0;
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            _ => return Err(invalid()),
+        };
+        let n: u64 = digits.parse().map_err(|_| invalid())?;
+        secs += n * unit;
+        digits.clear();
+    }
+    if !digits.is_empty() || secs == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+fn parse_keepalive(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut port = None;
+    let mut interval = Duration::from_secs(1);
+    let mut grace = Duration::from_secs(5);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--port" => {
+                let value = args.next().ok_or_else(|| tr!("--port needs a value"))?;
+                port = Some(parse_port(&value)?);
+            }
+            "-i" | "--interval" => interval = parse_seconds(args.next(), "--interval")?,
+            "-g" | "--grace" => grace = parse_seconds(args.next(), "--grace")?,
+            "-h" | "--help" => return Ok(Command::Help),
+            "--" => break,
+            _ => return Err(tr!("unexpected argument '{}' (missing `--`?)", arg)),
+        }
+    }
+
+    let command: Vec<String> = args.collect();
+    if command.is_empty() {
+        return Err(tr!("keepalive needs a command after `--`"));
+    }
+
+    Ok(Command::Keepalive(KeepaliveOptions {
+        port: port.ok_or_else(|| tr!("keepalive needs --port"))?,
+        interval,
+        grace,
+        command,
+    }))
+}
+
+fn parse_seconds(value: Option<String>, flag: &str) -> Result<Duration, String> {
+    let value = value.ok_or_else(|| tr!("{} needs a value", flag))?;
+    value
+        .parse()
+        .ok()
+        .filter(|s: &f64| *s > 0.0)
+        .and_then(|s| Duration::try_from_secs_f64(s).ok())
+        .ok_or_else(|| tr!("invalid {} value '{}'", flag, value))
+}
+
+pub fn parse_port(value: &str) -> Result<u16, String> {
+    value
+        .trim_start_matches(':')
+        .parse()
+        .ok()
+        .filter(|p| *p > 0)
+        .ok_or_else(|| tr!("invalid port '{}'", value))
+}
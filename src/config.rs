@@ -0,0 +1,574 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+use ratatui::style::Color;
+
+use crate::PortProcess;
+use crate::keymap::{self, Action};
+use crate::theme::{self, Theme};
+
+/// Signals offered for the kill action, in the order the settings view
+/// cycles through them.
+pub const SIGNALS: &[&str] = &["TERM", "INT", "HUP", "KILL"];
+/// Signals a `[graceful]` entry may name.
+pub const STOP_SIGNALS: &[&str] = &[
+    "TERM", "INT", "HUP", "QUIT", "USR1", "USR2", "WINCH", "KILL",
+];
+/// Grace periods offered by the settings view for the graceful kill.
+pub const GRACE_CHOICES: &[u64] = &[2, 5, 10, 30];
+/// Auto-refresh intervals offered by the settings view; 0 is off.
+pub const REFRESH_CHOICES: &[u64] = &[0, 2, 5, 10, 30];
+
+/// Settings the user can change from inside rip, stored as a small subset
+/// of TOML (`key = value` with strings, integers, booleans and arrays of
+/// strings, and `[keys]`, `[theme]` and `[graceful]` tables).
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Signal sent by the kill action to servers without a known graceful
+    /// stop signal, without the SIG prefix.
+    pub signal: String,
+    /// Ask before killing.
+    pub confirm_kill: bool,
+    /// Seconds the graceful kill waits after SIGTERM before sending SIGKILL.
+    pub grace_secs: u64,
+    /// Seconds between automatic refreshes; 0 is off.
+    pub refresh_secs: u64,
+    /// Leave this platform's own OS services (rapportd, systemd-resolved,
+    /// svchost.exe, ...) out of the list.
+    pub hide_system: bool,
+    /// Draw in colour; false is the monochrome theme, keeping only bold,
+    /// reverse and the like.
+    pub colors: bool,
+    /// Built-in theme the `[theme]` table starts from.
+    pub theme: String,
+    /// `[theme]`: colours replacing the theme's, by slot.
+    pub palette: Vec<(String, Color)>,
+    /// Rows to leave out of the list: a process name, `:PORT` or `pid:PID`.
+    pub ignore: Vec<String>,
+    /// Rows that need a capital Y to kill, matched like `ignore`, on top of
+    /// the built-in list (sshd, init systems, ...).
+    pub protect: Vec<String>,
+    /// Rows rip refuses to kill at all, matched like `ignore`.
+    pub never_kill: Vec<String>,
+    /// `[keys]`: actions bound to other keys than the defaults.
+    pub keys: Vec<(Action, Vec<KeyCode>)>,
+    /// `[graceful]`: the stop signal for a program, by name, ahead of the
+    /// built-in ones (`nginx = "QUIT"`).
+    pub graceful: Vec<(String, String)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            signal: "TERM".to_string(),
+            confirm_kill: true,
+            grace_secs: 5,
+            refresh_secs: 0,
+            hide_system: true,
+            colors: true,
+            theme: "default".to_string(),
+            palette: Vec::new(),
+            ignore: Vec::new(),
+            protect: Vec::new(),
+            never_kill: Vec::new(),
+            keys: Vec::new(),
+            graceful: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        (self.refresh_secs > 0).then(|| Duration::from_secs(self.refresh_secs))
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        Duration::from_secs(self.grace_secs)
+    }
+
+    /// The palette to draw with: the named theme with `[theme]` laid
+    /// over it, or monochrome when colours are off.
+    pub fn theme(&self) -> Theme {
+        if !self.colors {
+            return Theme::MONOCHROME;
+        }
+        let mut theme = Theme::builtin(&self.theme).unwrap_or(Theme::DEFAULT);
+        for (slot, color) in &self.palette {
+            theme.set(slot, *color);
+        }
+        theme
+    }
+
+    pub fn ignores(&self, process: &PortProcess) -> bool {
+        self.ignore.iter().any(|entry| matches(entry, process))
+    }
+
+    pub fn protects(&self, process: &PortProcess) -> bool {
+        self.protect.iter().any(|entry| matches(entry, process))
+    }
+
+    /// Why `never_kill` will not let `process` be killed, if it will not.
+    pub fn never_kill_refusal(&self, process: &PortProcess) -> Option<String> {
+        self.never_kill
+            .iter()
+            .any(|entry| matches(entry, process))
+            .then(|| {
+                tr!(
+                    "{} (PID: {}) is on the never_kill list in the config; not killing it",
+                    process.name,
+                    process.pid_label()
+                )
+            })
+    }
+
+    /// Every setting as the line `save` writes for it, with its table
+    /// (empty for the top level) and the name it is looked up by.
+    fn entries(&self) -> Vec<(&'static str, String, String)> {
+        let list = |entries: &[String]| {
+            let quoted: Vec<String> = entries.iter().map(|entry| quote(entry)).collect();
+            format!("[{}]", quoted.join(", "))
+        };
+        let top = [
+            ("signal", quote(&self.signal)),
+            ("confirm_kill", self.confirm_kill.to_string()),
+            ("grace_secs", self.grace_secs.to_string()),
+            ("refresh_secs", self.refresh_secs.to_string()),
+            ("hide_system", self.hide_system.to_string()),
+            ("colors", self.colors.to_string()),
+            ("theme", quote(&self.theme)),
+            ("ignore", list(&self.ignore)),
+            ("protect", list(&self.protect)),
+            ("never_kill", list(&self.never_kill)),
+        ];
+        let mut entries: Vec<_> = top
+            .into_iter()
+            .map(|(key, value)| ("", key.to_string(), format!("{} = {}", key, value)))
+            .collect();
+        for (action, codes) in &self.keys {
+            let names: Vec<String> = codes.iter().map(|code| keymap::key_name(*code)).collect();
+            let line = format!("{} = {}", action.name(), list(&names));
+            entries.push(("keys", action.name().to_string(), line));
+        }
+        for (slot, color) in &self.palette {
+            let line = format!("{} = {}", slot, quote(&color.to_string()));
+            entries.push(("theme", slot.clone(), line));
+        }
+        for (program, signal) in &self.graceful {
+            let line = format!("{} = {}", quote(program), quote(signal));
+            entries.push(("graceful", program.clone(), line));
+        }
+        entries
+    }
+
+    fn to_toml(&self) -> String {
+        let mut toml = "# Written by rip; edit freely.\n".to_string();
+        let mut table = "";
+        for (name, _, line) in self.entries() {
+            if name != table {
+                table = name;
+                toml.push_str(&format!("\n[{}]\n", table));
+            }
+            toml.push_str(&line);
+            toml.push('\n');
+        }
+        toml
+    }
+}
+
+/// Whether a list entry names `process`: `:PORT`, `pid:PID` or a process
+/// name.
+pub fn matches(entry: &str, process: &PortProcess) -> bool {
+    if let Some(port) = entry.strip_prefix(':') {
+        port.parse() == Ok(process.port)
+    } else if let Some(pid) = entry.strip_prefix("pid:") {
+        pid.parse() == Ok(process.pid)
+    } else {
+        entry == process.name
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rip/config.toml`, or `~/.config/rip/config.toml`.
+pub fn path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("rip").join("config.toml"))
+}
+
+/// Read the config file. A missing file is the default config.
+pub fn load() -> Result<Config, String> {
+    let Some(path) = path() else {
+        return Ok(Config::default());
+    };
+    match fs::read_to_string(&path) {
+        Ok(contents) => parse(&contents).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// `load` for the commands that kill outside the TUI: a config that does
+/// not parse would quietly drop its `never_kill` and `protect` lists, so
+/// say why and exit instead.
+pub fn load_or_exit() -> Config {
+    load().unwrap_or_else(|e| {
+        eprintln!("rip: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Write `config` back. An existing file only has the lines for settings
+/// that changed rewritten, so its comments and order survive. Returns its
+/// path.
+pub fn save(config: &Config) -> io::Result<PathBuf> {
+    let path = path().ok_or_else(|| io::Error::other(tr!("cannot find a home directory")))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => update(&contents, config).map_err(io::Error::other)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => config.to_toml(),
+        Err(e) => return Err(e),
+    };
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+/// `contents` with the settings that differ from `config` rewritten in
+/// place, new ones added at the end of their table and removed ones
+/// dropped. Everything else is left as it was.
+pub fn update(contents: &str, config: &Config) -> Result<String, String> {
+    let before = parse(contents)?.entries();
+    let after = config.entries();
+    let line_of = |entries: &[(&str, String, String)], table: &str, name: &str| {
+        entries
+            .iter()
+            .find(|(t, n, _)| *t == table && n == name)
+            .map(|(_, _, line)| line.clone())
+    };
+
+    let mut lines: Vec<Option<String>> = contents.lines().map(|l| Some(l.to_string())).collect();
+    // Where each table's settings end, for adding new ones after them.
+    let mut ends: Vec<(String, usize)> = vec![(String::new(), 0)];
+    let mut seen = Vec::new();
+    let mut table = String::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index].clone().unwrap_or_default();
+        let code = strip_comment(&line).trim();
+        index += 1;
+        if let Some(name) = code.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = name.trim().to_string();
+            ends.push((table.clone(), index));
+            continue;
+        }
+        let Some((key, value)) = code.split_once('=') else {
+            continue;
+        };
+        let start = index - 1;
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !value.ends_with(']') && index < lines.len() {
+            value.push_str(strip_comment(lines[index].as_deref().unwrap_or_default()).trim());
+            index += 1;
+        }
+        let key = key.trim();
+        let name = parse_string(key).unwrap_or_else(|| key.to_string());
+        if let Some(end) = ends.iter_mut().rev().find(|(t, _)| *t == table) {
+            end.1 = index;
+        }
+        seen.push((table.clone(), name.clone()));
+        let old = line_of(&before, &table, &name);
+        let new = line_of(&after, &table, &name);
+        if old == new {
+            continue;
+        }
+        for line in &mut lines[start + 1..index] {
+            *line = None;
+        }
+        lines[start] = new.map(|new| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let comment = line[strip_comment(&line).len()..].trim_end();
+            if comment.is_empty() {
+                format!("{}{}", indent, new)
+            } else {
+                format!("{}{} {}", indent, new, comment)
+            }
+        });
+    }
+
+    let mut added: Vec<(usize, String)> = Vec::new();
+    let mut tables = String::new();
+    let mut last = "";
+    for (table, name, line) in &after {
+        if seen.iter().any(|(t, n)| t == table && n == name)
+            || line_of(&before, table, name).as_ref() == Some(line)
+        {
+            continue;
+        }
+        match ends.iter().rev().find(|(t, _)| t == table) {
+            Some((_, end)) => added.push((*end, line.clone())),
+            None => {
+                if *table != last {
+                    last = table;
+                    tables.push_str(&format!("\n[{}]\n", table));
+                }
+                tables.push_str(line);
+                tables.push('\n');
+            }
+        }
+    }
+
+    let mut updated = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        for (_, new) in added.iter().filter(|(at, _)| *at == index) {
+            updated.push_str(new);
+            updated.push('\n');
+        }
+        if let Some(line) = line {
+            updated.push_str(line);
+            updated.push('\n');
+        }
+    }
+    for (_, new) in added.iter().filter(|(at, _)| *at == lines.len()) {
+        updated.push_str(new);
+        updated.push('\n');
+    }
+    Ok(updated + &tables)
+}
+
+pub fn parse(contents: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut lines = contents.lines().enumerate();
+    let mut table = String::new();
+
+    while let Some((index, line)) = lines.next() {
+        let number = index + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = name.trim().to_string();
+            if !["keys", "theme", "graceful"].contains(&table.as_str()) {
+                return Err(tr!("line {}: unknown table '{}'", number, table));
+            }
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| tr!("line {}: expected `key = value`", number))?;
+        let mut value = value.trim().to_string();
+        // Arrays may span several lines.
+        while value.starts_with('[') && !value.ends_with(']') {
+            let Some((_, next)) = lines.next() else {
+                return Err(tr!("line {}: unterminated array", number));
+            };
+            value.push(' ');
+            value.push_str(strip_comment(next).trim());
+        }
+
+        match table.as_str() {
+            "keys" => {
+                config.keys.push(parse_binding(key.trim(), &value, number)?);
+                continue;
+            }
+            "theme" => {
+                config
+                    .palette
+                    .push(parse_color(key.trim(), &value, number)?);
+                continue;
+            }
+            "graceful" => {
+                config
+                    .graceful
+                    .push(parse_stop_signal(key.trim(), &value, number)?);
+                continue;
+            }
+            _ => {}
+        }
+
+        match key.trim() {
+            "signal" => {
+                let signal = parse_string(&value)
+                    .map(|s| s.trim_start_matches("SIG").to_uppercase())
+                    .filter(|s| SIGNALS.contains(&s.as_str()))
+                    .ok_or_else(|| {
+                        tr!(
+                            "line {}: signal must be one of {}",
+                            number,
+                            SIGNALS.join(", ")
+                        )
+                    })?;
+                config.signal = signal;
+            }
+            "confirm_kill" => {
+                config.confirm_kill = value
+                    .parse()
+                    .map_err(|_| tr!("line {}: confirm_kill must be true or false", number))?
+            }
+            "grace_secs" => {
+                config.grace_secs = value.parse().map_err(|_| {
+                    tr!(
+                        "line {}: grace_secs must be a whole number of seconds",
+                        number
+                    )
+                })?
+            }
+            "refresh_secs" => {
+                config.refresh_secs = value.parse().map_err(|_| {
+                    tr!(
+                        "line {}: refresh_secs must be a whole number of seconds",
+                        number
+                    )
+                })?
+            }
+            "hide_system" => {
+                config.hide_system = value
+                    .parse()
+                    .map_err(|_| tr!("line {}: hide_system must be true or false", number))?
+            }
+            "colors" => {
+                config.colors = value
+                    .parse()
+                    .map_err(|_| tr!("line {}: colors must be true or false", number))?
+            }
+            "theme" => {
+                config.theme = parse_string(&value)
+                    .filter(|name| Theme::builtin(name).is_some())
+                    .ok_or_else(|| {
+                        let names: Vec<&str> =
+                            theme::BUILTIN.iter().map(|(name, _)| *name).collect();
+                        tr!("line {}: theme must be one of {}", number, names.join(", "))
+                    })?
+            }
+            "ignore" => {
+                config.ignore = parse_strings(&value)
+                    .ok_or_else(|| tr!("line {}: ignore must be a list of strings", number))?
+            }
+            "protect" => {
+                config.protect = parse_strings(&value)
+                    .ok_or_else(|| tr!("line {}: protect must be a list of strings", number))?
+            }
+            "never_kill" => {
+                config.never_kill = parse_strings(&value)
+                    .ok_or_else(|| tr!("line {}: never_kill must be a list of strings", number))?
+            }
+            other => return Err(tr!("line {}: unknown setting '{}'", number, other)),
+        }
+    }
+    Ok(config)
+}
+
+/// `kill = "x"` or `kill = ["x", "Enter"]` in the `[keys]` table.
+fn parse_binding(name: &str, value: &str, number: usize) -> Result<(Action, Vec<KeyCode>), String> {
+    let action =
+        Action::by_name(name).ok_or_else(|| tr!("line {}: unknown action '{}'", number, name))?;
+    let names = parse_strings(value)
+        .or_else(|| parse_string(value).map(|key| vec![key]))
+        .ok_or_else(|| tr!("line {}: {} must be a key or a list of keys", number, name))?;
+    let keys = names
+        .iter()
+        .map(|key| {
+            keymap::parse_key(key).ok_or_else(|| tr!("line {}: unknown key '{}'", number, key))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((action, keys))
+}
+
+/// `nginx = "QUIT"` (or `"SIGQUIT"`) in the `[graceful]` table; the name
+/// may be quoted, for programs with dots or spaces in it.
+fn parse_stop_signal(
+    program: &str,
+    value: &str,
+    number: usize,
+) -> Result<(String, String), String> {
+    let program = parse_string(program).unwrap_or_else(|| program.to_string());
+    let signal = parse_string(value)
+        .map(|s| s.trim_start_matches("SIG").to_uppercase())
+        .filter(|s| STOP_SIGNALS.contains(&s.as_str()))
+        .ok_or_else(|| {
+            tr!(
+                "line {}: {} must be one of {}",
+                number,
+                program,
+                STOP_SIGNALS.join(", ")
+            )
+        })?;
+    Ok((program, signal))
+}
+
+/// `danger = "lightred"` in the `[theme]` table.
+fn parse_color(slot: &str, value: &str, number: usize) -> Result<(String, Color), String> {
+    let mut scratch = Theme::DEFAULT;
+    if !scratch.set(slot, Color::Reset) {
+        return Err(tr!("line {}: unknown theme colour '{}'", number, slot));
+    }
+    let color = parse_string(value)
+        .and_then(|name| theme::parse_color(&name))
+        .ok_or_else(|| {
+            tr!(
+                "line {}: {} must be a colour name, a number from 0 to 255 or #rrggbb",
+                number,
+                slot
+            )
+        })?;
+    Ok((slot.to_string(), color))
+}
+
+/// Drop a trailing `# comment` that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// `"a"` -> a
+fn parse_string(value: &str) -> Option<String> {
+    let mut strings = parse_strings(&format!("[{}]", value))?;
+    (strings.len() == 1).then(|| strings.remove(0))
+}
+
+/// `["a", "b"]` -> a, b
+fn parse_strings(value: &str) -> Option<Vec<String>> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+    let mut strings = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        match chars.next() {
+            None => return Some(strings),
+            Some('"') => {}
+            Some(_) => return None,
+        }
+        let mut string = String::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => string.push(match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    c => c,
+                }),
+                c => string.push(c),
+            }
+        }
+        strings.push(string);
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,117 @@
+use crate::{PortProcess, UNKNOWN_PID};
+
+/// A port where one process listens on a wildcard address while another
+/// listens on a specific IP. Connections are routed by destination address:
+/// the more specific bind wins for its own IP, the wildcard gets the rest.
+#[derive(Clone, Debug)]
+pub struct BindConflict {
+    pub port: u16,
+    pub protocol: String,
+    pub wildcard: Vec<PortProcess>,
+    pub specific: Vec<PortProcess>,
+}
+
+impl BindConflict {
+    /// Whether `row` is one of the sockets in the conflict: the same
+    /// process on this port and protocol, not just the same process. Rows
+    /// with no known owner are told apart by address instead.
+    pub fn involves(&self, row: &PortProcess) -> bool {
+        row.port == self.port
+            && row.protocol == self.protocol
+            && self
+                .wildcard
+                .iter()
+                .chain(self.specific.iter())
+                .any(|p| p.pid == row.pid && (p.pid != UNKNOWN_PID || p.address == row.address))
+    }
+
+    pub fn explain(&self) -> String {
+        let specific = self
+            .specific
+            .iter()
+            .map(|p| tr!("{} gets {}", p.name, p.address))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let wildcard = self
+            .wildcard
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        tr!(
+            ":{} {}: {}; {} ({}) gets every other address",
+            self.port,
+            self.protocol,
+            specific,
+            wildcard,
+            self.wildcard[0].address
+        )
+    }
+}
+
+pub fn is_wildcard(address: &str) -> bool {
+    matches!(address, "*" | "0.0.0.0" | "::" | "[::]")
+}
+
+/// Whether `address` is only reachable from this machine.
+pub fn is_loopback(address: &str) -> bool {
+    let host = address.trim_start_matches('[').trim_end_matches(']');
+    host.starts_with("127.") || host == "::1" || host == "localhost"
+}
+
+/// Whether a wildcard bind takes connections for the family `specific` is
+/// in. lsof and `/proc/net` print `*` for both families' wildcards, so it
+/// could be either.
+fn same_family(wildcard: &str, specific: &str) -> bool {
+    let ipv6 = specific.contains(':');
+    match wildcard {
+        "0.0.0.0" => !ipv6,
+        "::" | "[::]" => ipv6,
+        _ => true,
+    }
+}
+
+pub fn find_bind_conflicts(processes: &[PortProcess]) -> Vec<BindConflict> {
+    let mut conflicts: Vec<BindConflict> = Vec::new();
+
+    for process in processes {
+        if conflicts
+            .iter()
+            .any(|c| c.port == process.port && c.protocol == process.protocol)
+        {
+            continue;
+        }
+
+        let (wildcard, specific): (Vec<PortProcess>, Vec<PortProcess>) = processes
+            .iter()
+            .filter(|p| p.port == process.port && p.protocol == process.protocol)
+            .cloned()
+            .partition(|p| is_wildcard(&p.address));
+        // 0.0.0.0 and [::1] never compete for a connection.
+        let specific: Vec<PortProcess> = specific
+            .into_iter()
+            .filter(|s| wildcard.iter().any(|w| same_family(&w.address, &s.address)))
+            .collect();
+        let wildcard: Vec<PortProcess> = wildcard
+            .into_iter()
+            .filter(|w| specific.iter().any(|s| same_family(&w.address, &s.address)))
+            .collect();
+
+        let distinct_owner = specific.iter().any(|s| {
+            wildcard
+                .iter()
+                .any(|w| w.pid != s.pid || w.pid == UNKNOWN_PID)
+        });
+
+        if distinct_owner {
+            conflicts.push(BindConflict {
+                port: process.port,
+                protocol: process.protocol.clone(),
+                wildcard,
+                specific,
+            });
+        }
+    }
+
+    conflicts
+}
@@ -0,0 +1,430 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::json::{self, Value};
+use crate::{PortProcess, enrich};
+
+/// The processes Docker Desktop publishes container ports through on
+/// macOS, where containers run in a VM and the host only sees its backend.
+const DESKTOP_PROCESSES: [&str; 3] = ["com.docker.backend", "com.docker.vpnkit", "vpnkit-bridge"];
+
+/// Processes that only hold a published port on a container's behalf:
+/// Docker Desktop's VM, `docker-proxy` and rootless Docker's `rootlesskit`.
+/// Killing one leaves the container running (Docker restarts the first),
+/// so the container is what to stop.
+const PROXY_PROCESSES: [&str; 2] = ["docker-proxy", "rootlesskit"];
+
+/// The container a listener runs in, as its runtime describes it.
+pub struct Container {
+    /// "docker" or "podman".
+    pub runtime: &'static str,
+    pub id: String,
+    pub name: String,
+    /// The image reference it was started from, e.g. "nginx:1.25".
+    pub image: String,
+    /// The registry digest of that image ("nginx@sha256:..."), when it was
+    /// pulled rather than built locally.
+    pub digest: Option<String>,
+    /// The user the entrypoint runs as; empty means the image default, root.
+    pub user: String,
+    pub host_network: bool,
+    pub privileged: bool,
+    /// The local image id ("sha256:...").
+    image_id: String,
+    /// Its IP address on each of its networks.
+    addresses: Vec<String>,
+    labels: Vec<(String, String)>,
+}
+
+/// How the container is meant to be managed: the file that defines it and
+/// the command that restarts it the way its tooling expects.
+pub struct Lifecycle {
+    pub file: Option<PathBuf>,
+    pub restart: String,
+}
+
+impl Container {
+    /// `docker stop` (or `podman stop`) it.
+    pub fn stop(&self) -> io::Result<()> {
+        let output = Command::new(self.runtime)
+            .args(["stop", &self.name])
+            .output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(io::Error::other(match stderr.lines().next() {
+            Some(line) => line.to_string(),
+            None => tr!(
+                "{} stop failed with status: {}",
+                self.runtime,
+                output.status
+            ),
+        }))
+    }
+
+    pub fn runs_as_root(&self) -> bool {
+        let user = self.user.split(':').next().unwrap_or_default();
+        matches!(user, "" | "0" | "root")
+    }
+
+    fn label(&self, key: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The Compose file or Kubernetes manifest the container comes from,
+    /// read from the labels Compose and the kubelet put on it; for
+    /// Kubernetes, which does not label the manifest, a YAML file in the
+    /// current directory (or one below it) that names the workload.
+    pub fn lifecycle(&self) -> Option<Lifecycle> {
+        if let Some(service) = self.label("com.docker.compose.service") {
+            let project = self.label("com.docker.compose.project").unwrap_or_default();
+            let file = self
+                .label("com.docker.compose.project.config_files")
+                .and_then(|files| files.split(',').next())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    let dir = Path::new(self.label("com.docker.compose.project.working_dir")?);
+                    COMPOSE_FILES
+                        .iter()
+                        .map(|name| dir.join(name))
+                        .find(|path| path.is_file())
+                });
+            let restart = match &file {
+                Some(file) => format!(
+                    "{} compose -f {} -p {} up -d {}",
+                    self.runtime,
+                    file.display(),
+                    project,
+                    service
+                ),
+                None => format!("{} compose -p {} up -d {}", self.runtime, project, service),
+            };
+            return Some(Lifecycle { file, restart });
+        }
+
+        let pod = self.label("io.kubernetes.pod.name")?;
+        let namespace = self
+            .label("io.kubernetes.pod.namespace")
+            .unwrap_or("default");
+        let workload = workload_name(pod);
+        let file = find_manifest(workload);
+        let restart = match &file {
+            Some(file) => format!("kubectl -n {} apply -f {}", namespace, file.display()),
+            // Its controller replaces a deleted pod.
+            None => format!("kubectl -n {} delete pod {}", namespace, pod),
+        };
+        Some(Lifecycle { file, restart })
+    }
+}
+
+/// A host port a running container publishes, from `docker ps`.
+pub struct Published {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub host_port: u16,
+    /// "tcp" or "udp".
+    pub protocol: String,
+    /// The port inside the container it forwards to.
+    pub target: u16,
+}
+
+/// Whether `name` is Docker Desktop's VM backend rather than a workload.
+/// lsof cuts command names to nine characters, so a prefix that long counts.
+pub fn is_desktop_vm(name: &str) -> bool {
+    name.len() >= 9 && DESKTOP_PROCESSES.iter().any(|p| p.starts_with(name))
+}
+
+/// Whether `name` holds ports for containers rather than serving them
+/// itself, cut short by lsof or not.
+pub fn is_port_proxy(name: &str) -> bool {
+    is_desktop_vm(name) || name.len() >= 9 && PROXY_PROCESSES.iter().any(|p| p.starts_with(name))
+}
+
+/// The published port `process` holds, if a running container publishes
+/// one on that port and protocol.
+pub fn published_at(process: &PortProcess) -> Option<Published> {
+    published_ports()
+        .into_iter()
+        .find(|p| p.host_port == process.port && p.protocol.eq_ignore_ascii_case(&process.protocol))
+}
+
+/// Every port published by a running Docker container; empty when the CLI
+/// is missing or cannot reach the daemon.
+pub fn published_ports() -> Vec<Published> {
+    let Ok(output) = Command::new("docker")
+        .args([
+            "ps",
+            "--format",
+            "{{.ID}}\t{{.Names}}\t{{.Image}}\t{{.Ports}}",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut published = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split('\t');
+        let (Some(id), Some(name), Some(image), Some(ports)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        for (host_port, target, protocol) in ports.split(", ").flat_map(parse_mapping) {
+            published.push(Published {
+                id: id.to_string(),
+                name: name.to_string(),
+                image: image.to_string(),
+                host_port,
+                protocol,
+                target,
+            });
+        }
+    }
+    published
+}
+
+/// "0.0.0.0:8080->80/tcp" -> (8080, 80, "tcp"); a range mapping such as
+/// "0.0.0.0:5000-5001->5000-5001/tcp" gives one per port. Ports exposed
+/// but not published ("80/tcp") give none.
+fn parse_mapping(mapping: &str) -> Vec<(u16, u16, String)> {
+    let Some((host, target)) = mapping.split_once("->") else {
+        return Vec::new();
+    };
+    let Some((target, protocol)) = target.split_once('/') else {
+        return Vec::new();
+    };
+    let host = host.rsplit(':').next().unwrap_or(host);
+    let range = |text: &str| -> Option<(u16, u16)> {
+        match text.split_once('-') {
+            Some((first, last)) => Some((first.parse().ok()?, last.parse().ok()?)),
+            None => text.parse().ok().map(|port| (port, port)),
+        }
+    };
+    let (Some((host_first, host_last)), Some((target_first, _))) = (range(host), range(target))
+    else {
+        return Vec::new();
+    };
+    (host_first..=host_last)
+        .map(|port| {
+            (
+                port,
+                target_first + (port - host_first),
+                protocol.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// The names `docker compose` looks for in a project directory.
+const COMPOSE_FILES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yml",
+    "docker-compose.yaml",
+];
+
+/// "web-5d8f7c9b6-x2k4z" (Deployment) -> "web", "db-0" (StatefulSet) ->
+/// "db".
+fn workload_name(pod: &str) -> &str {
+    let mut name = pod;
+    if let Some((rest, suffix)) = name.rsplit_once('-') {
+        if suffix.bytes().all(|b| b.is_ascii_digit()) {
+            return rest;
+        }
+        if suffix.len() == 5 {
+            name = rest;
+        }
+    }
+    match name.rsplit_once('-') {
+        Some((rest, hash)) if (8..=10).contains(&hash.len()) && name != pod => rest,
+        _ => name,
+    }
+}
+
+/// A Kubernetes manifest in the current directory or one level below that
+/// has `name: <workload>`.
+fn find_manifest(workload: &str) -> Option<PathBuf> {
+    let wanted = format!("name: {}", workload);
+    let defines = |path: &Path| {
+        let is_yaml = path.extension().is_some_and(|e| e == "yaml" || e == "yml");
+        is_yaml
+            && fs::read_to_string(path).is_ok_and(|text| {
+                text.contains("kind:") && text.lines().any(|line| line.trim() == wanted)
+            })
+    };
+    let mut dirs = vec![PathBuf::from(".")];
+    let mut depth = 0;
+    while depth < 2 {
+        let mut next = Vec::new();
+        for dir in &dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.is_dir() {
+                    next.push(path);
+                } else if defines(&path) {
+                    return Some(path.strip_prefix(".").unwrap_or(&path).to_path_buf());
+                }
+            }
+        }
+        dirs = next;
+        depth += 1;
+    }
+    None
+}
+
+/// The container behind a listener: `of_process` for its PID, or, when a
+/// port proxy holds the port, the container that publishes it.
+pub fn of_listener(process: &PortProcess) -> Option<Container> {
+    if !is_port_proxy(&process.name) {
+        return of_process(process.pid);
+    }
+    let Some(published) = published_at(process) else {
+        return of_process(process.pid);
+    };
+    let mut container = inspect("docker", &[published.id]).into_iter().next()?;
+    container.digest = digest(container.runtime, &container.image_id);
+    Some(container)
+}
+
+/// The container `pid` belongs to: found through its cgroup, or, for the
+/// `docker-proxy` that forwards a published port, through the container
+/// address it forwards to. Linux only; `None` when the runtime's CLI is
+/// missing or cannot reach its daemon.
+pub fn of_process(pid: u32) -> Option<Container> {
+    let mut container = match cgroup_container(pid) {
+        Some((runtime, id)) => inspect(runtime, &[id]).into_iter().next()?,
+        None => proxied_container(pid)?,
+    };
+    container.digest = digest(container.runtime, &container.image_id);
+    Some(container)
+}
+
+/// The container a `docker-proxy` process forwards to.
+fn proxied_container(pid: u32) -> Option<Container> {
+    let argv = enrich::command_line(pid)?;
+    if !argv.first()?.ends_with("docker-proxy") {
+        return None;
+    }
+    let ip = argv
+        .iter()
+        .position(|arg| arg == "-container-ip")
+        .and_then(|i| argv.get(i + 1))?;
+    let output = Command::new("docker").args(["ps", "-q"]).output().ok()?;
+    let ids: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    inspect("docker", &ids)
+        .into_iter()
+        .find(|container| container.addresses.contains(ip))
+}
+
+/// The runtime and 64-hex container id from `/proc/PID/cgroup`:
+/// "docker-<id>.scope", "/docker/<id>" or podman's "libpod-<id>.scope".
+fn cgroup_container(pid: u32) -> Option<(&'static str, String)> {
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    for segment in cgroup.lines().flat_map(|line| line.split('/')) {
+        let (runtime, rest) = if let Some(rest) = segment.strip_prefix("libpod-") {
+            ("podman", rest)
+        } else if let Some(rest) = segment.strip_prefix("docker-") {
+            ("docker", rest)
+        } else {
+            ("docker", segment)
+        };
+        let id = rest.strip_suffix(".scope").unwrap_or(rest);
+        if id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Some((runtime, id.to_string()));
+        }
+    }
+    None
+}
+
+/// `docker inspect` (or `podman inspect`) of each id, in order; ids that
+/// fail to inspect are left out.
+fn inspect(runtime: &'static str, ids: &[String]) -> Vec<Container> {
+    if ids.is_empty() {
+        return Vec::new();
+    }
+    let Ok(output) = Command::new(runtime).arg("inspect").args(ids).output() else {
+        return Vec::new();
+    };
+    let Ok(Value::Array(items)) = json::parse(&String::from_utf8_lossy(&output.stdout)) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| parse_container(runtime, item))
+        .collect()
+}
+
+fn parse_container(runtime: &'static str, item: &Value) -> Option<Container> {
+    fn text(value: Option<&Value>) -> &str {
+        value.and_then(Value::as_str).unwrap_or_default()
+    }
+    let config = item.get("Config");
+    let host = item.get("HostConfig");
+    let addresses = match item.get("NetworkSettings").and_then(|n| n.get("Networks")) {
+        Some(Value::Object(networks)) => networks
+            .iter()
+            .map(|(_, network)| text(network.get("IPAddress")).to_string())
+            .filter(|ip| !ip.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+    let labels = match config.and_then(|c| c.get("Labels")) {
+        Some(Value::Object(labels)) => labels
+            .iter()
+            .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let image = match text(config.and_then(|c| c.get("Image"))) {
+        "" => text(item.get("ImageName")),
+        image => image,
+    };
+    let id = text(item.get("Id"));
+    if id.is_empty() {
+        return None;
+    }
+    Some(Container {
+        runtime,
+        id: id.chars().take(12).collect(),
+        name: text(item.get("Name")).trim_start_matches('/').to_string(),
+        image: image.to_string(),
+        digest: None,
+        user: text(config.and_then(|c| c.get("User"))).to_string(),
+        host_network: text(host.and_then(|h| h.get("NetworkMode"))) == "host",
+        privileged: host.and_then(|h| h.get("Privileged")) == Some(&Value::Bool(true)),
+        image_id: text(item.get("Image")).to_string(),
+        addresses,
+        labels,
+    })
+}
+
+/// The first repo digest of the image with id `image`.
+fn digest(runtime: &str, image: &str) -> Option<String> {
+    let output = Command::new(runtime)
+        .args([
+            "image",
+            "inspect",
+            "--format",
+            "{{json .RepoDigests}}",
+            image,
+        ])
+        .output()
+        .ok()?;
+    let Ok(Value::Array(digests)) = json::parse(String::from_utf8_lossy(&output.stdout).trim())
+    else {
+        return None;
+    };
+    digests.first()?.as_str().map(str::to_string)
+}
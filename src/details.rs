@@ -0,0 +1,377 @@
+use std::fs;
+use std::process::Command;
+
+use crate::backend::{self, Backend};
+use crate::risk::Risk;
+use crate::{PortProcess, UNKNOWN_PID, cgroup, container, enrich, package, procfs, ssinfo};
+
+/// One titled block of the detail pane.
+pub struct Section {
+    pub title: String,
+    /// (label, value); rows with an empty label are shown as plain text.
+    pub rows: Vec<(String, String)>,
+}
+
+/// Everything the detail pane shows about `process`. Runs external tools
+/// and reads `/proc`, so callers cache the result per selected row.
+pub fn collect(process: &PortProcess, risk: Option<&Risk>) -> Vec<Section> {
+    let mut sections = vec![Section {
+        title: tr!("Process"),
+        rows: vec![
+            (tr!("Name"), process.name.clone()),
+            (tr!("PID"), process.pid_label()),
+            (
+                tr!("User"),
+                process.user.clone().unwrap_or_else(|| "?".to_string()),
+            ),
+            (
+                tr!("Socket"),
+                format!("{}:{}/{}", process.address, process.port, process.protocol),
+            ),
+        ],
+    }];
+    if process.pid == UNKNOWN_PID {
+        return sections;
+    }
+
+    if let Some(binary) = enrich::executable(process.pid) {
+        let package = match package::owner(&binary) {
+            Some(package) => format!("{} ({})", package.name, package.manager),
+            None => tr!("none (not installed by a package manager)"),
+        };
+        sections[0]
+            .rows
+            .push((tr!("Binary"), binary.display().to_string()));
+        sections[0].rows.push((tr!("Package"), package));
+    }
+    let rows = &mut sections[0].rows;
+    let argv = enrich::command_line(process.pid);
+    if let Some(argv) = &argv {
+        rows.push((tr!("Command"), argv.join(" ")));
+    }
+    if let Some(cwd) = enrich::working_directory(process.pid) {
+        rows.push((tr!("Directory"), cwd.display().to_string()));
+    }
+    if let Some((_, started)) = enrich::process_status(process.pid)
+        && !started.is_empty()
+    {
+        rows.push((tr!("Started"), started));
+    }
+    if let Some(ppid) = enrich::parent_pid(process.pid) {
+        let parent = procfs::process_name(ppid)
+            .map(|name| format!("{} ({})", ppid, name))
+            .unwrap_or_else(|| ppid.to_string());
+        rows.push((tr!("Parent PID"), parent));
+    }
+    if let Some(bytes) = enrich::resident_memory(process.pid) {
+        rows.push((tr!("Memory"), tr!("{} resident", format_bytes(bytes))));
+    }
+
+    if let Some(risk) = risk.filter(|r| r.score > 0) {
+        sections.push(Section {
+            title: tr!("Kill risk: {}", risk.score),
+            rows: risk
+                .reasons
+                .iter()
+                .map(|reason| (String::new(), reason.clone()))
+                .collect(),
+        });
+    }
+
+    if let Some(container) = container::of_listener(process) {
+        sections.push(container_section(&container));
+    }
+
+    if process.name.to_lowercase().starts_with("kubectl")
+        && let Some(forward) = argv.as_deref().and_then(enrich::port_forward)
+    {
+        sections.push(port_forward_section(&forward, process.port));
+    }
+
+    let sockets = listening_fds(process.pid);
+    if !sockets.is_empty() {
+        sections.push(Section {
+            title: tr!("Listening sockets"),
+            rows: sockets
+                .into_iter()
+                .map(|socket| {
+                    let mut value = format!("{} {}", socket.protocol, socket.address);
+                    if let Some(inode) = socket.inode {
+                        value.push_str(&tr!(" (inode {})", inode));
+                    }
+                    (tr!("fd {}", socket.fd), value)
+                })
+                .collect(),
+        });
+    }
+
+    if backend::selected().0 == Backend::Ss
+        && let Some(section) = socket_internals(process)
+    {
+        sections.push(section);
+    }
+
+    if let Some(limits) = cgroup::limits(process.pid) {
+        let unlimited = || tr!("no limit");
+        sections.push(Section {
+            title: tr!("cgroup"),
+            rows: vec![
+                (tr!("Group"), limits.group.clone()),
+                (
+                    tr!("Memory limit"),
+                    limits
+                        .memory_max
+                        .map(format_bytes)
+                        .unwrap_or_else(unlimited),
+                ),
+                (
+                    tr!("Memory used"),
+                    limits.memory_current.map(format_bytes).unwrap_or_default(),
+                ),
+                (
+                    tr!("CPU limit"),
+                    limits
+                        .cpus()
+                        .map(|cpus| tr!("{} CPUs", format!("{:.2}", cpus)))
+                        .unwrap_or_else(unlimited),
+                ),
+            ],
+        });
+    }
+    sections
+}
+
+/// Which image the container runs and with what privileges, with a warning
+/// for the combination that leaves the host most exposed.
+fn container_section(container: &container::Container) -> Section {
+    let mut rows = vec![
+        (
+            tr!("Container"),
+            format!(
+                "{} ({}, {})",
+                container.name, container.id, container.runtime
+            ),
+        ),
+        (tr!("Image"), container.image.clone()),
+        (
+            tr!("Digest"),
+            container
+                .digest
+                .clone()
+                .unwrap_or_else(|| tr!("none (built locally)")),
+        ),
+        (
+            tr!("Runs as"),
+            match container.user.as_str() {
+                "" => tr!("root (image default)"),
+                user => user.to_string(),
+            },
+        ),
+        (
+            tr!("Network"),
+            if container.host_network {
+                tr!("host")
+            } else {
+                tr!("its own")
+            },
+        ),
+    ];
+    if container.privileged {
+        rows.push((tr!("Privileged"), tr!("yes")));
+    }
+    if let Some(lifecycle) = container.lifecycle() {
+        if let Some(file) = lifecycle.file {
+            rows.push((tr!("Defined in"), file.display().to_string()));
+        }
+        rows.push((tr!("Restart with"), lifecycle.restart));
+    }
+    if container.runs_as_root() && container.host_network {
+        rows.push((
+            tr!("Warning"),
+            tr!("runs as root on the host network: nothing isolates its ports from the host's"),
+        ));
+    }
+    Section {
+        title: tr!("Container"),
+        rows,
+    }
+}
+
+/// Where a `kubectl port-forward` sends the port, so the row is more than
+/// just "kubectl".
+fn port_forward_section(forward: &enrich::PortForward, port: u16) -> Section {
+    let mut rows = vec![
+        (tr!("Target"), forward.target.clone()),
+        (
+            tr!("Namespace"),
+            forward
+                .namespace
+                .clone()
+                .unwrap_or_else(|| tr!("the context's default")),
+        ),
+    ];
+    if let Some(context) = &forward.context {
+        rows.push((tr!("Context"), context.clone()));
+    }
+    for (local, remote) in &forward.ports {
+        let local = match local {
+            Some(local) if *local == port => tr!(":{} (this row)", local),
+            Some(local) => format!(":{}", local),
+            None => tr!("a random port"),
+        };
+        rows.push((tr!("Forwards"), format!("{} -> {}", local, remote)));
+    }
+    rows.push((
+        tr!("Stopping"),
+        tr!("SIGINT, like Ctrl-C: kubectl closes its streams to the API server"),
+    ));
+    Section {
+        title: tr!("kubectl port-forward"),
+        rows,
+    }
+}
+
+/// Queue lengths, socket memory, congestion control and timers of the
+/// process's sockets on the port, which only ss (not lsof) can tell.
+fn socket_internals(process: &PortProcess) -> Option<Section> {
+    let (sockets, more) = ssinfo::sockets(process.pid, process.port);
+    if sockets.is_empty() {
+        return None;
+    }
+    let mut rows = Vec::new();
+    for socket in sockets {
+        let listener = matches!(socket.state.as_str(), "LISTEN" | "UNCONN");
+        if listener {
+            rows.push((String::new(), format!("{} {}", socket.state, socket.local)));
+            rows.push((
+                tr!("Queue"),
+                tr!(
+                    "{} waiting to be accepted, backlog {}",
+                    socket.recv_q,
+                    socket.send_q
+                ),
+            ));
+        } else {
+            rows.push((
+                String::new(),
+                format!("{} {} -> {}", socket.state, socket.local, socket.peer),
+            ));
+            rows.push((
+                tr!("Queue"),
+                tr!(
+                    "{} unread, {} unsent",
+                    format_bytes(socket.recv_q),
+                    format_bytes(socket.send_q)
+                ),
+            ));
+        }
+        if let Some(memory) = &socket.memory {
+            rows.push((
+                tr!("Memory"),
+                tr!(
+                    "receive {} of {}, send {} of {}",
+                    format_bytes(memory.receive),
+                    format_bytes(memory.receive_buffer),
+                    format_bytes(memory.send),
+                    format_bytes(memory.send_buffer)
+                ),
+            ));
+        }
+        let mut congestion: Vec<String> = socket.congestion.into_iter().collect();
+        congestion.extend(socket.cwnd.map(|cwnd| tr!("cwnd {}", cwnd)));
+        congestion.extend(socket.rtt.map(|rtt| tr!("rtt {} ms", rtt)));
+        congestion.extend(socket.retrans.map(|r| tr!("retransmits {}", r)));
+        if !congestion.is_empty() {
+            rows.push((tr!("Congestion"), congestion.join(", ")));
+        }
+        if let Some(timer) = socket.timer {
+            rows.push((
+                tr!("Timer"),
+                tr!(
+                    "{}, fires in {}, {} retransmits",
+                    timer.name,
+                    timer.expires,
+                    timer.retransmits
+                ),
+            ));
+        }
+    }
+    if more > 0 {
+        rows.push((String::new(), tr!("... and {} more sockets", more)));
+    }
+    Some(Section {
+        title: tr!("Socket internals (ss)"),
+        rows,
+    })
+}
+
+/// A file descriptor of a process that is a listening (or unconnected UDP)
+/// socket.
+struct SocketFd {
+    fd: u32,
+    protocol: String,
+    address: String,
+    inode: Option<u64>,
+}
+
+/// The process's listening socket fds, from lsof's field output. The socket
+/// inode comes from `/proc/PID/fd` where that exists (Linux), which is what
+/// `ss -e` and `/proc/net/tcp` report too.
+fn listening_fds(pid: u32) -> Vec<SocketFd> {
+    let Ok(output) = Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-i", "-P", "-n", "-F", "fPn"])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    let mut sockets = Vec::new();
+    let mut fd = None;
+    let mut protocol = String::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let (field, value) = line.split_at(line.len().min(1));
+        match field {
+            "f" => fd = value.parse().ok(),
+            "P" => protocol = value.to_string(),
+            "n" if !value.contains("->") => {
+                if let Some(fd) = fd.take() {
+                    sockets.push(SocketFd {
+                        fd,
+                        protocol: protocol.clone(),
+                        address: value.to_string(),
+                        inode: socket_inode(pid, fd),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    sockets
+}
+
+/// "socket:[12345]" -> 12345
+fn socket_inode(pid: u32, fd: u32) -> Option<u64> {
+    let target = fs::read_link(format!("/proc/{}/fd/{}", pid, fd)).ok()?;
+    target
+        .to_str()?
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// 1536 -> "1.5 KiB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::backend;
+use crate::clock::utc_timestamp;
+use crate::config;
+use crate::scanner::RawScan;
+
+/// How many status messages to keep for the bundle's "recent log".
+pub const LOG_CAPACITY: usize = 50;
+
+/// Timestamped status messages, oldest first.
+#[derive(Default)]
+pub struct MessageLog {
+    entries: VecDeque<String>,
+    last: Option<String>,
+}
+
+impl MessageLog {
+    /// Record `message` if it differs from the last one recorded.
+    pub fn record(&mut self, message: Option<&String>) {
+        if message == self.last.as_ref() {
+            return;
+        }
+        self.last = message.cloned();
+        if let Some(message) = message {
+            if self.entries.len() == LOG_CAPACITY {
+                self.entries.pop_front();
+            }
+            self.entries
+                .push_back(format!("[{}] {}", utc_timestamp(), message));
+        }
+    }
+}
+
+/// Write a plain-text bundle describing a backend failure, suitable for
+/// attaching to a GitHub issue. Returns the file's path.
+pub fn write_bundle(failure: &str, raw: &RawScan, log: &MessageLog) -> io::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("rip-diagnostics-{}.txt", secs));
+
+    let mut report = String::new();
+    report.push_str(&format!(
+        "rip {} diagnostic bundle\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    report.push_str(&format!("generated: {} UTC\n", utc_timestamp()));
+    report.push_str(&format!("failure: {}\n\n", failure));
+
+    report.push_str("== system ==\n");
+    report.push_str(&format!(
+        "os: {} {}\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    ));
+    for (program, args) in system_commands() {
+        // `lsof -v` reports on stderr, so keep both streams.
+        if let Ok(output) = Command::new(program).args(*args).output() {
+            report.push_str(&format!(
+                "{} {}: {}{}\n",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stdout).trim(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    report.push_str(&format!(
+        "args: {}\n\n",
+        std::env::args().collect::<Vec<_>>().join(" ")
+    ));
+
+    report.push_str("== config ==\n");
+    match config::path() {
+        Some(path) => match fs::read_to_string(&path) {
+            Ok(contents) => report.push_str(&format!("{}:\n{}\n", path.display(), contents)),
+            Err(e) => report.push_str(&format!("{}: {}\n\n", path.display(), e)),
+        },
+        None => report.push_str("(no home directory)\n\n"),
+    }
+
+    report.push_str("== backend ==\n");
+    let (backend, reason) = backend::selected();
+    report.push_str(&format!("selected: {} ({})\n", backend.name(), reason));
+    report.push_str(&format!("command: {}\n", raw.command));
+    report.push_str(&format!("status: {}\n", raw.status));
+    report.push_str(&format!("--- stderr ---\n{}\n", raw.stderr));
+    report.push_str(&format!("--- stdout ---\n{}\n\n", raw.stdout));
+
+    report.push_str("== recent log ==\n");
+    for entry in &log.entries {
+        report.push_str(entry);
+        report.push('\n');
+    }
+
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+fn system_commands() -> &'static [(&'static str, &'static [&'static str])] {
+    if cfg!(target_os = "macos") {
+        &[("sw_vers", &[]), ("uname", &["-a"]), ("lsof", &["-v"])]
+    } else {
+        &[("uname", &["-a"]), ("lsof", &["-v"])]
+    }
+}
@@ -0,0 +1,47 @@
+use std::io;
+use std::time::Instant;
+
+use crate::backend;
+use crate::portscan;
+
+/// Print which scanning backends can run on this machine, which one rip
+/// uses and why, and what a scan with it finds.
+pub fn run() -> io::Result<()> {
+    println!(
+        "{}",
+        tr!(
+            "rip {} on {}/{}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    );
+    println!();
+    for backend in backend::ALL {
+        let status = match backend.unavailable() {
+            Some(reason) => tr!("unavailable: {}", reason),
+            None if !backend.knows_owners() => tr!("available (no owners)"),
+            None => tr!("available"),
+        };
+        println!("  {:<12} {}", backend.name(), status);
+    }
+
+    let (backend, reason) = backend::selected();
+    println!();
+    println!("{}", tr!("Using: {} ({})", backend.name(), reason));
+    let started = Instant::now();
+    match backend.scan(&portscan::DEFAULT_RANGE) {
+        Ok((processes, stats)) => {
+            let ms = format!("{:.1}", started.elapsed().as_secs_f64() * 1000.0);
+            println!(
+                "{}",
+                tr!("Found {} listeners in {} ms", processes.len(), ms)
+            );
+            if let Some(failure) = stats.failure {
+                println!("{}", tr!("Warning: {}", failure));
+            }
+        }
+        Err(e) => println!("{}", tr!("{} failed: {}", backend.name(), e)),
+    }
+    Ok(())
+}
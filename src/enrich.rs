@@ -0,0 +1,715 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{PortProcess, conflict, container, procfs};
+
+const JETBRAINS_PRODUCTS: &[&str] = &[
+    "idea",
+    "pycharm",
+    "webstorm",
+    "goland",
+    "clion",
+    "rider",
+    "rubymine",
+    "phpstorm",
+    "datagrip",
+    "rustrover",
+    "jetbrains",
+];
+
+// VS Code and its forks, matched whole so `codeium` or `coder` is not one.
+// lsof cuts names to nine characters, so the long ones appear cut too.
+const VSCODE_NAMES: &[&str] = &[
+    "code",
+    "code-oss",
+    "code-insiders",
+    "code-insi",
+    "code-server",
+    "code-serv",
+    "codium",
+    "cursor",
+];
+
+// lsof truncates command names, so these are prefixes.
+const LANGUAGE_SERVERS: &[&str] = &[
+    "rust-ana", "gopls", "clangd", "pyright", "pylsp", "jdtls", "tsserver", "typescri",
+];
+
+/// JetBrains' built-in web server.
+const JETBRAINS_PORT: u16 = 63342;
+/// Default `node --inspect` port.
+const NODE_INSPECT_PORT: u16 = 9229;
+
+/// How long `netstat -an`'s counts are reused for. It walks every socket
+/// and can take a second on a busy Mac, far too long to run on each scan.
+const NETSTAT_EVERY: Duration = Duration::from_secs(10);
+
+/// Established connections per local address and port.
+type Connections = HashMap<(String, u16), usize>;
+
+/// The last `netstat -an` counts, and when they were taken.
+static NETSTAT: Mutex<Option<(Instant, Option<Connections>)>> = Mutex::new(None);
+
+/// Contexts by PID, port and start time.
+type Contexts = HashMap<(u32, u16, String), Option<String>>;
+
+/// Contexts already worked out: a process's argv does not change while it
+/// runs, and `jcmd` attaches to the JVM, which takes far longer than a scan
+/// should.
+static CONTEXTS: Mutex<Option<Contexts>> = Mutex::new(None);
+
+/// Fill in `context` for runtimes whose process name alone says nothing
+/// about what is running (every Spring Boot app is just "java", every
+/// dev server just "python"), and tag IDE/debugger helpers.
+pub fn enrich(processes: &mut [PortProcess]) {
+    let nice = nice_values();
+    let established = established_connections();
+    for process in processes.iter_mut() {
+        process.tag = ide_tag(process);
+        process.nice = nice.get(&process.pid).copied();
+    }
+    let listeners: Vec<(String, u16)> = processes
+        .iter()
+        .map(|p| (p.address.clone(), p.port))
+        .collect();
+    for process in processes.iter_mut() {
+        process.connections = established
+            .as_ref()
+            .filter(|_| process.protocol == "TCP" && process.listening)
+            .map(|counts| accepted(process, &listeners, counts));
+    }
+    cached_contexts(processes);
+    if processes.iter().any(|p| container::is_port_proxy(&p.name)) {
+        proxy_context(processes);
+    }
+}
+
+/// Fill in each runtime's context, reusing the one worked out for the same
+/// process on an earlier scan. A PID that was reused has a new start time,
+/// so it is never given its predecessor's context.
+fn cached_contexts(processes: &mut [PortProcess]) {
+    let mut cache = CONTEXTS.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(HashMap::new);
+    cache.retain(|(pid, port, _), _| processes.iter().any(|p| p.pid == *pid && p.port == *port));
+    for process in processes.iter_mut() {
+        if !has_context(&process.name) {
+            continue;
+        }
+        let Some(started) = start_time(process.pid) else {
+            process.context = runtime_context(process);
+            continue;
+        };
+        process.context = cache
+            .entry((process.pid, process.port, started))
+            .or_insert_with(|| runtime_context(process))
+            .clone();
+    }
+}
+
+/// Whether `runtime_context` has anything to say about a process called
+/// `name`.
+fn has_context(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["java", "python", "kubectl"]
+        .iter()
+        .any(|runtime| name.starts_with(runtime))
+}
+
+/// The main class, script or forward a `java`, `python` or `kubectl`
+/// listener runs.
+fn runtime_context(process: &PortProcess) -> Option<String> {
+    let name = process.name.to_lowercase();
+    if name.starts_with("java") {
+        command_line(process.pid)
+            .and_then(|argv| java_main(&argv))
+            .or_else(|| jcmd_main(process.pid))
+    } else if name.starts_with("python") {
+        command_line(process.pid).and_then(|argv| python_context(process.pid, &argv))
+    } else if name.starts_with("kubectl") {
+        command_line(process.pid)
+            .and_then(|argv| port_forward(&argv))
+            .map(|forward| forward.summary(process.port))
+    } else {
+        None
+    }
+}
+
+/// When a process started, in whatever form the system gives: clock ticks
+/// since boot from `/proc/<pid>/stat` on Linux, `ps`'s `lstart` elsewhere.
+fn start_time(pid: u32) -> Option<String> {
+    if let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        // The name in parentheses may hold spaces; starttime is the 22nd
+        // field, the 20th after it.
+        let (_, fields) = stat.rsplit_once(')')?;
+        return fields.split_whitespace().nth(19).map(str::to_string);
+    }
+    process_status(pid)
+        .map(|(_, started)| started)
+        .filter(|started| !started.is_empty())
+}
+
+/// Every port a Docker container publishes is held by a proxy: Docker
+/// Desktop's VM backend on macOS, `docker-proxy` or `rootlesskit` on Linux.
+/// Name the container, its image and the port it forwards to instead, e.g.
+/// "web [nginx:1.25] -> 80/tcp".
+fn proxy_context(processes: &mut [PortProcess]) {
+    let published = container::published_ports();
+    for process in processes
+        .iter_mut()
+        .filter(|p| container::is_port_proxy(&p.name))
+    {
+        process.context = published
+            .iter()
+            .find(|p| {
+                p.host_port == process.port && p.protocol.eq_ignore_ascii_case(&process.protocol)
+            })
+            .map(|p| format!("{} [{}] -> {}/{}", p.name, p.image, p.target, p.protocol));
+    }
+}
+
+/// Recognise listeners that belong to IDEs, editors, language servers and
+/// debuggers rather than to the user's own services.
+pub fn ide_tag(process: &PortProcess) -> Option<&'static str> {
+    let name = process.name.to_lowercase();
+
+    if process.port == JETBRAINS_PORT || JETBRAINS_PRODUCTS.iter().any(|p| name.starts_with(p)) {
+        return Some("jetbrains");
+    }
+    // `Code Helper (Plugin)` is VS Code too; lsof may print its space as
+    // `\x20`.
+    let program = name.split([' ', '\\']).next().unwrap_or(&name);
+    if VSCODE_NAMES.contains(&program) {
+        return Some("vscode");
+    }
+    if LANGUAGE_SERVERS.iter().any(|p| name.starts_with(p)) {
+        return Some("lsp");
+    }
+    if name.starts_with("node") {
+        let argv = command_line(process.pid).unwrap_or_default();
+        if argv.iter().any(|arg| arg.contains(".vscode-server")) {
+            return Some("vscode");
+        }
+        if process.port == NODE_INSPECT_PORT || argv.iter().any(|arg| arg.starts_with("--inspect"))
+        {
+            return Some("debugger");
+        }
+    }
+    None
+}
+
+/// Parent PID of a running process.
+pub fn parent_pid(pid: u32) -> Option<u32> {
+    let output = Command::new("ps")
+        .args(["-o", "ppid=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Nice value of every process, from a single `ps` run. Processes without
+/// one (kernel threads show `-`) are left out.
+fn nice_values() -> HashMap<u32, i32> {
+    let Ok(output) = Command::new("ps").args(["-A", "-o", "pid=,ni="]).output() else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+/// The connections `process` accepted: those to its own address, or for a
+/// wildcard bind, those to any address of its family on its port that no
+/// listener in `listeners` binds specifically.
+pub fn accepted(
+    process: &PortProcess,
+    listeners: &[(String, u16)],
+    counts: &HashMap<(String, u16), usize>,
+) -> usize {
+    if !conflict::is_wildcard(&process.address) {
+        let local = (process.address.clone(), process.port);
+        return counts.get(&local).copied().unwrap_or(0);
+    }
+    counts
+        .iter()
+        .filter(|((address, port), _)| {
+            *port == process.port
+                && match process.address.as_str() {
+                    "0.0.0.0" => !address.starts_with('['),
+                    "::" | "[::]" => address.starts_with('['),
+                    _ => true,
+                }
+                && !listeners
+                    .iter()
+                    .any(|(bound, p)| p == port && bound == address)
+        })
+        .map(|(_, n)| n)
+        .sum()
+}
+
+/// Established TCP connections per local address and port, from one pass
+/// over every socket, not just the listening ones the backends list:
+/// `/proc/net` on Linux, `netstat -an` elsewhere, at most every
+/// `NETSTAT_EVERY`.
+fn established_connections() -> Option<Connections> {
+    if cfg!(target_os = "linux") {
+        return procfs::established().ok();
+    }
+    let mut last = NETSTAT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, counts)) = last.as_ref()
+        && at.elapsed() < NETSTAT_EVERY
+    {
+        return counts.clone();
+    }
+    let counts = Command::new("netstat")
+        .arg("-an")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            let mut counts = HashMap::new();
+            count_netstat_established(&String::from_utf8_lossy(&output.stdout), &mut counts);
+            counts
+        });
+    *last = Some((Instant::now(), counts.clone()));
+    counts
+}
+
+/// Add the established TCP connections in `netstat -an` output to
+/// `counts`, by local address and port. macOS, the BSDs and Windows all
+/// print the local address third from last, before the peer and the state:
+/// `127.0.0.1.3000` and `::1.3000` on macOS and the BSDs, `127.0.0.1:3000`
+/// and `[::1]:3000` on Windows.
+pub fn count_netstat_established(stdout: &str, counts: &mut HashMap<(String, u16), usize>) {
+    for line in stdout.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [protocol, .., local, _, "ESTABLISHED"] = fields.as_slice() else {
+            continue;
+        };
+        if !protocol.to_lowercase().starts_with("tcp") {
+            continue;
+        }
+        let Some((address, Ok(port))) = local
+            .rsplit_once(['.', ':'])
+            .map(|(address, port)| (address, port.parse::<u16>()))
+        else {
+            continue;
+        };
+        // A scoped IPv6 address names its interface: `fe80::1%lo0`.
+        let address = address.split('%').next().unwrap_or(address);
+        let address = if address.contains(':') && !address.starts_with('[') {
+            format!("[{}]", address)
+        } else {
+            address.to_string()
+        };
+        *counts.entry((address, port)).or_insert(0) += 1;
+    }
+}
+
+/// The process group and session a process belongs to: what job control
+/// acts on, and what a group kill would signal.
+pub struct ProcessGroup {
+    pub pgid: u32,
+    pub sid: u32,
+    /// The group's leader has exited while members still run, often a
+    /// shell's job left behind holding a port.
+    pub leader_gone: bool,
+}
+
+#[cfg(unix)]
+pub fn process_group(pid: u32) -> Option<ProcessGroup> {
+    let ids = |pid: u32| {
+        let pid = pid as libc::pid_t;
+        let (pgid, sid) = unsafe { (libc::getpgid(pid), libc::getsid(pid)) };
+        (pgid >= 0 && sid >= 0).then_some((pgid as u32, sid as u32))
+    };
+    let (pgid, sid) = ids(pid)?;
+    Some(ProcessGroup {
+        pgid,
+        sid,
+        leader_gone: ids(pgid).is_none(),
+    })
+}
+
+/// Windows has no process groups or sessions in this sense.
+#[cfg(not(unix))]
+pub fn process_group(_pid: u32) -> Option<ProcessGroup> {
+    None
+}
+
+/// The processes in group `pgid`, as (pid, name), from a single `ps` run.
+pub fn group_members(pgid: u32) -> Vec<(u32, String)> {
+    let Ok(output) = Command::new("ps")
+        .args(["-A", "-o", "pid=,pgid=,comm="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let group: u32 = fields.next()?.parse().ok()?;
+            let name = fields.collect::<Vec<_>>().join(" ");
+            (group == pgid).then(|| (pid, basename(&name).to_string()))
+        })
+        .collect()
+}
+
+/// Path of the program a process is running: exact from `/proc` where
+/// available, otherwise from `ps` when it reports an absolute path (macOS).
+pub fn executable(pid: u32) -> Option<PathBuf> {
+    if let Ok(path) = fs::read_link(format!("/proc/{}/exe", pid)) {
+        return Some(path);
+    }
+    let output = Command::new("ps")
+        .args(["-o", "comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    path.is_absolute().then_some(path)
+}
+
+/// Working directory of a running process: from `/proc` where available,
+/// otherwise from lsof's `cwd` entry (macOS).
+pub fn working_directory(pid: u32) -> Option<PathBuf> {
+    if let Ok(path) = fs::read_link(format!("/proc/{}/cwd", pid)) {
+        return Some(path);
+    }
+    let output = Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+/// Whether a process's working directory is `dir` or below it.
+pub fn works_under(pid: u32, dir: &Path) -> bool {
+    working_directory(pid).is_some_and(|cwd| cwd.starts_with(dir))
+}
+
+/// How long a process has been running, from `ps`'s `[[DD-]HH:]MM:SS`
+/// elapsed time.
+pub fn running_for(pid: u32) -> Option<Duration> {
+    let output = Command::new("ps")
+        .args(["-o", "etime=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (days, clock) = match text.trim().split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, text.trim()),
+    };
+    let mut secs = 0;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(Duration::from_secs(days * 86_400 + secs))
+}
+
+/// Resident memory of a running process in bytes; `ps` reports it in KiB.
+pub fn resident_memory(pid: u32) -> Option<u64> {
+    let output = Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let kib: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Login name of the user running process `pid`.
+pub fn process_user(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "user=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!user.is_empty()).then_some(user)
+}
+
+/// Login name of the user running rip.
+pub fn current_user() -> Option<String> {
+    let output = Command::new("id").arg("-un").output().ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Scheduler state (e.g. "S", "Z") and start time of a running process. The
+/// start time tells it apart from a later process that reused the PID.
+pub fn process_status(pid: u32) -> Option<(String, String)> {
+    if cfg!(windows) {
+        return windows_process_status(pid);
+    }
+    let output = Command::new("ps")
+        .args(["-o", "stat=,lstart=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (state, started) = text.trim().split_once(char::is_whitespace)?;
+    Some((state.to_string(), started.trim().to_string()))
+}
+
+/// tasklist knows neither states nor start times; a running process is
+/// reported as "R" with an empty start time.
+fn windows_process_status(pid: u32) -> Option<(String, String)> {
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let quoted = format!("\"{}\"", pid);
+    text.lines()
+        .any(|line| line.split(',').nth(1) == Some(quoted.as_str()))
+        .then(|| ("R".to_string(), String::new()))
+}
+
+/// The argument vector of a running process: exact from `/proc` where
+/// available, otherwise whitespace-split from `ps`.
+pub fn command_line(pid: u32) -> Option<Vec<String>> {
+    if let Ok(raw) = fs::read(format!("/proc/{}/cmdline", pid)) {
+        let argv: Vec<String> = raw
+            .split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        if !argv.is_empty() {
+            return Some(argv);
+        }
+    }
+
+    let output = Command::new("ps")
+        .args(["-o", "args=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let argv: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    (!argv.is_empty()).then_some(argv)
+}
+
+/// The jar, module or main class a `java` command line launches.
+fn java_main(argv: &[String]) -> Option<String> {
+    let mut args = argv.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-jar" => return args.next().map(|jar| basename(jar).to_string()),
+            "-m" | "--module" => return args.next().cloned(),
+            "-cp" | "-classpath" | "--class-path" | "-p" | "--module-path" | "--add-modules"
+            | "--add-opens" | "--add-exports" => {
+                args.next();
+            }
+            a if a.starts_with('-') => {}
+            main_class => return Some(main_class.to_string()),
+        }
+    }
+    None
+}
+
+/// What a `python` command line runs, plus its virtualenv if any, e.g.
+/// `uvicorn app.main:app [myproj/.venv]`.
+fn python_context(pid: u32, argv: &[String]) -> Option<String> {
+    let target = python_target(argv);
+    let venv = virtualenv(pid, argv);
+    match (target, venv) {
+        (Some(target), Some(venv)) => Some(format!("{} [{}]", target, venv)),
+        (Some(target), None) => Some(target),
+        (None, Some(venv)) => Some(format!("[{}]", venv)),
+        (None, None) => None,
+    }
+}
+
+fn python_target(argv: &[String]) -> Option<String> {
+    const SHOWN_ARGS: usize = 3;
+
+    let mut args = argv.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" => {
+                let module = args.next()?;
+                let rest: Vec<&str> = args.take(SHOWN_ARGS).map(String::as_str).collect();
+                return Some(join_command(module, &rest));
+            }
+            "-c" => return Some("-c".to_string()),
+            "-X" | "-W" | "--check-hash-based-pycs" => {
+                args.next();
+            }
+            a if a.starts_with('-') => {}
+            script => {
+                let rest: Vec<&str> = args.take(SHOWN_ARGS).map(String::as_str).collect();
+                return Some(join_command(basename(script), &rest));
+            }
+        }
+    }
+    None
+}
+
+/// What a `kubectl port-forward` forwards to, from its command line.
+pub struct PortForward {
+    /// `pod/web-7d9f`, `svc/api`, `deploy/worker`: what kubectl was given.
+    pub target: String,
+    /// `None` when kubectl uses the kubeconfig context's namespace.
+    pub namespace: Option<String>,
+    /// The kubeconfig context, when not the current one.
+    pub context: Option<String>,
+    /// (local port, remote port or port name). No local port when kubectl
+    /// picks a random one (`:80`).
+    pub ports: Vec<(Option<u16>, String)>,
+}
+
+impl PortForward {
+    /// The list's context column, e.g. "svc/api [staging] -> 80".
+    pub fn summary(&self, local: u16) -> String {
+        let mut summary = self.target.clone();
+        if let Some(namespace) = &self.namespace {
+            summary.push_str(&format!(" [{}]", namespace));
+        }
+        let remote = self
+            .ports
+            .iter()
+            .find(|(port, _)| *port == Some(local))
+            .or_else(|| self.ports.first());
+        if let Some((_, remote)) = remote {
+            summary.push_str(&format!(" -> {}", remote));
+        }
+        summary
+    }
+}
+
+/// Parse `kubectl [flags] port-forward [flags] TYPE/NAME [LOCAL:]REMOTE...`.
+/// `None` for any other kubectl command.
+pub fn port_forward(argv: &[String]) -> Option<PortForward> {
+    // kubectl flags that take a value, which must not be read as the target.
+    const VALUED: &[&str] = &[
+        "--address",
+        "--as",
+        "--as-group",
+        "--cache-dir",
+        "--certificate-authority",
+        "--client-certificate",
+        "--client-key",
+        "--cluster",
+        "--context",
+        "--kubeconfig",
+        "-n",
+        "--namespace",
+        "--pod-running-timeout",
+        "--request-timeout",
+        "-s",
+        "--server",
+        "--tls-server-name",
+        "--token",
+        "--user",
+        "-v",
+    ];
+
+    let mut namespace = None;
+    let mut context = None;
+    let mut positional = Vec::new();
+    let mut args = argv.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with('-') => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        if !flag.starts_with('-') || flag == "-" {
+            positional.push(arg.as_str());
+            continue;
+        }
+        if !VALUED.contains(&flag) {
+            continue;
+        }
+        let value = inline.or_else(|| args.next().cloned());
+        match flag {
+            "-n" | "--namespace" => namespace = value,
+            "--context" => context = value,
+            _ => {}
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    positional.find(|arg| *arg == "port-forward")?;
+    let target = positional.next()?;
+    let target = if target.contains('/') {
+        target.to_string()
+    } else {
+        format!("pod/{}", target)
+    };
+    let ports = positional
+        .map(|spec| match spec.split_once(':') {
+            Some((local, remote)) => (local.parse().ok(), remote.to_string()),
+            None => (spec.parse().ok(), spec.to_string()),
+        })
+        .collect();
+    Some(PortForward {
+        target,
+        namespace,
+        context,
+        ports,
+    })
+}
+
+fn join_command(program: &str, args: &[&str]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The virtualenv a python process runs in: the interpreter's own venv
+/// (`<venv>/bin/python` next to a `pyvenv.cfg`), else `$VIRTUAL_ENV`.
+fn virtualenv(pid: u32, argv: &[String]) -> Option<String> {
+    let from_interpreter = Path::new(argv.first()?)
+        .parent()
+        .and_then(Path::parent)
+        .filter(|venv| venv.join("pyvenv.cfg").is_file())
+        .map(Path::to_path_buf);
+
+    let venv = from_interpreter.or_else(|| {
+        let environ = fs::read(format!("/proc/{}/environ", pid)).ok()?;
+        environ
+            .split(|b| *b == 0)
+            .find_map(|var| var.strip_prefix(b"VIRTUAL_ENV="))
+            .map(|path| String::from_utf8_lossy(path).into_owned().into())
+    })?;
+
+    let name = venv.file_name()?.to_string_lossy().into_owned();
+    // Generic names like ".venv" only make sense next to their project.
+    if matches!(name.as_str(), ".venv" | "venv" | ".env" | "env")
+        && let Some(project) = venv.parent().and_then(Path::file_name)
+    {
+        return Some(format!("{}/{}", project.to_string_lossy(), name));
+    }
+    Some(name)
+}
+
+/// Ask the JVM itself, for launchers that hide the main class from argv.
+fn jcmd_main(pid: u32) -> Option<String> {
+    let output = Command::new("jcmd")
+        .args([&pid.to_string(), "VM.command_line"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("java_command:"))
+        .and_then(|command| command.split_whitespace().next())
+        .map(|main| basename(main).to_string())
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use crate::{PortProcess, enrich};
+
+/// A step of a graceful kill, reported as it happens.
+pub enum Step {
+    /// SIGTERM was delivered and the process is still running; this many
+    /// seconds of the grace period are left.
+    Waiting(u64),
+    /// The process exited by itself this long after SIGTERM.
+    Exited(Duration),
+    /// The grace period ran out with the process still running; it is time
+    /// for SIGKILL.
+    Expired,
+}
+
+/// A graceful kill in progress: SIGTERM has been sent and the process has
+/// until the grace period is up to exit by itself. The caller sends the
+/// signals, so they go through its `Signaller` and kill log.
+pub struct Escalation {
+    pub process: PortProcess,
+    /// Its start time, so a reused PID does not look like it survived.
+    started: Option<String>,
+    sent: Instant,
+    grace: Duration,
+    reported: Option<u64>,
+}
+
+impl Escalation {
+    /// Start the clock on `process`. Call it just before sending SIGTERM,
+    /// while the process is certainly still there to take a start time from.
+    pub fn new(process: &PortProcess, grace: Duration) -> Self {
+        Escalation {
+            process: process.clone(),
+            started: enrich::process_status(process.pid).map(|(_, started)| started),
+            sent: Instant::now(),
+            grace,
+            reported: None,
+        }
+    }
+
+    /// What has changed since the last poll: the process exited, the grace
+    /// period ran out, or another second of it went by.
+    pub fn poll(&mut self) -> Option<Step> {
+        if crate::process::has_exited(self.process.pid, self.started.as_deref()) {
+            return Some(Step::Exited(self.sent.elapsed()));
+        }
+        let Some(left) = self.grace.checked_sub(self.sent.elapsed()) else {
+            return Some(Step::Expired);
+        };
+        // Rounded up, so the status bar counts down once a second.
+        let secs = left.as_secs() + 1;
+        if self.reported == Some(secs) {
+            return None;
+        }
+        self.reported = Some(secs);
+        Some(Step::Waiting(secs))
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::process::Command;
+
+use crate::PortProcess;
+
+/// Whether inbound traffic can reach a listener.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Allowed,
+    Blocked,
+    /// Bound to loopback, so unreachable from the network whatever the rules say.
+    LocalOnly,
+    Unknown,
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Allowed => "allow",
+            Verdict::Blocked => "block",
+            Verdict::LocalOnly => "local",
+            Verdict::Unknown => "?",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// `None` matches any protocol.
+    protocol: Option<String>,
+    /// `None` matches any port.
+    ports: Option<Vec<RangeInclusive<u16>>>,
+    allow: bool,
+    /// pf `quick`: stop evaluating at this rule even in last-match mode.
+    quick: bool,
+}
+
+impl Rule {
+    fn matches(&self, port: u16, protocol: &str) -> bool {
+        let protocol_ok = self
+            .protocol
+            .as_deref()
+            .is_none_or(|p| p.eq_ignore_ascii_case(protocol));
+        let port_ok = self
+            .ports
+            .as_ref()
+            .is_none_or(|ranges| ranges.iter().any(|r| r.contains(&port)));
+        protocol_ok && port_ok
+    }
+}
+
+/// The inbound rules of the local firewall, reduced to what matters for
+/// "is this port reachable".
+#[derive(Debug)]
+pub struct Ruleset {
+    source: &'static str,
+    rules: Vec<Rule>,
+    default_allow: bool,
+    /// pf evaluates every rule and the last match wins; the others stop at
+    /// the first match.
+    last_match: bool,
+}
+
+impl Ruleset {
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    pub fn verdict(&self, process: &PortProcess) -> Verdict {
+        if is_loopback(&process.address) {
+            return Verdict::LocalOnly;
+        }
+
+        let mut allowed = self.default_allow;
+        for rule in self
+            .rules
+            .iter()
+            .filter(|r| r.matches(process.port, &process.protocol))
+        {
+            allowed = rule.allow;
+            if !self.last_match || rule.quick {
+                break;
+            }
+        }
+
+        if allowed {
+            Verdict::Allowed
+        } else {
+            Verdict::Blocked
+        }
+    }
+}
+
+fn is_loopback(address: &str) -> bool {
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    address.starts_with("127.") || address == "::1" || address == "localhost"
+}
+
+/// Read the active firewall's inbound rules. `Err` carries a reason shown
+/// to the user (usually missing privileges).
+pub fn load() -> Result<Ruleset, String> {
+    if cfg!(target_os = "macos") {
+        run("pfctl", &["-sr"]).map(|out| parse_pf(&out))
+    } else if cfg!(windows) {
+        run(
+            "netsh",
+            &[
+                "advfirewall",
+                "firewall",
+                "show",
+                "rule",
+                "name=all",
+                "dir=in",
+            ],
+        )
+        .map(|out| parse_netsh(&out))
+    } else {
+        run("nft", &["list", "ruleset"])
+            .map(|out| parse_nft(&out))
+            .or_else(|_| run("iptables", &["-S"]).map(|out| parse_iptables(&out)))
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!(
+            "{}: {}",
+            program,
+            stderr.lines().next().unwrap_or("failed (needs root?)")
+        ))
+    }
+}
+
+fn parse_port_list(spec: &str, range_sep: char) -> Vec<RangeInclusive<u16>> {
+    spec.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .filter_map(|item| match item.split_once(range_sep) {
+            Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+            None => {
+                let port = item.parse().ok()?;
+                Some(port..=port)
+            }
+        })
+        .collect()
+}
+
+/// The port spec following `keyword`: a single token, or a `{ ... }` set.
+fn port_spec<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = line[line.find(keyword)? + keyword.len()..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+    if let Some(set) = rest.strip_prefix('{') {
+        set.split_once('}').map(|(inner, _)| inner)
+    } else {
+        rest.split_whitespace().next()
+    }
+}
+
+/// What a rule does with a packet it matches.
+#[derive(Debug)]
+enum Target {
+    Verdict(bool),
+    /// Evaluate a user chain, then carry on here unless it decided.
+    Jump(String),
+    /// Evaluate a user chain and never come back.
+    Goto(String),
+    Return,
+}
+
+/// A rule as written, in a chain that may be reached through jumps.
+#[derive(Debug)]
+struct ChainRule {
+    protocol: Option<String>,
+    ports: Option<Vec<RangeInclusive<u16>>>,
+    target: Target,
+}
+
+/// How deep jumps are followed, against chains that jump in circles.
+const MAX_JUMPS: usize = 16;
+
+/// The rules of `chain` and of every user chain it reaches, in evaluation
+/// order, each narrowed by the protocol and ports of the jumps that lead
+/// to it. Returns whether evaluation ends in this chain (an unconditional
+/// verdict or goto), so the caller's later rules are never reached.
+fn inline(
+    chains: &HashMap<String, Vec<ChainRule>>,
+    chain: &str,
+    outer: (Option<&str>, Option<&[RangeInclusive<u16>]>),
+    depth: usize,
+    rules: &mut Vec<Rule>,
+) -> bool {
+    let Some(entries) = chains.get(chain).filter(|_| depth <= MAX_JUMPS) else {
+        return false;
+    };
+    for entry in entries {
+        let protocol = match (outer.0, entry.protocol.as_deref()) {
+            (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b) => continue,
+            (a, b) => b.or(a),
+        };
+        let ports = match (outer.1, entry.ports.as_deref()) {
+            (Some(a), Some(b)) => {
+                let both = intersect(a, b);
+                if both.is_empty() {
+                    continue;
+                }
+                Some(both)
+            }
+            (a, b) => b.or(a).map(<[_]>::to_vec),
+        };
+        let unconditional = protocol.is_none() && ports.is_none();
+        match &entry.target {
+            Target::Verdict(allow) => {
+                rules.push(Rule {
+                    protocol: protocol.map(str::to_string),
+                    ports,
+                    allow: *allow,
+                    quick: false,
+                });
+                if unconditional {
+                    return true;
+                }
+            }
+            Target::Jump(target) | Target::Goto(target) => {
+                let ended = inline(
+                    chains,
+                    target,
+                    (protocol, ports.as_deref()),
+                    depth + 1,
+                    rules,
+                );
+                let goto = matches!(entry.target, Target::Goto(_));
+                if (ended || goto) && unconditional {
+                    return true;
+                }
+            }
+            // Only an unconditional return surely skips the rest.
+            Target::Return if unconditional => return false,
+            Target::Return => {}
+        }
+    }
+    false
+}
+
+fn intersect(a: &[RangeInclusive<u16>], b: &[RangeInclusive<u16>]) -> Vec<RangeInclusive<u16>> {
+    let mut both = Vec::new();
+    for x in a {
+        for y in b {
+            let (start, end) = (*x.start().max(y.start()), *x.end().min(y.end()));
+            if start <= end {
+                both.push(start..=end);
+            }
+        }
+    }
+    both
+}
+
+/// nftables' filter chains on the input hook, in `ip`, `ip6` and `inet`
+/// tables (`arp` and `bridge` tables have input hooks too, for other
+/// traffic), with the user chains they jump to.
+pub fn parse_nft(ruleset: &str) -> Ruleset {
+    // Chains are only unique within a table.
+    let mut chains: HashMap<String, Vec<ChainRule>> = HashMap::new();
+    let mut inputs = Vec::new();
+    let mut default_allow = true;
+    let mut table: Option<String> = None;
+    let mut chain: Option<String> = None;
+
+    for line in ruleset.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix("table ") {
+            let mut words = header.split_whitespace();
+            let family = words.next().unwrap_or_default();
+            let name = words.next().unwrap_or_default();
+            table = matches!(family, "ip" | "ip6" | "inet").then(|| format!("{} {}", family, name));
+            chain = None;
+        } else if let Some(header) = line.strip_prefix("chain ") {
+            chain = table.as_ref().map(|table| {
+                let name = header.trim_end_matches('{').trim();
+                format!("{}/{}", table, name)
+            });
+        } else if line == "}" {
+            chain = None;
+        } else if let Some(current) = &chain {
+            if line.starts_with("type filter hook input") {
+                inputs.push(current.clone());
+                if line.contains("policy drop") {
+                    default_allow = false;
+                }
+                continue;
+            }
+            let table = current.split('/').next().unwrap_or_default();
+            if let Some(rule) = nft_rule(line, table) {
+                chains.entry(current.clone()).or_default().push(rule);
+            }
+        }
+    }
+
+    let mut rules = Vec::new();
+    for input in &inputs {
+        inline(&chains, input, (None, None), 0, &mut rules);
+    }
+    Ruleset {
+        source: "nftables",
+        rules,
+        default_allow,
+        last_match: false,
+    }
+}
+
+/// One nft rule line, or `None` for one that does not decide new inbound
+/// traffic from anywhere: interface-, state-, source- or
+/// destination-specific rules, logging, counters.
+fn nft_rule(line: &str, table: &str) -> Option<ChainRule> {
+    const NARROWING: &[&str] = &["iif", "ct state", "saddr", "daddr", "fib "];
+    if NARROWING.iter().any(|word| line.contains(word)) {
+        return None;
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let target = match words.as_slice() {
+        [.., "accept"] => Target::Verdict(true),
+        [.., "drop"] => Target::Verdict(false),
+        [.., "jump", chain] => Target::Jump(format!("{}/{}", table, chain)),
+        [.., "goto", chain] => Target::Goto(format!("{}/{}", table, chain)),
+        [.., "return"] => Target::Return,
+        _ if line.contains(" reject") || line.starts_with("reject") => Target::Verdict(false),
+        _ => return None,
+    };
+    let protocol = ["tcp", "udp"]
+        .into_iter()
+        .find(|p| line.starts_with(p) || line.contains(&format!("{} dport", p)))
+        .map(str::to_string);
+    let ports = port_spec(line, "dport ").map(|spec| parse_port_list(spec, '-'));
+    Some(ChainRule {
+        protocol,
+        ports,
+        target,
+    })
+}
+
+/// `iptables -S`: the INPUT chain and the user chains it jumps to (ufw's
+/// and firewalld's rules all live in those). Rules on other built-in
+/// chains (OUTPUT, FORWARD) and chains INPUT never reaches are ignored.
+pub fn parse_iptables(rules_out: &str) -> Ruleset {
+    let mut chains: HashMap<String, Vec<ChainRule>> = HashMap::new();
+    let mut default_allow = true;
+
+    for line in rules_out.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let value = |flag: &str| {
+            words
+                .iter()
+                .position(|w| *w == flag)
+                .and_then(|i| words.get(i + 1))
+                .copied()
+        };
+
+        match words.as_slice() {
+            ["-P", "INPUT", policy, ..] => default_allow = *policy == "ACCEPT",
+            ["-A", chain, ..] => {
+                if ["-i", "-s", "-d", "--dst-type", "--pkt-type"]
+                    .iter()
+                    .any(|flag| value(flag).is_some())
+                    || line.contains("--state")
+                    || line.contains("--ctstate")
+                {
+                    continue;
+                }
+                let target = match (value("-j"), value("-g")) {
+                    (Some("ACCEPT"), _) => Target::Verdict(true),
+                    (Some("DROP" | "REJECT"), _) => Target::Verdict(false),
+                    (Some("RETURN"), _) => Target::Return,
+                    // LOG, MARK and friends are no chain, so `inline` carries
+                    // on past them.
+                    (Some(target), _) => Target::Jump(target.to_string()),
+                    (_, Some(target)) => Target::Goto(target.to_string()),
+                    _ => continue,
+                };
+                let ports = value("--dport")
+                    .or_else(|| value("--dports"))
+                    .map(|spec| parse_port_list(spec, ':'));
+                chains
+                    .entry(chain.to_string())
+                    .or_default()
+                    .push(ChainRule {
+                        protocol: value("-p").map(str::to_string),
+                        ports,
+                        target,
+                    });
+            }
+            _ => {}
+        }
+    }
+
+    let mut rules = Vec::new();
+    inline(&chains, "INPUT", (None, None), 0, &mut rules);
+    Ruleset {
+        source: "iptables",
+        rules,
+        default_allow,
+        last_match: false,
+    }
+}
+
+fn parse_pf(rules_out: &str) -> Ruleset {
+    let mut rules = Vec::new();
+
+    for line in rules_out.lines().map(str::trim) {
+        let allow = if line.starts_with("pass") {
+            true
+        } else if line.starts_with("block") {
+            false
+        } else {
+            continue;
+        };
+        if line.contains(" out ") || line.contains(" on lo") {
+            continue;
+        }
+        // `proto { tcp udp }` is treated as any protocol.
+        let protocol = port_spec(line, "proto ")
+            .filter(|p| !p.contains(' '))
+            .map(str::to_string);
+        // Only the destination port matters: the last `port` in the rule.
+        let ports = line
+            .rfind("port ")
+            .and_then(|i| port_spec(&line[i..], "port "))
+            .map(|spec| parse_port_list(spec, ':'));
+        rules.push(Rule {
+            protocol,
+            ports,
+            allow,
+            quick: line.contains(" quick"),
+        });
+    }
+
+    Ruleset {
+        source: "pf",
+        rules,
+        default_allow: true,
+        last_match: true,
+    }
+}
+
+fn parse_netsh(rules_out: &str) -> Ruleset {
+    let mut rules = Vec::new();
+    let mut fields: Vec<(String, String)> = Vec::new();
+
+    let mut flush = |fields: &mut Vec<(String, String)>| {
+        let get = |key: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        if get("Enabled") == Some("Yes") && get("Direction") == Some("In") {
+            let allow = get("Action") == Some("Allow");
+            let protocol = get("Protocol").filter(|p| *p != "Any").map(str::to_string);
+            let ports = get("LocalPort")
+                .filter(|p| *p != "Any")
+                .map(|spec| parse_port_list(spec, '-'));
+            rules.push(Rule {
+                protocol,
+                ports,
+                allow,
+                quick: false,
+            });
+        }
+        fields.clear();
+    };
+
+    for line in rules_out.lines() {
+        if line.starts_with("Rule Name:") {
+            flush(&mut fields);
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            fields.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    flush(&mut fields);
+
+    // Windows Firewall blocks unsolicited inbound traffic by default, and a
+    // matching block rule overrides any allow rule.
+    rules.sort_by_key(|r| r.allow);
+
+    Ruleset {
+        source: "Windows Firewall",
+        rules,
+        default_allow: false,
+        last_match: false,
+    }
+}
@@ -0,0 +1,168 @@
+use std::io::{self, Write};
+
+use crate::PortProcess;
+use crate::json::Value;
+
+/// Writes a list of listeners in one output format, for `rip --list`.
+/// Rows carry the fields `serve --stdio` returns, in the same order.
+pub trait Formatter: Sync {
+    /// The name `--format` selects it by.
+    fn name(&self) -> &'static str;
+    fn write(&self, out: &mut dyn Write, processes: &[PortProcess]) -> io::Result<()>;
+}
+
+/// Every formatter `--format` accepts; the first is the default.
+pub static FORMATTERS: &[&dyn Formatter] = &[&Text, &Json, &Yaml, &Csv, &Markdown];
+
+/// The formatter called `name`.
+pub fn by_name(name: &str) -> Result<&'static dyn Formatter, String> {
+    FORMATTERS
+        .iter()
+        .copied()
+        .find(|f| f.name() == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = FORMATTERS.iter().map(|f| f.name()).collect();
+            tr!(
+                "unknown format '{}' (available: {})",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// One line per listener, as the TUI shows them.
+struct Text;
+
+impl Formatter for Text {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn write(&self, out: &mut dyn Write, processes: &[PortProcess]) -> io::Result<()> {
+        for p in processes {
+            writeln!(
+                out,
+                ":{} {} {} (PID: {}) {}",
+                p.port,
+                p.protocol,
+                p.name,
+                p.pid_label(),
+                p.address
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A JSON array of objects.
+struct Json;
+
+impl Formatter for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn write(&self, out: &mut dyn Write, processes: &[PortProcess]) -> io::Result<()> {
+        let rows = processes.iter().map(Value::from).collect();
+        writeln!(out, "{}", Value::Array(rows))
+    }
+}
+
+/// A YAML sequence of mappings; strings are double-quoted the JSON way,
+/// which YAML reads the same.
+struct Yaml;
+
+impl Formatter for Yaml {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn write(&self, out: &mut dyn Write, processes: &[PortProcess]) -> io::Result<()> {
+        if processes.is_empty() {
+            return writeln!(out, "[]");
+        }
+        for fields in processes.iter().map(fields) {
+            for (i, (key, value)) in fields.iter().enumerate() {
+                let lead = if i == 0 { "- " } else { "  " };
+                writeln!(out, "{}{}: {}", lead, key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CSV with a header row, quoted where RFC 4180 needs it.
+struct Csv;
+
+impl Formatter for Csv {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn write(&self, out: &mut dyn Write, processes: &[PortProcess]) -> io::Result<()> {
+        writeln!(out, "{}", columns().join(","))?;
+        for fields in processes.iter().map(fields) {
+            let cells: Vec<String> = fields
+                .iter()
+                .map(|(_, value)| {
+                    let cell = plain(value);
+                    if cell.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", cell.replace('"', "\"\""))
+                    } else {
+                        cell
+                    }
+                })
+                .collect();
+            writeln!(out, "{}", cells.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// A Markdown (GitHub-flavoured) table.
+struct Markdown;
+
+impl Formatter for Markdown {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn write(&self, out: &mut dyn Write, processes: &[PortProcess]) -> io::Result<()> {
+        let columns = columns();
+        writeln!(out, "| {} |", columns.join(" | "))?;
+        writeln!(out, "|{}", "---|".repeat(columns.len()))?;
+        for fields in processes.iter().map(fields) {
+            let cells: Vec<String> = fields
+                .iter()
+                .map(|(_, value)| plain(value).replace('|', "\\|").replace('\n', " "))
+                .collect();
+            writeln!(out, "| {} |", cells.join(" | "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A listener's fields as `serve --stdio` returns them.
+fn fields(process: &PortProcess) -> Vec<(String, Value)> {
+    match Value::from(process) {
+        Value::Object(fields) => fields,
+        _ => unreachable!("a process is an object"),
+    }
+}
+
+/// The field names, for table headers.
+fn columns() -> Vec<String> {
+    fields(&PortProcess::default())
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// A value as a bare table cell: strings unquoted, null empty.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
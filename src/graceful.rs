@@ -0,0 +1,49 @@
+use crate::config::Config;
+use crate::enrich;
+
+/// Signals that ask well-known servers to finish in-flight work and exit,
+/// rather than dropping connections the way SIGKILL does.
+const GRACEFUL_SIGNALS: &[(&str, &str)] = &[
+    // nginx: QUIT is "graceful shutdown", TERM is "fast shutdown".
+    ("nginx", "QUIT"),
+    // gunicorn: TERM lets workers finish their current requests.
+    ("gunicorn", "TERM"),
+    // uvicorn treats INT and TERM alike: stop accepting, drain, exit.
+    ("uvicorn", "TERM"),
+    // puma: TERM stops the server after finishing outstanding requests.
+    ("puma", "TERM"),
+    // kubectl port-forward only traps INT (Ctrl-C), on which it closes its
+    // streams to the API server; TERM just ends it.
+    ("kubectl", "INT"),
+];
+
+/// The graceful stop signal for process `pid` called `name`: the config's
+/// `[graceful]` entry for it, else a built-in one. Entries match the name
+/// exactly, or the program argv[0] names, which lsof does not truncate
+/// and which a retitled process keeps first (`nginx: master process`).
+/// `nginx-exporter` is not nginx.
+pub fn signal_for<'a>(config: &'a Config, pid: u32, name: &str) -> Option<&'a str> {
+    entry_for(config, pid, name).map(|(_, signal)| signal)
+}
+
+/// The `[graceful]` or built-in entry `signal_for` goes by, as
+/// `(server, signal)`.
+pub fn entry_for<'a>(config: &'a Config, pid: u32, name: &str) -> Option<(&'a str, &'a str)> {
+    let mut names = vec![name.to_lowercase()];
+    names.extend(program(pid));
+    let known = |server: &str| names.iter().any(|name| name == server);
+    config
+        .graceful
+        .iter()
+        .map(|(server, signal)| (server.as_str(), signal.as_str()))
+        .chain(GRACEFUL_SIGNALS.iter().copied())
+        .find(|(server, _)| known(&server.to_lowercase()))
+}
+
+/// The file name argv[0] starts with, lower-cased.
+fn program(pid: u32) -> Option<String> {
+    let argv = enrich::command_line(pid)?;
+    let first = argv.first()?.split_whitespace().next()?;
+    let program = first.rsplit('/').next()?.trim_end_matches(':');
+    (!program.is_empty()).then(|| program.to_lowercase())
+}
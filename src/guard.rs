@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cli::GuardOptions;
+use crate::clock::utc_timestamp;
+use crate::config::Config;
+use crate::{
+    PortProcess, UNKNOWN_PID, audit, config, enrich, get_port_processes, graceful, process, risk,
+};
+
+/// A process by PID and start time, so a PID reused by some later process
+/// is not spared along with the one that had it.
+type Identity = (u32, Option<String>);
+
+enum Decision {
+    Kill,
+    Spare,
+}
+
+/// Watch a single port and evict whatever binds it, until interrupted.
+pub fn run(options: GuardOptions) -> io::Result<()> {
+    let mut assume_yes = options.assume_yes;
+    let mut spared: HashSet<Identity> = HashSet::new();
+    let config = config::load_or_exit();
+
+    log(&tr!(
+        "guarding :{} (checking every {}, Ctrl-C to stop)",
+        options.port,
+        format!("{:?}", options.interval)
+    ));
+
+    loop {
+        let mut occupants: Vec<(PortProcess, Identity)> = get_port_processes()
+            .into_iter()
+            // A socket whose owner is hidden from us has no PID to signal.
+            .filter(|p| p.port == options.port && p.pid != UNKNOWN_PID)
+            .map(|p| {
+                let started = enrich::process_status(p.pid).map(|(_, started)| started);
+                let identity = (p.pid, started);
+                (p, identity)
+            })
+            .collect();
+        // Once off the port, a spared process is asked about again if it
+        // comes back; its TCP and UDP (or IPv4 and IPv6) rows are one.
+        spared.retain(|identity| occupants.iter().any(|(_, id)| id == identity));
+        occupants.retain(|(_, identity)| !spared.contains(identity));
+        let mut seen = HashSet::new();
+        occupants.retain(|(p, _)| seen.insert(p.pid));
+
+        for (process, identity) in occupants {
+            if let Some(refusal) = risk::kill_refusal(&process, &config) {
+                spared.insert(identity);
+                log(&refusal);
+                continue;
+            }
+            let decision = if assume_yes {
+                Decision::Kill
+            } else {
+                match confirm(&process)? {
+                    Some(decision) => decision,
+                    None => {
+                        assume_yes = true;
+                        Decision::Kill
+                    }
+                }
+            };
+
+            match decision {
+                Decision::Kill if options.dry_run => {
+                    spared.insert(identity);
+                    log(&tr!(
+                        "would evict {} (PID: {}) from :{}/{} (safe mode)",
+                        process.name,
+                        process.pid,
+                        process.port,
+                        process.protocol
+                    ));
+                }
+                Decision::Kill => match evict(&config, &process, identity.1.as_deref()) {
+                    Ok(signal) => log(&tr!(
+                        "evicted {} (PID: {}) from :{}/{} with SIG{}",
+                        process.name,
+                        process.pid,
+                        process.port,
+                        process.protocol,
+                        signal
+                    )),
+                    Err(e) => log(&tr!("failed to kill PID {}: {}", process.pid, e)),
+                },
+                Decision::Spare => {
+                    spared.insert(identity);
+                    log(&tr!(
+                        "leaving {} (PID: {}) on :{}",
+                        process.name,
+                        process.pid,
+                        process.port
+                    ));
+                }
+            }
+        }
+
+        thread::sleep(options.interval);
+    }
+}
+
+/// Stop `process` as the TUI's kill would, with its graceful stop signal or
+/// else the configured one, then SIGKILL once the grace period is up, since
+/// the port has to come free. Returns the signal that ended it.
+fn evict(config: &Config, process: &PortProcess, started: Option<&str>) -> io::Result<String> {
+    let signal = graceful::signal_for(config, process.pid, &process.name)
+        .unwrap_or(&config.signal)
+        .to_string();
+    if signal == "KILL" {
+        audit::kill(&process::System, process, "guard")?;
+        return Ok(signal);
+    }
+    let result = process::signal(process.pid, &signal);
+    let entry = audit::Entry {
+        pid: Some(process.pid),
+        name: &process.name,
+        port: Some(process.port),
+        signal: &signal,
+        group: None,
+        via: "guard",
+    };
+    audit::record(&entry, &result);
+    result?;
+
+    let deadline = Instant::now() + Duration::from_secs(config.grace_secs);
+    while !process::has_exited(process.pid, started) {
+        if Instant::now() >= deadline {
+            audit::kill(&process::System, process, "guard")?;
+            return Ok("KILL".to_string());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(signal)
+}
+
+/// Ask before evicting. `None` means "yes, and stop asking".
+fn confirm(process: &PortProcess) -> io::Result<Option<Decision>> {
+    print!(
+        "{}",
+        tr!(
+            "{} bound :{} (PID: {}). Kill it? [y/N/a(lways)] ",
+            process.name,
+            process.port,
+            process.pid
+        )
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(Decision::Kill),
+        "a" | "always" => None,
+        _ => Some(Decision::Spare),
+    })
+}
+
+fn log(message: &str) {
+    println!("[{}] {}", utc_timestamp(), message);
+}
@@ -0,0 +1,41 @@
+use std::ops::RangeInclusive;
+
+use crate::PortProcess;
+use crate::portscan::{self, ephemeral_range};
+
+/// The strips shown by default: well-known ports, the usual dev-server
+/// ports and the ephemeral range.
+pub fn default_ranges() -> Vec<RangeInclusive<u16>> {
+    vec![1..=1024, 3000..=9000, ephemeral_range()]
+}
+
+/// Parse `1-1024,3000-9000,ephemeral`.
+pub fn parse_ranges(value: &str) -> Result<Vec<RangeInclusive<u16>>, String> {
+    value
+        .split(',')
+        .map(|part| match part.trim() {
+            "ephemeral" => Ok(ephemeral_range()),
+            range => portscan::parse_range(range),
+        })
+        .collect()
+}
+
+/// Split `range` into `cells` equal buckets and count the distinct
+/// occupied ports in each.
+pub fn buckets(processes: &[PortProcess], range: &RangeInclusive<u16>, cells: usize) -> Vec<usize> {
+    let cells = cells.max(1);
+    let mut counts = vec![0; cells];
+    let span = usize::from(range.end() - range.start()) + 1;
+    let mut ports: Vec<u16> = processes
+        .iter()
+        .map(|p| p.port)
+        .filter(|port| range.contains(port))
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    for port in ports {
+        let offset = usize::from(port - range.start());
+        counts[offset * cells / span] += 1;
+    }
+    counts
+}
@@ -0,0 +1,110 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::{clock, enrich};
+
+/// A process rip killed this session, with what it takes to start it
+/// again, captured before the signal went out.
+pub struct Victim {
+    pub pid: u32,
+    pub name: String,
+    /// The port it was killed for; none for the owner of a Unix socket.
+    pub port: Option<u16>,
+    /// Who ran it: a relaunch runs as rip's user, so only theirs are.
+    pub user: Option<String>,
+    /// When, as `HH:MM:SS`.
+    pub at: String,
+    /// Empty when the command line could not be read.
+    pub argv: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// Its environment, where the platform lets rip read it (`/proc`);
+    /// otherwise a relaunch inherits rip's own.
+    pub env: Option<Vec<(OsString, OsString)>>,
+    /// The PID it was started again as.
+    pub relaunched: Option<u32>,
+}
+
+impl Victim {
+    pub fn capture(pid: u32, name: &str, port: Option<u16>) -> Self {
+        Victim {
+            pid,
+            name: name.to_string(),
+            port,
+            user: enrich::process_user(pid),
+            at: clock::local_time_of_day(),
+            argv: enrich::command_line(pid).unwrap_or_default(),
+            cwd: enrich::working_directory(pid),
+            env: environment(pid),
+            relaunched: None,
+        }
+    }
+
+    /// Run the captured command again in its old directory and
+    /// environment, detached from rip (its own process group, output to a
+    /// log in the temp directory) so it outlives rip. Returns the new PID
+    /// and the log.
+    pub fn relaunch(&self) -> io::Result<(u32, PathBuf)> {
+        let Some((program, args)) = self.argv.split_first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("its command line could not be read when it was killed"),
+            ));
+        };
+        let log = std::env::temp_dir().join(format!("rip-relaunch-{}-{}.log", self.name, self.pid));
+        let output = File::create(&log)?;
+
+        // `./server` or `../bin/dev` meant the old directory, not rip's.
+        let program = match &self.cwd {
+            Some(cwd) if program.contains('/') && !program.starts_with('/') => cwd.join(program),
+            _ => PathBuf::from(program),
+        };
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(output.try_clone()?)
+            .stderr(output);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        if let Some(env) = &self.env {
+            command.env_clear().envs(env.iter().map(|(k, v)| (k, v)));
+        }
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let child = command.spawn()?;
+        Ok((child.id(), log))
+    }
+}
+
+/// Whether sending `signal` is meant to end the process, rather than to
+/// reload, pause or resume it.
+pub fn ends_process(signal: &str) -> bool {
+    !matches!(signal, "HUP" | "STOP" | "CONT" | "USR1" | "USR2" | "WINCH")
+}
+
+#[cfg(target_os = "linux")]
+fn environment(pid: u32) -> Option<Vec<(OsString, OsString)>> {
+    use std::os::unix::ffi::OsStringExt;
+
+    let raw = std::fs::read(format!("/proc/{}/environ", pid)).ok()?;
+    Some(
+        raw.split(|b| *b == 0)
+            .filter_map(|entry| {
+                let at = entry.iter().position(|b| *b == b'=')?;
+                Some((
+                    OsString::from_vec(entry[..at].to_vec()),
+                    OsString::from_vec(entry[at + 1..].to_vec()),
+                ))
+            })
+            .collect(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn environment(_pid: u32) -> Option<Vec<(OsString, OsString)>> {
+    None
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Translations shipped with rip as gettext `.po` catalogs. English is the
+/// source language and needs no catalog; missing entries fall back to it.
+const CATALOGS: &[(&str, &str)] = &[
+    ("de", include_str!("../locales/de.po")),
+    ("es", include_str!("../locales/es.po")),
+];
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Translate a message and fill its `{}` placeholders, like `format!`.
+///
+/// The English text is the message id. Translations may reorder arguments
+/// with `{0}`, `{1}`, ...
+#[macro_export]
+macro_rules! tr {
+    ($msgid:literal) => {
+        $crate::i18n::tr($msgid).to_string()
+    };
+    ($msgid:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill(
+            $crate::i18n::tr($msgid),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+
+/// Pick the language from `--lang`, or else `LC_ALL`, `LC_MESSAGES` and
+/// `LANG`. Only an explicit `--lang` that rip has no catalog for is an
+/// error; unknown locales from the environment fall back to English.
+pub fn init(lang: Option<&str>) -> Result<(), String> {
+    let (requested, explicit) = match lang {
+        Some(lang) => (lang.to_string(), true),
+        None => (
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|var| std::env::var(var).ok())
+                .find(|value| !value.is_empty())
+                .unwrap_or_default(),
+            false,
+        ),
+    };
+
+    // "de_DE.UTF-8" -> "de"
+    let language = requested
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if matches!(language.as_str(), "" | "en" | "c" | "posix") {
+        return Ok(());
+    }
+
+    match CATALOGS.iter().find(|(code, _)| *code == language) {
+        Some((_, source)) => {
+            let _ = CATALOG.set(parse_po(source));
+            Ok(())
+        }
+        None if explicit => Err(format!(
+            "unsupported language '{}' (available: en, {})",
+            requested,
+            CATALOGS
+                .iter()
+                .map(|(code, _)| *code)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The translation of `msgid` in the active language.
+pub fn tr(msgid: &'static str) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(msgid))
+        .map(String::as_str)
+        .unwrap_or(msgid)
+}
+
+/// Substitute `{}` (next argument) and `{N}` (argument N) in `template`.
+/// `{{` and `}}` are literal braces.
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut next = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => out.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => out.push('}'),
+            '{' => {
+                let mut index = String::new();
+                while let Some(c) = chars.next_if(|c| *c != '}') {
+                    index.push(c);
+                }
+                chars.next();
+                let index = index.parse().unwrap_or_else(|_| {
+                    next += 1;
+                    next - 1
+                });
+                if let Some(arg) = args.get(index) {
+                    out.push_str(&arg.to_string());
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Read the `msgid`/`msgstr` pairs of a `.po` file, including multi-line
+/// strings. Untranslated (empty) entries are skipped.
+fn parse_po(source: &str) -> HashMap<String, String> {
+    let mut catalog = HashMap::new();
+    let mut msgid = String::new();
+    let mut msgstr = String::new();
+    let mut in_msgstr = false;
+
+    for line in source.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("msgid ") {
+            if in_msgstr && !msgid.is_empty() && !msgstr.is_empty() {
+                catalog.insert(std::mem::take(&mut msgid), std::mem::take(&mut msgstr));
+            }
+            msgid = unquote(rest);
+            msgstr.clear();
+            in_msgstr = false;
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            msgstr = unquote(rest);
+            in_msgstr = true;
+        } else if line.starts_with('"') {
+            let target = if in_msgstr { &mut msgstr } else { &mut msgid };
+            target.push_str(&unquote(line));
+        }
+    }
+    if !msgid.is_empty() && !msgstr.is_empty() {
+        catalog.insert(msgid, msgstr);
+    }
+    catalog
+}
+
+fn unquote(quoted: &str) -> String {
+    let inner = quoted
+        .trim()
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or_default();
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::io;
+
+/// One listening TCP or bound UDP socket from the IP Helper tables.
+pub struct TableRow {
+    pub protocol: &'static str,
+    /// `*` for a wildcard bind, like lsof.
+    pub address: String,
+    pub port: u16,
+    pub pid: u32,
+}
+
+/// Listening TCP and all UDP sockets, IPv4 and IPv6, with their owning
+/// PIDs, from `GetExtendedTcpTable`/`GetExtendedUdpTable`: what `netstat
+/// -ano` shows, without parsing it.
+#[cfg(windows)]
+pub fn dump() -> io::Result<Vec<TableRow>> {
+    let mut rows = Vec::new();
+    for family in [sys::AF_INET, sys::AF_INET6] {
+        sys::tcp_listeners(family, &mut rows)?;
+        sys::udp_sockets(family, &mut rows)?;
+    }
+    Ok(rows)
+}
+
+#[cfg(not(windows))]
+pub fn dump() -> io::Result<Vec<TableRow>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        tr!("the IP Helper API is only available on Windows"),
+    ))
+}
+
+/// PID -> executable name (`nginx.exe`) for every running process, from a
+/// Toolhelp snapshot. Unlike opening each process, this also names system
+/// services that an unelevated rip may not query.
+#[cfg(windows)]
+pub fn process_names() -> HashMap<u32, String> {
+    sys::process_names()
+}
+
+#[cfg(not(windows))]
+pub fn process_names() -> HashMap<u32, String> {
+    HashMap::new()
+}
+
+/// `iphlpapi.h`, `tlhelp32.h` and the few kernel32 calls they need.
+#[cfg(windows)]
+mod sys {
+    use std::collections::HashMap;
+    use std::ffi::c_void;
+    use std::io;
+    use std::mem::size_of;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::TableRow;
+
+    pub const AF_INET: u32 = 2;
+    pub const AF_INET6: u32 = 23;
+    const TCP_TABLE_OWNER_PID_LISTENER: i32 = 3;
+    const UDP_TABLE_OWNER_PID: i32 = 1;
+    const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+    const TH32CS_SNAPPROCESS: u32 = 2;
+    const INVALID_HANDLE_VALUE: isize = -1;
+    const MAX_PATH: usize = 260;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct TcpRow {
+        state: u32,
+        local_addr: [u8; 4],
+        local_port: [u8; 4],
+        remote_addr: [u8; 4],
+        remote_port: [u8; 4],
+        pid: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Tcp6Row {
+        local_addr: [u8; 16],
+        local_scope: u32,
+        local_port: [u8; 4],
+        remote_addr: [u8; 16],
+        remote_scope: u32,
+        remote_port: [u8; 4],
+        state: u32,
+        pid: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct UdpRow {
+        local_addr: [u8; 4],
+        local_port: [u8; 4],
+        pid: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Udp6Row {
+        local_addr: [u8; 16],
+        local_scope: u32,
+        local_port: [u8; 4],
+        pid: u32,
+    }
+
+    #[repr(C)]
+    struct ProcessEntry {
+        size: u32,
+        usage: u32,
+        pid: u32,
+        heap: usize,
+        module: u32,
+        threads: u32,
+        parent: u32,
+        priority: i32,
+        flags: u32,
+        exe: [u16; MAX_PATH],
+    }
+
+    #[link(name = "iphlpapi")]
+    unsafe extern "system" {
+        fn GetExtendedTcpTable(
+            table: *mut c_void,
+            size: *mut u32,
+            order: i32,
+            family: u32,
+            class: i32,
+            reserved: u32,
+        ) -> u32;
+        fn GetExtendedUdpTable(
+            table: *mut c_void,
+            size: *mut u32,
+            order: i32,
+            family: u32,
+            class: i32,
+            reserved: u32,
+        ) -> u32;
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn CreateToolhelp32Snapshot(flags: u32, pid: u32) -> isize;
+        fn Process32FirstW(snapshot: isize, entry: *mut ProcessEntry) -> i32;
+        fn Process32NextW(snapshot: isize, entry: *mut ProcessEntry) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+    }
+
+    type GetTable = unsafe extern "system" fn(*mut c_void, *mut u32, i32, u32, i32, u32) -> u32;
+
+    pub fn tcp_listeners(family: u32, out: &mut Vec<TableRow>) -> io::Result<()> {
+        let table = table(GetExtendedTcpTable, family, TCP_TABLE_OWNER_PID_LISTENER)?;
+        if family == AF_INET {
+            out.extend(rows::<TcpRow>(&table).map(|row| TableRow {
+                protocol: "TCP",
+                address: ipv4(row.local_addr),
+                port: port(row.local_port),
+                pid: row.pid,
+            }));
+        } else {
+            out.extend(rows::<Tcp6Row>(&table).map(|row| TableRow {
+                protocol: "TCP",
+                address: ipv6(row.local_addr),
+                port: port(row.local_port),
+                pid: row.pid,
+            }));
+        }
+        Ok(())
+    }
+
+    pub fn udp_sockets(family: u32, out: &mut Vec<TableRow>) -> io::Result<()> {
+        let table = table(GetExtendedUdpTable, family, UDP_TABLE_OWNER_PID)?;
+        if family == AF_INET {
+            out.extend(rows::<UdpRow>(&table).map(|row| TableRow {
+                protocol: "UDP",
+                address: ipv4(row.local_addr),
+                port: port(row.local_port),
+                pid: row.pid,
+            }));
+        } else {
+            out.extend(rows::<Udp6Row>(&table).map(|row| TableRow {
+                protocol: "UDP",
+                address: ipv6(row.local_addr),
+                port: port(row.local_port),
+                pid: row.pid,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Call `get` until the buffer is big enough; sockets opened between
+    /// the size query and the copy can make the first attempt too small.
+    fn table(get: GetTable, family: u32, class: i32) -> io::Result<Vec<u8>> {
+        let mut size = 0u32;
+        let mut buffer: Vec<u8> = Vec::new();
+        loop {
+            let result = unsafe { get(buffer.as_mut_ptr().cast(), &mut size, 0, family, class, 0) };
+            match result {
+                0 => return Ok(buffer),
+                ERROR_INSUFFICIENT_BUFFER => buffer.resize(size as usize, 0),
+                error => return Err(io::Error::from_raw_os_error(error as i32)),
+            }
+        }
+    }
+
+    /// The rows after the table's leading `dwNumEntries`.
+    fn rows<T: Copy>(table: &[u8]) -> impl Iterator<Item = T> + '_ {
+        let count = if table.len() >= size_of::<u32>() {
+            u32::from_ne_bytes([table[0], table[1], table[2], table[3]]) as usize
+        } else {
+            0
+        };
+        table[size_of::<u32>().min(table.len())..]
+            .chunks_exact(size_of::<T>())
+            .take(count)
+            .map(|row| unsafe { std::ptr::read_unaligned(row.as_ptr().cast()) })
+    }
+
+    /// Ports are stored in network byte order in the low 16 bits.
+    fn port(raw: [u8; 4]) -> u16 {
+        u16::from_be_bytes([raw[0], raw[1]])
+    }
+
+    fn ipv4(raw: [u8; 4]) -> String {
+        let ip = Ipv4Addr::from(raw);
+        if ip.is_unspecified() {
+            "*".to_string()
+        } else {
+            ip.to_string()
+        }
+    }
+
+    fn ipv6(raw: [u8; 16]) -> String {
+        let ip = Ipv6Addr::from(raw);
+        if ip.is_unspecified() {
+            "*".to_string()
+        } else {
+            format!("[{}]", ip)
+        }
+    }
+
+    pub fn process_names() -> HashMap<u32, String> {
+        let mut names = HashMap::new();
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+        if snapshot == INVALID_HANDLE_VALUE {
+            return names;
+        }
+        let mut entry = ProcessEntry {
+            size: size_of::<ProcessEntry>() as u32,
+            usage: 0,
+            pid: 0,
+            heap: 0,
+            module: 0,
+            threads: 0,
+            parent: 0,
+            priority: 0,
+            flags: 0,
+            exe: [0; MAX_PATH],
+        };
+        let mut more = unsafe { Process32FirstW(snapshot, &mut entry) } != 0;
+        while more {
+            let len = entry.exe.iter().position(|c| *c == 0).unwrap_or(MAX_PATH);
+            names.insert(entry.pid, String::from_utf16_lossy(&entry.exe[..len]));
+            more = unsafe { Process32NextW(snapshot, &mut entry) } != 0;
+        }
+        unsafe { CloseHandle(snapshot) };
+        names
+    }
+}
@@ -0,0 +1,170 @@
+use std::io;
+use std::process::{Child, Command};
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::backend::Backend;
+use crate::cli::KeepaliveOptions;
+use crate::clock::utc_timestamp;
+use crate::scanner::{PortScanner, SystemScanner};
+
+/// Restarts that never got the port listening wait `RESTART_BASE`, doubling
+/// each time up to `RESTART_MAX`, so a command that fails at once is not
+/// respawned every interval.
+const RESTART_BASE: Duration = Duration::from_secs(1);
+const RESTART_MAX: Duration = Duration::from_secs(60);
+
+/// The process group the command runs in, for `on_signal`.
+#[cfg(unix)]
+static GROUP: AtomicI32 = AtomicI32::new(0);
+
+/// Run a command and restart it whenever nothing is listening on the port.
+pub fn run(options: KeepaliveOptions) -> io::Result<()> {
+    log(&tr!(
+        "keeping :{} alive with `{}` (Ctrl-C to stop)",
+        options.port,
+        options.command.join(" ")
+    ));
+
+    #[cfg(unix)]
+    forward_signals();
+    let mut scanner = SystemScanner::default();
+    scanner.fallback_range = options.port..=options.port;
+    let mut child = spawn(&options)?;
+    let mut started = Instant::now();
+    // Restarts since the port was last seen listening.
+    let mut failures = 0;
+    let mut scan_failed = false;
+
+    loop {
+        thread::sleep(options.interval);
+
+        // A scan that failed says nothing about the port; restarting on it
+        // would kill a healthy server.
+        match listening(&scanner, options.port) {
+            Ok(true) => {
+                failures = 0;
+                scan_failed = false;
+                continue;
+            }
+            Ok(false) => scan_failed = false,
+            Err(e) => {
+                if !scan_failed {
+                    log(&tr!("cannot scan ports ({}), checking again", e));
+                }
+                scan_failed = true;
+                continue;
+            }
+        }
+
+        let reason = match child.try_wait()? {
+            Some(status) => {
+                // Whatever it left running in its group goes too.
+                stop(&mut child);
+                tr!("command exited ({})", status)
+            }
+            None if started.elapsed() >= options.grace => {
+                stop(&mut child);
+                tr!(
+                    "nothing listening on :{} after {}",
+                    options.port,
+                    format!("{:?}", options.grace)
+                )
+            }
+            None => continue,
+        };
+
+        let backoff = RESTART_BASE
+            .saturating_mul(1 << failures.min(8))
+            .min(RESTART_MAX);
+        failures += 1;
+        if failures > 1 {
+            log(&tr!(
+                "{}, restarting in {}",
+                reason,
+                format!("{:?}", backoff)
+            ));
+            thread::sleep(backoff);
+        } else {
+            log(&tr!("{}, restarting", reason));
+        }
+        child = spawn(&options)?;
+        started = Instant::now();
+    }
+}
+
+/// Whether anything listens on `port`. `Err` when the scan could not tell,
+/// unless the bind probe checked the port itself.
+fn listening(scanner: &SystemScanner, port: u16) -> Result<bool, String> {
+    let (processes, stats) = scanner.scan().map_err(|e| e.to_string())?;
+    if processes.is_empty()
+        && stats.backend != Backend::BindProbe.name()
+        && let Some(failure) = stats.failure
+    {
+        return Err(failure);
+    }
+    Ok(processes.iter().any(|p| p.port == port))
+}
+
+/// Start the command as the leader of its own process group, so `stop`
+/// reaches whatever it forks (`npm run dev`, a shell script) too.
+fn spawn(options: &KeepaliveOptions) -> io::Result<Child> {
+    let mut command = Command::new(&options.command[0]);
+    command.args(&options.command[1..]);
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let child = command.spawn()?;
+    #[cfg(unix)]
+    GROUP.store(child.id() as i32, Ordering::SeqCst);
+    log(&tr!("started PID {}", child.id()));
+    Ok(child)
+}
+
+/// Kill the command's process group, or just the command where there are
+/// no groups, and reap it.
+fn stop(child: &mut Child) {
+    // SAFETY: kill has no memory effects; a group already gone is ESRCH.
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// In its own group the command no longer gets the terminal's Ctrl-C, so
+/// pass SIGINT, SIGTERM and SIGHUP on to it before keepalive goes.
+#[cfg(unix)]
+fn forward_signals() {
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        // SAFETY: the handler only reads an atomic and calls
+        // async-signal-safe functions.
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+/// Signal the command's group, then take the signal's default action,
+/// which `SA_RESETHAND` restored.
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    let group = GROUP.load(Ordering::SeqCst);
+    // SAFETY: kill and raise are async-signal-safe.
+    unsafe {
+        if group > 0 {
+            libc::kill(-group, signal);
+        }
+        libc::raise(signal);
+    }
+}
+
+fn log(message: &str) {
+    println!("[{}] {}", utc_timestamp(), message);
+}
@@ -0,0 +1,277 @@
+use crossterm::event::KeyCode;
+
+/// Something a key does in the process list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Kill,
+    Menu,
+    Mark,
+    Filter,
+    Protocol,
+    Mine,
+    Workspace,
+    Sort,
+    Graceful,
+    Signal,
+    CoreKill,
+    StacksKill,
+    Open,
+    Window,
+    Users,
+    Apps,
+    Heatmap,
+    Sockets,
+    Details,
+    Tree,
+    KillTree,
+    KillGroup,
+    History,
+    NiceUp,
+    NiceDown,
+    Hide,
+    HideForever,
+    Settings,
+    Health,
+    Firewall,
+    Groups,
+    Upnp,
+    Mdns,
+    Ide,
+    Os,
+    Ephemeral,
+    LowPower,
+    Sudo,
+    Diagnostics,
+    Debug,
+    Refresh,
+    Quit,
+}
+
+/// Every action with its name in the config's `[keys]` table and its
+/// default keys.
+const ACTIONS: &[(Action, &str, &[KeyCode])] = &[
+    (Action::Up, "up", &[KeyCode::Up, KeyCode::Char('k')]),
+    (Action::Down, "down", &[KeyCode::Down, KeyCode::Char('j')]),
+    (Action::Kill, "kill", &[KeyCode::Enter, KeyCode::Char('d')]),
+    (Action::Menu, "menu", &[KeyCode::Char('m')]),
+    (Action::Mark, "mark", &[KeyCode::Char(' ')]),
+    (Action::Filter, "filter", &[KeyCode::Char('/')]),
+    (Action::Protocol, "protocol", &[KeyCode::Char('t')]),
+    (Action::Mine, "mine", &[KeyCode::Char('M')]),
+    (Action::Workspace, "workspace", &[KeyCode::Char('W')]),
+    (Action::Sort, "sort", &[KeyCode::Char('O')]),
+    (Action::Graceful, "graceful", &[KeyCode::Char('g')]),
+    (Action::Signal, "signal", &[KeyCode::Char('s')]),
+    (Action::CoreKill, "core_kill", &[KeyCode::Char('C')]),
+    (Action::StacksKill, "stacks_kill", &[KeyCode::Char('T')]),
+    (Action::Open, "open", &[KeyCode::Char('o')]),
+    (Action::Window, "window", &[KeyCode::Char('w')]),
+    (Action::Users, "users", &[KeyCode::Char('u')]),
+    (Action::Apps, "apps", &[KeyCode::Char('a')]),
+    (Action::Heatmap, "heatmap", &[KeyCode::Char('p')]),
+    (Action::Sockets, "sockets", &[KeyCode::Char('U')]),
+    (Action::Details, "details", &[KeyCode::Tab]),
+    (Action::Tree, "tree", &[KeyCode::Char('P')]),
+    (Action::KillTree, "kill_tree", &[KeyCode::Char('K')]),
+    (Action::KillGroup, "kill_group", &[KeyCode::Char('D')]),
+    (Action::History, "history", &[KeyCode::Char('H')]),
+    (Action::NiceUp, "nice_up", &[KeyCode::Char('+')]),
+    (Action::NiceDown, "nice_down", &[KeyCode::Char('-')]),
+    (Action::Hide, "hide", &[KeyCode::Char('x')]),
+    (Action::HideForever, "hide_forever", &[KeyCode::Char('X')]),
+    (Action::Settings, "settings", &[KeyCode::Char('S')]),
+    (Action::Health, "health", &[KeyCode::Char('h')]),
+    (Action::Firewall, "firewall", &[KeyCode::Char('f')]),
+    (Action::Groups, "groups", &[KeyCode::Char('G')]),
+    (Action::Upnp, "upnp", &[KeyCode::Char('n')]),
+    (Action::Mdns, "mdns", &[KeyCode::Char('b')]),
+    (Action::Ide, "ide", &[KeyCode::Char('i')]),
+    (Action::Os, "os", &[KeyCode::Char('v')]),
+    (Action::Ephemeral, "ephemeral", &[KeyCode::Char('e')]),
+    (Action::LowPower, "low_power", &[KeyCode::Char('L')]),
+    (Action::Sudo, "sudo", &[KeyCode::Char('E')]),
+    (Action::Diagnostics, "diagnostics", &[KeyCode::Char('B')]),
+    (Action::Debug, "debug", &[KeyCode::F(12)]),
+    (Action::Refresh, "refresh", &[KeyCode::Char('r')]),
+    (Action::Quit, "quit", &[KeyCode::Char('q'), KeyCode::Esc]),
+];
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, _, _)| *action == self)
+            .map_or("", |(_, name, _)| name)
+    }
+
+    pub fn by_name(name: &str) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(action, _, _)| *action)
+    }
+}
+
+/// The help bar: each label with the actions it stands for. An entry with
+/// one action shows all its keys, one with several the first key of each
+/// (`↑/↓:Navigate`). Actions left out are documented in the README only.
+fn help_entries() -> Vec<(String, &'static [Action])> {
+    vec![
+        (tr!("Navigate"), &[Action::Up, Action::Down]),
+        (tr!("Kill"), &[Action::Kill]),
+        (tr!("Menu"), &[Action::Menu]),
+        (tr!("Mark"), &[Action::Mark]),
+        (tr!("Filter"), &[Action::Filter]),
+        (tr!("TCP/UDP"), &[Action::Protocol]),
+        (tr!("Mine"), &[Action::Mine]),
+        (tr!("Workspace"), &[Action::Workspace]),
+        (tr!("Sort"), &[Action::Sort]),
+        (tr!("Graceful"), &[Action::Graceful]),
+        (tr!("Signal"), &[Action::Signal]),
+        (tr!("Core+Kill"), &[Action::CoreKill]),
+        (tr!("Stacks+Kill"), &[Action::StacksKill]),
+        (tr!("Open"), &[Action::Open]),
+        (tr!("Window"), &[Action::Window]),
+        (tr!("Users"), &[Action::Users]),
+        (tr!("Apps"), &[Action::Apps]),
+        (tr!("Heatmap"), &[Action::Heatmap]),
+        (tr!("Sockets"), &[Action::Sockets]),
+        (tr!("Details"), &[Action::Details]),
+        (tr!("Tree"), &[Action::Tree]),
+        (tr!("History"), &[Action::History]),
+        (tr!("Nice"), &[Action::NiceUp, Action::NiceDown]),
+        (tr!("Hide"), &[Action::Hide]),
+        (tr!("Settings"), &[Action::Settings]),
+        (tr!("Health"), &[Action::Health]),
+        (tr!("Firewall"), &[Action::Firewall]),
+        (tr!("Groups"), &[Action::Groups]),
+        (tr!("UPnP"), &[Action::Upnp]),
+        (tr!("mDNS"), &[Action::Mdns]),
+        (tr!("IDE"), &[Action::Ide]),
+        (tr!("OS"), &[Action::Os]),
+        (tr!("Refresh"), &[Action::Refresh]),
+        (tr!("Quit"), &[Action::Quit]),
+    ]
+}
+
+/// Which keys trigger which action: the defaults, with the config's
+/// `[keys]` table laid over them.
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Keymap {
+    /// The defaults with `overrides` applied. A rebound action loses its
+    /// default keys, and a key given to it is taken from whatever action
+    /// had it before.
+    pub fn new(overrides: &[(Action, Vec<KeyCode>)]) -> Self {
+        let mut bindings: Vec<(Action, Vec<KeyCode>)> = ACTIONS
+            .iter()
+            .map(|(action, _, keys)| (*action, keys.to_vec()))
+            .collect();
+        for (action, keys) in overrides {
+            for (other, bound) in &mut bindings {
+                if other == action {
+                    bound.clone_from(keys);
+                } else {
+                    bound.retain(|key| !keys.contains(key));
+                }
+            }
+        }
+        Keymap { bindings }
+    }
+
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&code))
+            .map(|(action, _)| *action)
+    }
+
+    fn keys_of(&self, action: Action) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[][..], |(_, keys)| keys.as_slice())
+    }
+
+    /// The first key bound to `action`, the way the help bar shows it.
+    pub fn key_for(&self, action: Action) -> Option<String> {
+        self.keys_of(action).first().map(|key| key_label(*key))
+    }
+
+    /// The help bar for the active bindings.
+    pub fn help_line(&self) -> String {
+        let mut parts = Vec::new();
+        for (label, actions) in help_entries() {
+            let keys: Vec<String> = match actions {
+                [only] => self
+                    .keys_of(*only)
+                    .iter()
+                    .map(|key| key_label(*key))
+                    .collect(),
+                several => several
+                    .iter()
+                    .filter_map(|action| self.keys_of(*action).first())
+                    .map(|key| key_label(*key))
+                    .collect(),
+            };
+            if !keys.is_empty() {
+                parts.push(format!("{}:{}", keys.join("/"), label));
+            }
+        }
+        parts.join("  ")
+    }
+}
+
+/// A key as written in the config: one character, or a name such as
+/// `Enter`, `Space`, `Tab`, `Up` or `F5`.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match name.to_lowercase().as_str() {
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        other => KeyCode::F(other.strip_prefix('f')?.parse().ok()?),
+    };
+    Some(code)
+}
+
+/// A key the way the config names it, for writing the config back.
+pub fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+/// A key the way the help bar shows it.
+fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Char(' ') => tr!("Space"),
+        other => key_name(other),
+    }
+}
@@ -0,0 +1,68 @@
+//! Finding which process holds which port, and killing it: the part of rip
+//! that other tools can embed. The `rip` binary (TUI, `rip <PORT>`, `pick`,
+//! `guard`, `serve`, ...) is one consumer of it.
+//!
+//! ```no_run
+//! use rip::scanner::{PortScanner, SystemScanner};
+//!
+//! let (listeners, _) = SystemScanner::default().scan()?;
+//! for p in listeners.iter().filter(|p| p.port == 3000) {
+//!     rip::process::kill(p.pid)?;
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+#[macro_use]
+pub mod i18n;
+mod iphlpapi;
+mod libproc;
+mod netlink;
+
+pub mod backend;
+pub mod clock;
+pub mod conflict;
+pub mod container;
+pub mod enrich;
+pub mod json;
+pub mod lsof;
+pub mod portscan;
+pub mod process;
+pub mod procfs;
+pub mod scanner;
+pub mod session;
+pub mod state;
+
+/// Placeholder PID for ports whose owning process could not be determined.
+/// Never signalled: `process::signal` refuses it.
+pub const UNKNOWN_PID: u32 = 0;
+
+/// One socket and the process holding it.
+#[derive(Clone, Debug, Default)]
+pub struct PortProcess {
+    pub pid: u32,
+    pub port: u16,
+    pub protocol: String,
+    pub name: String,
+    pub address: String,
+    /// False for sockets that only talk to one peer (connected UDP), which
+    /// are usually outgoing traffic rather than servers.
+    pub listening: bool,
+    pub user: Option<String>,
+    /// Scheduling priority (`nice`), where the platform reports one.
+    pub nice: Option<i32>,
+    /// Established TCP connections on its port, the live traffic a kill
+    /// would drop; `None` for UDP, or where they could not be counted.
+    pub connections: Option<usize>,
+    pub context: Option<String>,
+    pub tag: Option<&'static str>,
+}
+
+impl PortProcess {
+    pub fn pid_label(&self) -> String {
+        if self.pid == UNKNOWN_PID {
+            "?".to_string()
+        } else {
+            self.pid.to_string()
+        }
+    }
+}
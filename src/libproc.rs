@@ -0,0 +1,327 @@
+use std::io;
+
+/// One listening TCP or bound UDP socket found in a process's file table.
+pub struct ProcSocket {
+    pub pid: u32,
+    pub name: String,
+    pub protocol: &'static str,
+    /// `*` for a wildcard bind, like lsof.
+    pub address: String,
+    pub port: u16,
+    /// False for connected UDP sockets.
+    pub listening: bool,
+    pub user: Option<String>,
+}
+
+/// Listening TCP and bound UDP sockets of every process rip may inspect,
+/// read with libproc (`proc_listpids`, `proc_pidinfo`, `proc_pidfdinfo`):
+/// the calls lsof itself makes on macOS, without starting a process or
+/// formatting and re-parsing its output.
+#[cfg(target_os = "macos")]
+pub fn sockets() -> io::Result<Vec<ProcSocket>> {
+    sys::sockets()
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn sockets() -> io::Result<Vec<ProcSocket>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        tr!("libproc is only available on macOS"),
+    ))
+}
+
+/// `sys/proc_info.h` and `libproc.h`. Only the leading part of
+/// `socket_fdinfo` that rip reads is declared; the kernel is handed a
+/// buffer large enough for the whole struct.
+#[cfg(target_os = "macos")]
+mod sys {
+    use std::collections::HashMap;
+    use std::ffi::{CStr, c_char, c_int, c_void};
+    use std::io;
+    use std::mem::size_of;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::ProcSocket;
+
+    const PROC_ALL_PIDS: u32 = 1;
+    const PROC_PIDLISTFDS: c_int = 1;
+    const PROC_PIDFDSOCKETINFO: c_int = 3;
+    const PROX_FDTYPE_SOCKET: u32 = 2;
+    const SOCKINFO_IN: i32 = 1;
+    const SOCKINFO_TCP: i32 = 2;
+    const TSI_S_LISTEN: i32 = 1;
+    const INI_IPV4: u8 = 0x1;
+    const IPPROTO_TCP: i32 = 6;
+    /// `sizeof(struct socket_fdinfo)` is 792; leave room for growth.
+    const SOCKET_FDINFO_SIZE: usize = 1024;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct FdInfo {
+        fd: i32,
+        kind: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct FileInfo {
+        open_flags: u32,
+        status: u32,
+        offset: i64,
+        kind: i32,
+        guard_flags: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct VinfoStat {
+        dev: u32,
+        mode: u16,
+        nlink: u16,
+        ino: u64,
+        uid: u32,
+        gid: u32,
+        times: [i64; 8],
+        size: i64,
+        blocks: i64,
+        blksize: i32,
+        flags: u32,
+        generation: u32,
+        rdev: u32,
+        spare: [i64; 2],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct SockbufInfo {
+        cc: u32,
+        hiwat: u32,
+        mbcnt: u32,
+        mbmax: u32,
+        lowat: u32,
+        flags: i16,
+        timeo: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct InSockinfo {
+        fport: i32,
+        lport: i32,
+        gencnt: u64,
+        flags: u32,
+        flow: u32,
+        vflag: u8,
+        ip_ttl: u8,
+        rfu: u32,
+        /// `in4in6_addr` (IPv4 in the last 4 bytes) or `in6_addr`.
+        faddr: [u8; 16],
+        laddr: [u8; 16],
+        v4_tos: u8,
+        v6_hlim: u8,
+        v6_cksum: i32,
+        v6_ifindex: u16,
+        v6_hops: i16,
+    }
+
+    /// `tcp_sockinfo` starts with the same `in_sockinfo`.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct TcpSockinfo {
+        ini: InSockinfo,
+        state: i32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct SocketFdInfo {
+        file: FileInfo,
+        stat: VinfoStat,
+        so: u64,
+        pcb: u64,
+        kind: i32,
+        protocol: i32,
+        family: i32,
+        options: i16,
+        linger: i16,
+        state: i16,
+        qlen: i16,
+        incqlen: i16,
+        qlimit: i16,
+        timeo: i16,
+        error: u16,
+        oobmark: u32,
+        rcv: SockbufInfo,
+        snd: SockbufInfo,
+        soi_kind: i32,
+        rfu: u32,
+        proto: TcpSockinfo,
+    }
+
+    unsafe extern "C" {
+        fn proc_listpids(kind: u32, info: u32, buffer: *mut c_void, size: c_int) -> c_int;
+        fn proc_pidinfo(
+            pid: c_int,
+            flavor: c_int,
+            arg: u64,
+            buffer: *mut c_void,
+            size: c_int,
+        ) -> c_int;
+        fn proc_pidfdinfo(
+            pid: c_int,
+            fd: c_int,
+            flavor: c_int,
+            buffer: *mut c_void,
+            size: c_int,
+        ) -> c_int;
+        fn proc_name(pid: c_int, buffer: *mut c_void, size: u32) -> c_int;
+    }
+
+    pub fn sockets() -> io::Result<Vec<ProcSocket>> {
+        let mut users: HashMap<u32, String> = HashMap::new();
+        let mut sockets = Vec::new();
+        for pid in pids()? {
+            // Processes rip may not inspect, and ones that exited meanwhile,
+            // are skipped, as lsof does.
+            let Some(fds) = fds(pid) else {
+                continue;
+            };
+            let mut name = None;
+            for fd in fds.iter().filter(|fd| fd.kind == PROX_FDTYPE_SOCKET) {
+                let Some(info) = socket_info(pid, fd.fd) else {
+                    continue;
+                };
+                let ini = info.proto.ini;
+                let (protocol, listening) = match info.soi_kind {
+                    SOCKINFO_TCP if info.proto.state == TSI_S_LISTEN => ("TCP", true),
+                    SOCKINFO_IN if info.protocol != IPPROTO_TCP => ("UDP", ini.fport == 0),
+                    _ => continue,
+                };
+                let port = u16::from_be(ini.lport as u16);
+                if port == 0 {
+                    continue;
+                }
+                let uid = info.stat.uid;
+                sockets.push(ProcSocket {
+                    pid: pid as u32,
+                    name: name.get_or_insert_with(|| process_name(pid)).clone(),
+                    protocol,
+                    address: address(&ini),
+                    port,
+                    listening,
+                    user: Some(users.entry(uid).or_insert_with(|| user_name(uid)).clone()),
+                });
+            }
+        }
+        Ok(sockets)
+    }
+
+    fn pids() -> io::Result<Vec<c_int>> {
+        let count = unsafe { proc_listpids(PROC_ALL_PIDS, 0, std::ptr::null_mut(), 0) };
+        if count <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Room for processes started between the two calls.
+        let mut pids = vec![0 as c_int; count as usize / size_of::<c_int>() + 64];
+        let bytes = unsafe {
+            proc_listpids(
+                PROC_ALL_PIDS,
+                0,
+                pids.as_mut_ptr().cast(),
+                (pids.len() * size_of::<c_int>()) as c_int,
+            )
+        };
+        if bytes <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+        pids.truncate(bytes as usize / size_of::<c_int>());
+        pids.retain(|pid| *pid > 0);
+        Ok(pids)
+    }
+
+    fn fds(pid: c_int) -> Option<Vec<FdInfo>> {
+        let bytes = unsafe { proc_pidinfo(pid, PROC_PIDLISTFDS, 0, std::ptr::null_mut(), 0) };
+        if bytes <= 0 {
+            return None;
+        }
+        let mut fds = vec![FdInfo { fd: 0, kind: 0 }; bytes as usize / size_of::<FdInfo>() + 16];
+        let bytes = unsafe {
+            proc_pidinfo(
+                pid,
+                PROC_PIDLISTFDS,
+                0,
+                fds.as_mut_ptr().cast(),
+                (fds.len() * size_of::<FdInfo>()) as c_int,
+            )
+        };
+        if bytes <= 0 {
+            return None;
+        }
+        fds.truncate(bytes as usize / size_of::<FdInfo>());
+        Some(fds)
+    }
+
+    fn socket_info(pid: c_int, fd: c_int) -> Option<SocketFdInfo> {
+        let mut buffer = [0u8; SOCKET_FDINFO_SIZE];
+        let bytes = unsafe {
+            proc_pidfdinfo(
+                pid,
+                fd,
+                PROC_PIDFDSOCKETINFO,
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as c_int,
+            )
+        };
+        if (bytes as usize) < size_of::<SocketFdInfo>() {
+            return None;
+        }
+        Some(unsafe { std::ptr::read_unaligned(buffer.as_ptr().cast()) })
+    }
+
+    fn address(ini: &InSockinfo) -> String {
+        if ini.vflag & INI_IPV4 != 0 {
+            let [.., a, b, c, d] = ini.laddr;
+            let ip = Ipv4Addr::new(a, b, c, d);
+            if ip.is_unspecified() {
+                "*".to_string()
+            } else {
+                ip.to_string()
+            }
+        } else {
+            let ip = Ipv6Addr::from(ini.laddr);
+            if ip.is_unspecified() {
+                "*".to_string()
+            } else {
+                format!("[{}]", ip)
+            }
+        }
+    }
+
+    fn process_name(pid: c_int) -> String {
+        let mut buffer = [0u8; 256];
+        let len = unsafe { proc_name(pid, buffer.as_mut_ptr().cast(), buffer.len() as u32) };
+        String::from_utf8_lossy(&buffer[..len.max(0) as usize]).into_owned()
+    }
+
+    /// Login name for a uid from the directory service (macOS keeps most
+    /// users out of `/etc/passwd`).
+    fn user_name(uid: u32) -> String {
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut buffer = vec![0 as c_char; 1024];
+        let mut result = std::ptr::null_mut();
+        let status = unsafe {
+            libc::getpwuid_r(
+                uid,
+                &mut passwd,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+        if status != 0 || result.is_null() {
+            return uid.to_string();
+        }
+        let name = unsafe { CStr::from_ptr(passwd.pw_name) };
+        name.to_string_lossy().into_owned()
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::OnceLock;
+
+use crate::state;
+
+/// One way of calling lsof: each pass is an invocation, and their rows are
+/// merged. All of them pass `-n` and `-P`, so no address or port is looked
+/// up in DNS or `/etc/services`, which can stall a scan for seconds.
+pub struct Variant {
+    pub name: &'static str,
+    pub passes: &'static [&'static [&'static str]],
+}
+
+pub const VARIANTS: &[Variant] = &[
+    // TCP listeners and every UDP socket in one invocation.
+    Variant {
+        name: "combined",
+        passes: &[&["-iTCP", "-iUDP", "-sTCP:LISTEN", "-P", "-n"]],
+    },
+    // The same as two narrower invocations, which macOS's lsof answers
+    // faster than one that has to match either.
+    Variant {
+        name: "split",
+        passes: &[&["-nP", "-iTCP", "-sTCP:LISTEN"], &["-nP", "-iUDP"]],
+    },
+    // Split, and never calling stat() or readlink() on files, which can
+    // hang on an unresponsive network mount; -w drops the warnings that
+    // skipping them prints.
+    Variant {
+        name: "nonblocking",
+        passes: &[
+            &["-nP", "-b", "-w", "-iTCP", "-sTCP:LISTEN"],
+            &["-nP", "-b", "-w", "-iUDP"],
+        ],
+    },
+];
+
+/// The invocation `rip bench` found fastest here, or this platform's
+/// default.
+pub fn selected() -> &'static Variant {
+    static SELECTED: OnceLock<&'static Variant> = OnceLock::new();
+    SELECTED.get_or_init(|| {
+        state::get("lsof_variant")
+            .and_then(|name| VARIANTS.iter().find(|v| v.name == name))
+            .unwrap_or_else(default)
+    })
+}
+
+fn default() -> &'static Variant {
+    let name = if cfg!(target_os = "macos") {
+        "split"
+    } else {
+        "combined"
+    };
+    VARIANTS
+        .iter()
+        .find(|v| v.name == name)
+        .expect("a known variant")
+}
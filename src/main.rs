@@ -1,51 +1,617 @@
-use std::io::{self, stdout};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, IsTerminal, stdout};
+use std::net::IpAddr;
+use std::ops::{Range, RangeInclusive};
+use std::path::PathBuf;
 use std::process::Command;
-use std::time::Duration;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 
+#[macro_use]
+extern crate rip;
+
+mod a11y;
+mod audit;
+mod bench;
+mod cgroup;
+mod cleanup;
+mod cli;
+mod clipboard;
+mod config;
+mod details;
+mod diagnostics;
+mod doctor;
+mod escalate;
+mod firewall;
+mod format;
+mod graceful;
+mod guard;
+mod heatmap;
+mod history;
+mod keepalive;
+mod keymap;
+mod mdns;
+mod menu;
+mod monitor;
+mod package;
+mod pick;
+mod postmortem;
+mod power;
+mod probe;
+mod risk;
+mod rollup;
+mod rpc;
+mod rules;
+mod shutdown;
+mod ssinfo;
+mod system;
+mod theme;
+mod tree;
+mod unixsock;
+mod upnp;
+mod uri;
+mod window;
+mod wizard;
+
+#[cfg(test)]
+mod tests;
+
+use conflict::BindConflict;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use probe::Health;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+use rip::scanner::{self, PortScanner, ScanStats, get_port_processes};
+use rip::{
+    PortProcess, UNKNOWN_PID, backend, clock, conflict, container, enrich, i18n, json, lsof,
+    portscan, process, procfs, session, state,
 };
 
-#[derive(Clone, Debug)]
-struct PortProcess {
+/// Rows at the top of the settings view before the hidden items.
+const SETTINGS_ROWS: usize = 6;
+
+/// Wait before retrying a failed scan, doubled after each failure up to
+/// `RETRY_MAX`.
+const RETRY_BASE: Duration = Duration::from_secs(1);
+const RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Scans have been failing since the list was last filled; it still shows
+/// that list until a retry succeeds.
+struct Stale {
+    /// Why the last scan failed.
+    reason: String,
+    /// Failed scans in a row.
+    attempts: u32,
+    /// When the list shown was scanned.
+    since: Instant,
+    retry_at: Instant,
+}
+
+/// A destructive action on the selected row, possibly waiting for y/N.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KillAction {
+    Kill,
+    /// Save a core dump first, then kill.
+    CoreDumpThenKill,
+    /// Save the thread stacks first, then kill.
+    StacksThenKill,
+    /// Kill the owner of the selected row in the Unix socket view.
+    SocketOwner,
+    /// Send the signal picked with `s`, whatever the config says.
+    Signal(&'static str),
+    /// Kill every row marked with Space.
+    Marked,
+    /// SIGTERM, then SIGKILL if it is still running after the grace period.
+    Graceful,
+    /// Kill everything the selected process started, then the process.
+    Tree,
+    /// Signal the selected process's whole process group.
+    Group,
+}
+
+/// A signal refused because the process is someone else's, to send again
+/// through `sudo kill`.
+#[derive(Clone, Debug, PartialEq)]
+struct SudoKill {
     pid: u32,
-    port: u16,
-    protocol: String,
     name: String,
+    port: u16,
+    signal: String,
+}
+
+/// What the main pane shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum View {
+    Processes,
+    Rollup(rollup::GroupBy),
+    Heatmap,
+    Settings,
+    UnixSockets,
+}
+
+/// The column the process list is sorted by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SortKey {
+    Port,
+    Pid,
+    Name,
+    Protocol,
+}
+
+impl SortKey {
+    fn label(self) -> String {
+        match self {
+            SortKey::Port => tr!("port"),
+            SortKey::Pid => tr!("PID"),
+            SortKey::Name => tr!("name"),
+            SortKey::Protocol => tr!("protocol"),
+        }
+    }
+
+    fn next(self) -> SortKey {
+        match self {
+            SortKey::Port => SortKey::Pid,
+            SortKey::Pid => SortKey::Name,
+            SortKey::Name => SortKey::Protocol,
+            SortKey::Protocol => SortKey::Port,
+        }
+    }
+
+    fn compare(self, a: &PortProcess, b: &PortProcess) -> std::cmp::Ordering {
+        match self {
+            SortKey::Port => a.port.cmp(&b.port),
+            SortKey::Pid => a.pid.cmp(&b.pid),
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Protocol => a.protocol.cmp(&b.protocol),
+        }
+    }
 }
 
 struct App {
     processes: Vec<PortProcess>,
+    conflicts: Vec<BindConflict>,
+    hide_ide: bool,
+    show_ephemeral: bool,
+    ephemeral: RangeInclusive<u16>,
+    /// Lists the sockets; `SystemScanner` unless something else is plugged in.
+    scanner: Box<dyn PortScanner>,
+    /// Sends the kills; `process::System` unless something else is plugged
+    /// in.
+    signaller: Box<dyn process::Signaller>,
+    heatmap_ranges: Vec<RangeInclusive<u16>>,
+    low_power: bool,
+    /// The config's theme; monochrome when `NO_COLOR` is set.
+    theme: theme::Theme,
+    keymap: keymap::Keymap,
+    debug: bool,
+    scan_stats: ScanStats,
+    /// Set while scans fail and the list shows the last good one.
+    stale: Option<Stale>,
+    refresh_time: Duration,
+    last_refresh: Instant,
+    /// Wall-clock time of the last scan that succeeded, for the status bar.
+    refreshed_at: String,
+    render_time: Duration,
+    frame_times: VecDeque<Instant>,
+    log: diagnostics::MessageLog,
+    health_checks: bool,
+    probes: probe::Probes,
+    firewall: Option<Result<firewall::Ruleset, String>>,
+    /// Firewall rules being read on a thread, since `nft` and `iptables`
+    /// run again after every scan.
+    firewall_rx: Option<Receiver<Result<firewall::Ruleset, String>>>,
+    /// The scanner was still probing in the background after the last
+    /// scan; once it is done, scan again to list what it found.
+    scanner_busy: bool,
+    /// Process group and session per PID while their columns are shown.
+    process_groups: Option<HashMap<u32, enrich::ProcessGroup>>,
+    upnp: Option<Vec<upnp::Mapping>>,
+    upnp_rx: Option<Receiver<Result<Vec<upnp::Mapping>, String>>>,
+    local_ip: Option<IpAddr>,
+    mdns: Option<Vec<mdns::Service>>,
+    mdns_rx: Option<Receiver<Result<Vec<mdns::Service>, String>>>,
+    /// Who rip runs as, to tell which processes it may signal.
+    whoami: Option<String>,
+    config: config::Config,
+    /// Why the config file could not be read; changes are not saved over it.
+    config_error: Option<String>,
+    settings_state: ListState,
+    unix_sockets: Vec<unixsock::UnixSocket>,
+    unix_state: ListState,
+    /// Waiting for confirmation of this action on the selected row; `true`
+    /// when only a capital Y confirms it (high-risk kills).
+    pending_kill: Option<(KillAction, bool)>,
+    /// Waiting for y/N on sending this again with sudo.
+    pending_sudo: Option<SudoKill>,
+    /// The signal picker is open, with this index into `config::SIGNALS`
+    /// highlighted.
+    signal_picker: Option<usize>,
+    /// A graceful kill in progress, and the victim to add to the history
+    /// once the process has actually gone.
+    escalation: Option<(escalate::Escalation, history::Victim)>,
+    /// The kill wizard for a port with several processes behind it.
+    wizard: Option<wizard::Wizard>,
+    /// The context menu (`m` or a right click) for the selected row.
+    menu: Option<menu::Menu>,
+    /// Where the process list was last drawn, to tell which row a click
+    /// lands on.
+    list_area: Rect,
+    /// The process tree popup (`P`) for the selected row.
+    tree: Option<tree::Tree>,
+    /// Write what is signalled to the kill log; off in tests, whose
+    /// signals go to a fake system.
+    audit: bool,
+    /// Write settings changed here to the config file; off in tests, so
+    /// they leave the real one alone.
+    persist: bool,
+    /// What was killed this session, oldest first, so it can be started
+    /// again.
+    history: Vec<history::Victim>,
+    /// The kill history popup (`H`) is open, with this entry highlighted
+    /// (0 is the most recent).
+    history_popup: Option<usize>,
+    /// Rows marked with Space for a batch kill, by (pid, port).
+    marked: HashSet<(u32, u16)>,
+    risks: HashMap<u32, risk::Risk>,
+    show_details: bool,
+    /// Detail pane contents for the (pid, port) they were collected for.
+    details: Option<((u32, u16), Vec<details::Section>)>,
     list_state: ListState,
+    view: View,
+    groups: Vec<rollup::Group>,
+    group_state: ListState,
+    /// Narrow the process list to one rollup group.
+    drill: Option<(rollup::GroupBy, String)>,
+    /// Every row the last scan kept, before the `/` filter narrows them
+    /// down to `processes`.
+    unfiltered: Vec<PortProcess>,
+    /// Text typed after `/`; only rows whose name, port or PID contains it
+    /// are shown.
+    filter: String,
+    /// The filter bar has the keyboard.
+    editing_filter: bool,
+    /// `t`: only rows of this protocol are shown, on top of the filter.
+    only_protocol: Option<&'static str>,
+    /// `M`: only the current user's rows are shown.
+    only_mine: bool,
+    /// `W`/`--cwd`: only rows whose process works under this directory are
+    /// shown.
+    workspace: Option<PathBuf>,
+    /// The directory `W` narrows to: `--cwd`, or where rip was started.
+    workspace_root: Option<PathBuf>,
+    /// PIDs working under `workspace`, found at the last scan.
+    in_workspace: HashSet<u32>,
+    /// OS service rows the last scan left out (`hide_system`).
+    system_hidden: usize,
+    /// The column `O` sorts the list by, and whether in descending order.
+    sort: (SortKey, bool),
+    /// Formatted rows kept between frames; `None` until first drawn.
+    rows: Vec<Option<Line<'static>>>,
     message: Option<String>,
+    recorder: Option<session::Recorder>,
+    /// Set when showing a recorded session; nothing is scanned or signalled.
+    replay: Option<session::Replay>,
+    /// `--safe`: every kill asks first, and protected processes are refused.
+    safe: bool,
+    /// Scan through `sudo -n` (after E and a successful `sudo -v`).
+    elevated: bool,
+    /// E was pressed: the terminal has to be handed to sudo's password
+    /// prompt, which only the main loop can do.
+    wants_sudo: bool,
+    /// Like `wants_sudo`, for a `sudo kill` the user said yes to.
+    wants_sudo_kill: Option<SudoKill>,
     should_quit: bool,
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(options: cli::TuiOptions) -> Self {
+        let scanner = Box::new(scanner::SystemScanner::in_background(
+            options.scan_range.clone(),
+        ));
+        let mut app = App::with(options, config::load(), scanner, Box::new(process::System));
+        app.audit = true;
+        app.persist = true;
+        app
+    }
+
+    /// An app that lists ports with `scanner` and kills through
+    /// `signaller`, with `config` as read (or why it could not be).
+    fn with(
+        options: cli::TuiOptions,
+        config: Result<config::Config, String>,
+        scanner: Box<dyn PortScanner>,
+        signaller: Box<dyn process::Signaller>,
+    ) -> Self {
+        let (config, config_error) = match config {
+            Ok(config) => (config, None),
+            Err(e) => (config::Config::default(), Some(e)),
+        };
+        let (recorder, record_error) = match &options.record {
+            Some(path) => match session::Recorder::create(path) {
+                Ok(recorder) => (Some(recorder), None),
+                Err(e) => (
+                    None,
+                    Some(tr!("Cannot record to {}: {}", path.display(), e)),
+                ),
+            },
+            None => (None, None),
+        };
         let mut app = App {
             processes: Vec::new(),
+            conflicts: Vec::new(),
+            hide_ide: options.hide_ide,
+            show_ephemeral: options.show_ephemeral,
+            ephemeral: portscan::ephemeral_range(),
+            scanner,
+            signaller,
+            heatmap_ranges: options.heatmap_ranges,
+            low_power: options.low_power.unwrap_or_else(power::on_battery),
+            theme: theme_for(&config),
+            keymap: keymap::Keymap::new(&config.keys),
+            debug: options.debug,
+            scan_stats: ScanStats::default(),
+            stale: None,
+            refresh_time: Duration::ZERO,
+            last_refresh: Instant::now(),
+            refreshed_at: String::new(),
+            render_time: Duration::ZERO,
+            frame_times: VecDeque::new(),
+            log: diagnostics::MessageLog::default(),
+            health_checks: false,
+            probes: probe::Probes::new(),
+            firewall: None,
+            firewall_rx: None,
+            scanner_busy: false,
+            process_groups: None,
+            upnp: None,
+            upnp_rx: None,
+            local_ip: None,
+            mdns: None,
+            mdns_rx: None,
+            whoami: enrich::current_user(),
+            config,
+            config_error,
+            settings_state: ListState::default(),
+            unix_sockets: Vec::new(),
+            unix_state: ListState::default(),
+            pending_kill: None,
+            pending_sudo: None,
+            signal_picker: None,
+            escalation: None,
+            wizard: None,
+            menu: None,
+            list_area: Rect::default(),
+            tree: None,
+            audit: false,
+            persist: false,
+            history: Vec::new(),
+            history_popup: None,
+            marked: HashSet::new(),
+            risks: HashMap::new(),
+            show_details: false,
+            details: None,
             list_state: ListState::default(),
+            view: View::Processes,
+            groups: Vec::new(),
+            group_state: ListState::default(),
+            drill: None,
+            unfiltered: Vec::new(),
+            filter: String::new(),
+            editing_filter: false,
+            only_protocol: None,
+            only_mine: false,
+            workspace_root: options
+                .workspace
+                .clone()
+                .or_else(|| std::env::current_dir().ok()),
+            workspace: options.workspace,
+            in_workspace: HashSet::new(),
+            system_hidden: 0,
+            sort: (SortKey::Port, false),
+            rows: Vec::new(),
             message: None,
+            recorder,
+            replay: options.replay,
+            safe: options.safe,
+            elevated: false,
+            wants_sudo: false,
+            wants_sudo_kill: None,
             should_quit: false,
         };
         app.refresh_processes();
         if !app.processes.is_empty() {
             app.list_state.select(Some(0));
         }
+        if let Some(e) = &app.config_error {
+            app.message = Some(tr!("Config error: {} (changes will not be saved)", e));
+        }
+        if let Some(e) = record_error {
+            app.message = Some(e);
+        }
+        if let Some(port) = options.select_port {
+            match app.processes.iter().position(|p| p.port == port) {
+                Some(i) => {
+                    app.list_state.select(Some(i));
+                    if options.kill_selected {
+                        app.request_kill(KillAction::Kill);
+                    }
+                }
+                None => app.message = Some(tr!("Nothing is listening on :{}", port)),
+            }
+        }
         app
     }
 
     fn refresh_processes(&mut self) {
-        self.processes = get_port_processes();
-        self.message = Some(format!("Found {} processes", self.processes.len()));
+        if self.replay.is_some() {
+            return self.show_replay_step();
+        }
+        let started = Instant::now();
+        let (mut scanned, mut stats, mut sudo_error) = self.scan();
+        // Every listener gone at once is more often a backend hiccup than
+        // the truth; believe it once a second scan agrees.
+        if scanned.is_empty() && stats.failure.is_none() && !self.unfiltered.is_empty() {
+            (scanned, stats, sudo_error) = self.scan();
+        }
+        if scanned.is_empty()
+            && let Some(failure) = stats.failure.clone()
+        {
+            self.scan_stats = stats;
+            return self.keep_stale(failure);
+        }
+        let recovered = self.stale.take().is_some();
+        self.scanner_busy = self.scanner.busy();
+        (self.unfiltered, self.scan_stats) = (scanned, stats);
+        if self.hide_ide {
+            self.unfiltered.retain(|p| p.tag.is_none());
+        }
+        let scanned = self.unfiltered.len();
+        if self.config.hide_system {
+            self.unfiltered.retain(|p| !system::is_os_service(&p.name));
+        }
+        self.system_hidden = scanned - self.unfiltered.len();
+        self.unfiltered.retain(|p| !self.config.ignores(p));
+        if !self.show_ephemeral {
+            self.unfiltered
+                .retain(|p| p.listening || !self.ephemeral.contains(&p.port));
+        }
+        if let Some((by, key)) = &self.drill {
+            self.unfiltered.retain(|p| by.key(p) == *key);
+        }
+        self.conflicts = conflict::find_bind_conflicts(&self.unfiltered);
+        self.risks = risk::assess(&self.unfiltered, &self.config);
+        self.load_workspace();
+        self.apply_filter();
+        self.record(|recorder, app| recorder.snapshot(&app.processes));
+        self.refresh_time = started.elapsed();
+        self.last_refresh = Instant::now();
+        self.refreshed_at = clock::local_time_of_day();
+        if let Some(failure) = &self.scan_stats.failure {
+            self.message = Some(tr!(
+                "Backend error: {} (B: save diagnostic bundle)",
+                failure
+            ));
+            return self.finish_refresh();
+        }
+        self.message = Some(if let Some(e) = sudo_error {
+            tr!("Scanning with sudo failed ({}); E: try again", e)
+        } else if recovered {
+            tr!(
+                "Scanning works again; found {} processes",
+                self.processes.len()
+            )
+        } else if self.scan_stats.hidden > 0 {
+            tr!(
+                "Found {} processes; {} ports belong to other users (E: rescan with sudo)",
+                self.processes.len(),
+                self.scan_stats.hidden
+            )
+        } else {
+            tr!("Found {} processes", self.processes.len())
+        });
+        self.finish_refresh();
+    }
+
+    /// One scan, through sudo once elevated; if sudo fails, a scan as
+    /// ourselves and why sudo failed.
+    fn scan(&mut self) -> (Vec<PortProcess>, ScanStats, Option<io::Error>) {
+        if self.elevated {
+            match backend::scan_elevated() {
+                Ok((processes, stats)) => return (processes, stats, None),
+                Err(e) => {
+                    self.elevated = false;
+                    let (processes, stats) = self.scan_unelevated();
+                    return (processes, stats, Some(e));
+                }
+            }
+        }
+        let (processes, stats) = self.scan_unelevated();
+        (processes, stats, None)
+    }
+
+    /// A scan with `scanner`; one that cannot run at all lists nothing and
+    /// says why, like a failing backend.
+    fn scan_unelevated(&self) -> (Vec<PortProcess>, ScanStats) {
+        self.scanner.scan().unwrap_or_else(|e| {
+            let stats = ScanStats {
+                backend: self.scanner.name(),
+                failure: Some(e.to_string()),
+                ..ScanStats::default()
+            };
+            (Vec::new(), stats)
+        })
+    }
+
+    /// A scan failed without finding anything: keep showing the last good
+    /// list, marked stale, and retry after a backoff.
+    fn keep_stale(&mut self, reason: String) {
+        let attempts = self.stale.as_ref().map_or(0, |s| s.attempts) + 1;
+        let backoff = RETRY_BASE
+            .saturating_mul(1 << (attempts - 1).min(8))
+            .min(RETRY_MAX);
+        let since = self.stale.as_ref().map_or(self.last_refresh, |s| s.since);
+        self.message = Some(tr!("Backend error: {} (B: save diagnostic bundle)", reason));
+        self.stale = Some(Stale {
+            reason,
+            attempts,
+            since,
+            retry_at: Instant::now() + backoff,
+        });
+    }
+
+    /// E: start scanning through sudo, which first needs the terminal for
+    /// its password prompt; pressed again, go back to scanning as ourselves.
+    fn toggle_sudo(&mut self) {
+        if self.elevated {
+            self.elevated = false;
+            self.refresh_processes();
+            self.message = Some(tr!("Scanning without sudo again"));
+        } else {
+            self.wants_sudo = true;
+        }
+    }
+
+    /// Called by the main loop once `sudo -v` has run.
+    fn finish_sudo(&mut self, result: io::Result<()>) {
+        match result {
+            Ok(()) => {
+                self.elevated = true;
+                self.refresh_processes();
+            }
+            Err(e) => self.message = Some(tr!("sudo failed: {}", e)),
+        }
+    }
+
+    fn finish_refresh(&mut self) {
+        self.invalidate_rows();
+        self.marked
+            .retain(|mark| self.unfiltered.iter().any(|p| (p.pid, p.port) == *mark));
+        self.details = None;
+        self.regroup();
+        if self.health_checks {
+            self.probes.rescanned(&self.unfiltered);
+        }
+        if self.firewall.is_some() && self.firewall_rx.is_none() {
+            self.load_firewall();
+        }
+        if self.process_groups.is_some() {
+            self.load_process_groups();
+        }
+        self.clamp_selection();
+    }
 
+    /// Keep the selection on a row that exists, selecting the first one if
+    /// there was none.
+    fn clamp_selection(&mut self) {
         if self.processes.is_empty() {
             self.list_state.select(None);
         } else if let Some(selected) = self.list_state.selected() {
@@ -57,220 +623,3852 @@ impl App {
         }
     }
 
-    fn next(&mut self) {
-        if self.processes.is_empty() {
+    /// Show the rows recorded at the replay's current event, with the row
+    /// a recorded key press acted on selected.
+    fn show_replay_step(&mut self) {
+        let Some(replay) = &self.replay else {
             return;
-        }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i >= self.processes.len() - 1 {
-                    0
-                } else {
-                    i + 1
+        };
+        self.unfiltered = replay.snapshot().to_vec();
+        let mut acted_on = None;
+        self.message = replay.current().map(|(secs, event)| {
+            let at = format!("+{:.1}s", secs);
+            match event {
+                session::Event::Snapshot(rows) => {
+                    tr!("{}: list refreshed, {} processes", at, rows.len())
                 }
+                session::Event::Key(key, row) => {
+                    acted_on = *row;
+                    tr!("{}: pressed {}", at, key)
+                }
+                session::Event::Message(message) => format!("{}: {}", at, message),
             }
-            None => 0,
+        });
+        self.conflicts = conflict::find_bind_conflicts(&self.unfiltered);
+        self.risks.clear();
+        self.last_refresh = Instant::now();
+        self.apply_filter();
+        self.finish_refresh();
+        if let Some(i) = acted_on.and_then(|(pid, port)| {
+            self.processes
+                .iter()
+                .position(|p| (p.pid, p.port) == (pid, port))
+        }) {
+            self.list_state.select(Some(i));
+        }
+    }
+
+    fn step_replay(&mut self, forward: bool) {
+        if self
+            .replay
+            .as_mut()
+            .is_some_and(|replay| replay.step(forward))
+        {
+            self.show_replay_step();
+        }
+    }
+
+    /// Append to the session recording, if any. A write error ends the
+    /// recording, not the session.
+    fn record(&mut self, write: impl FnOnce(&mut session::Recorder, &App) -> io::Result<()>) {
+        let Some(mut recorder) = self.recorder.take() else {
+            return;
         };
-        self.list_state.select(Some(i));
+        match write(&mut recorder, self) {
+            Ok(()) => self.recorder = Some(recorder),
+            Err(e) => self.message = Some(tr!("Recording stopped: {}", e)),
+        }
     }
 
-    fn previous(&mut self) {
-        if self.processes.is_empty() {
+    fn toggle_ide_helpers(&mut self) {
+        self.hide_ide = !self.hide_ide;
+        self.refresh_processes();
+        self.message = Some(if self.hide_ide {
+            tr!("Hiding IDE/debugger helpers")
+        } else {
+            tr!("Showing IDE/debugger helpers")
+        });
+    }
+
+    /// `v`: show or hide the OS's own services, and remember it.
+    fn toggle_system_services(&mut self) {
+        self.config.hide_system = !self.config.hide_system;
+        self.refresh_processes();
+        let changed = system_services_label(self.config.hide_system);
+        self.message = Some(match self.save_config() {
+            Ok(()) => changed,
+            Err(e) => tr!("{} (this session only: {})", changed, e),
+        });
+    }
+
+    /// Add the selected row's process name (or, with `by_port`, its port) to
+    /// the ignore list and save it.
+    fn hide_selected(&mut self, by_port: bool) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
             return;
+        };
+        let entry = if by_port {
+            format!(":{}", process.port)
+        } else {
+            process.name.clone()
+        };
+        if !self.config.ignore.contains(&entry) {
+            self.config.ignore.push(entry.clone());
         }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.processes.len() - 1
+        self.refresh_processes();
+        self.message = Some(match self.save_config() {
+            Ok(()) => tr!("Hid {} (S: manage hidden items)", entry),
+            Err(e) => tr!("Hid {} for this session only: {}", entry, e),
+        });
+    }
+
+    /// Change the selected setting to its next value, or show the selected
+    /// hidden item again.
+    fn change_setting(&mut self) {
+        let Some(index) = self.settings_state.selected() else {
+            return;
+        };
+        let changed = match index {
+            0 => {
+                let signals = config::SIGNALS;
+                let current = signals.iter().position(|s| *s == self.config.signal);
+                let next = current.map_or(0, |i| (i + 1) % signals.len());
+                self.config.signal = signals[next].to_string();
+                tr!("Kill signal: SIG{}", self.config.signal)
+            }
+            1 => {
+                self.config.confirm_kill = !self.config.confirm_kill;
+                if self.config.confirm_kill {
+                    tr!("Asking before each kill")
                 } else {
-                    i - 1
+                    tr!("Killing without asking")
                 }
             }
-            None => 0,
+            2 => {
+                let choices = config::GRACE_CHOICES;
+                let current = choices.iter().position(|s| *s == self.config.grace_secs);
+                let next = current.map_or(0, |i| (i + 1) % choices.len());
+                self.config.grace_secs = choices[next];
+                tr!("Graceful kill: SIGKILL after {}s", self.config.grace_secs)
+            }
+            3 => {
+                let choices = config::REFRESH_CHOICES;
+                let current = choices.iter().position(|s| *s == self.config.refresh_secs);
+                let next = current.map_or(0, |i| (i + 1) % choices.len());
+                self.config.refresh_secs = choices[next];
+                refresh_label(self.config.refresh_secs)
+            }
+            4 => {
+                self.config.hide_system = !self.config.hide_system;
+                self.refresh_processes();
+                system_services_label(self.config.hide_system)
+            }
+            5 => {
+                let themes = theme::BUILTIN;
+                let current = themes
+                    .iter()
+                    .position(|(name, _)| *name == self.config.theme);
+                let next = current.map_or(0, |i| (i + 1) % themes.len());
+                self.config.theme = themes[next].0.to_string();
+                self.theme = theme_for(&self.config);
+                self.invalidate_rows();
+                theme_label(&self.config)
+            }
+            _ => return self.unhide_selected(),
         };
-        self.list_state.select(Some(i));
+        self.message = Some(match self.save_config() {
+            Ok(()) => changed,
+            Err(e) => tr!("{} (this session only: {})", changed, e),
+        });
     }
 
-    fn kill_selected(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(process) = self.processes.get(selected) {
-                let pid = process.pid;
-                let name = process.name.clone();
-
-                match kill_process(pid) {
-                    Ok(_) => {
-                        self.message = Some(format!("Killed process {} (PID: {})", name, pid));
-                        self.refresh_processes();
-                    }
-                    Err(e) => {
-                        self.message = Some(format!("Failed to kill PID {}: {}", pid, e));
-                    }
-                }
-            }
+    /// Remove the selected entry from the ignore list in the settings view.
+    fn unhide_selected(&mut self) {
+        let Some(index) = self
+            .settings_state
+            .selected()
+            .and_then(|i| i.checked_sub(SETTINGS_ROWS))
+            .filter(|i| *i < self.config.ignore.len())
+        else {
+            return;
+        };
+        let entry = self.config.ignore.remove(index);
+        if index >= self.config.ignore.len() {
+            self.settings_state
+                .select(Some(SETTINGS_ROWS + self.config.ignore.len() - 1));
         }
+        self.refresh_processes();
+        self.message = Some(match self.save_config() {
+            Ok(()) => tr!("Showing {} again", entry),
+            Err(e) => tr!("Showing {} again for this session only: {}", entry, e),
+        });
     }
-}
-
-fn get_port_processes() -> Vec<PortProcess> {
-    let output = Command::new("lsof")
-        .args(["-iTCP", "-iUDP", "-sTCP:LISTEN", "-P", "-n"])
-        .output();
 
-    let output = match output {
-        Ok(o) => o,
-        Err(_) => return Vec::new(),
-    };
+    fn save_config(&self) -> Result<(), String> {
+        if let Some(e) = &self.config_error {
+            return Err(tr!("not overwriting a config file with errors ({})", e));
+        }
+        if !self.persist {
+            return Ok(());
+        }
+        config::save(&self.config)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut processes = Vec::new();
-    let mut seen_pids: std::collections::HashSet<u32> = std::collections::HashSet::new();
+    fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+    }
 
-    for line in stdout.lines().skip(1) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 9 {
-            continue;
+    /// Detail pane sections for the selected row, collected on first use.
+    fn selected_details(&mut self) -> &[details::Section] {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return &[];
+        };
+        let key = (process.pid, process.port);
+        if self
+            .details
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != key)
+        {
+            let risk = self.risks.get(&process.pid);
+            self.details = Some((key, details::collect(process, risk)));
         }
+        self.details.as_ref().map_or(&[], |(_, sections)| sections)
+    }
 
-        let name = parts[0].to_string();
-        let pid: u32 = match parts[1].parse() {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
+    fn toggle_settings(&mut self) {
+        if self.view == View::Settings {
+            self.view = View::Processes;
+            return;
+        }
+        self.view = View::Settings;
+        if self.settings_state.selected().is_none() {
+            self.settings_state.select(Some(0));
+        }
+    }
 
-        if seen_pids.contains(&pid) {
-            continue;
+    fn toggle_unix_sockets(&mut self) {
+        if self.view == View::UnixSockets {
+            self.view = View::Processes;
+            return;
         }
+        self.view = View::UnixSockets;
+        self.refresh_unix_sockets();
+    }
 
-        let protocol = if parts[4].contains("TCP") || parts[7].contains("TCP") {
-            "TCP".to_string()
-        } else if parts[4].contains("UDP") || parts[7].contains("UDP") {
-            "UDP".to_string()
+    fn refresh_unix_sockets(&mut self) {
+        self.unix_sockets = unixsock::list();
+        let abstract_count = self.unix_sockets.iter().filter(|s| s.is_abstract()).count();
+        self.message = Some(if cfg!(windows) {
+            tr!("Found {} named pipes", self.unix_sockets.len())
         } else {
-            "???".to_string()
-        };
+            tr!(
+                "Found {} Unix sockets and named pipes ({} abstract)",
+                self.unix_sockets.len(),
+                abstract_count
+            )
+        });
+        let selected = self.unix_state.selected().unwrap_or(0);
+        self.unix_state.select(
+            (!self.unix_sockets.is_empty()).then(|| selected.min(self.unix_sockets.len() - 1)),
+        );
+    }
 
-        let addr_field = parts[8];
-        let port: u16 = if let Some(port_str) = addr_field.rsplit(':').next() {
-            port_str.parse().unwrap_or(0)
+    fn toggle_ephemeral(&mut self) {
+        self.show_ephemeral = !self.show_ephemeral;
+        self.refresh_processes();
+        self.message = Some(if self.show_ephemeral {
+            tr!("Showing outgoing sockets on ephemeral ports")
         } else {
-            0
+            tr!("Hiding outgoing sockets on ephemeral ports")
+        });
+    }
+
+    fn toggle_firewall(&mut self) {
+        self.invalidate_rows();
+        if self.firewall.take().is_some() | self.firewall_rx.take().is_some() {
+            self.message = Some(tr!("Firewall column off"));
+            return;
+        }
+        self.load_firewall();
+        self.message = Some(tr!("Reading firewall rules..."));
+    }
+
+    fn load_firewall(&mut self) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(firewall::load());
+        });
+        self.firewall_rx = Some(rx);
+    }
+
+    /// The column appears with the first rules read; later reads replace
+    /// them quietly.
+    fn poll_firewall(&mut self) -> bool {
+        let Some(ruleset) = self.firewall_rx.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return false;
         };
+        self.firewall_rx = None;
+        if self.firewall.is_none() {
+            self.message = Some(match &ruleset {
+                Ok(rules) => tr!("Firewall status from {}", rules.source()),
+                Err(e) => tr!("Cannot read firewall rules: {}", e),
+            });
+        }
+        self.firewall = Some(ruleset);
+        true
+    }
+
+    /// Rescan once the scanner's background probe has finished, keeping
+    /// the status message the way a timed refresh does.
+    fn poll_scanner(&mut self) -> bool {
+        if !self.scanner_busy || self.scanner.busy() {
+            return false;
+        }
+        self.scanner_busy = false;
+        self.auto_refresh();
+        true
+    }
 
-        if port > 0 {
-            seen_pids.insert(pid);
-            processes.push(PortProcess {
-                pid,
-                port,
-                protocol,
-                name,
+    /// `G`: show or hide the PGID and SID columns.
+    fn toggle_process_groups(&mut self) {
+        if self.process_groups.take().is_some() {
+            self.message = Some(tr!("Process group columns hidden"));
+        } else {
+            self.load_process_groups();
+            self.message = Some(if cfg!(windows) {
+                tr!("Windows has no process groups or sessions")
+            } else {
+                tr!("PGID/SID in red: the group's leader has exited")
             });
         }
+        self.invalidate_rows();
     }
 
-    processes.sort_by_key(|p| p.port);
-    processes
-}
+    fn load_process_groups(&mut self) {
+        self.process_groups = Some(
+            self.unfiltered
+                .iter()
+                .filter_map(|p| Some((p.pid, enrich::process_group(p.pid)?)))
+                .collect(),
+        );
+    }
 
-fn kill_process(pid: u32) -> io::Result<()> {
-    let status = Command::new("kill")
-        .arg("-9")
-        .arg(pid.to_string())
-        .status()?;
+    fn toggle_upnp(&mut self) {
+        self.invalidate_rows();
+        if self.upnp.take().is_some() || self.upnp_rx.take().is_some() {
+            self.message = Some(tr!("UPnP check off"));
+            return;
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(upnp::port_mappings());
+        });
+        self.local_ip = probe::local_ip();
+        self.upnp_rx = Some(rx);
+        self.message = Some(tr!("Asking the gateway for UPnP port mappings..."));
+    }
 
-    if status.success() {
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("kill command failed with status: {}", status),
-        ))
+    fn poll_upnp(&mut self) -> bool {
+        let Some(result) = self.upnp_rx.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return false;
+        };
+        self.upnp_rx = None;
+        match result {
+            Ok(mappings) => {
+                let exposed = self
+                    .processes
+                    .iter()
+                    .filter(|p| self.forwarded_port(p, &mappings).is_some())
+                    .count();
+                self.message = Some(tr!(
+                    "Gateway forwards {} ports, {} to listeners here",
+                    mappings.len(),
+                    exposed
+                ));
+                self.upnp = Some(mappings);
+            }
+            Err(e) => self.message = Some(tr!("UPnP check failed: {}", e)),
+        }
+        true
     }
-}
 
-fn main() -> io::Result<()> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    fn forwarded_port(&self, process: &PortProcess, mappings: &[upnp::Mapping]) -> Option<u16> {
+        mappings
+            .iter()
+            .find(|m| m.targets(process, self.local_ip))
+            .map(|m| m.external_port)
+    }
 
-    let mut app = App::new();
+    fn toggle_mdns(&mut self) {
+        self.invalidate_rows();
+        if self.mdns.take().is_some() || self.mdns_rx.take().is_some() {
+            self.message = Some(tr!("mDNS names off"));
+            return;
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(mdns::browse());
+        });
+        self.local_ip = probe::local_ip();
+        self.mdns_rx = Some(rx);
+        self.message = Some(tr!("Browsing mDNS services..."));
+    }
 
-    loop {
-        terminal.draw(|frame| ui(frame, &mut app))?;
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
-                        KeyCode::Down | KeyCode::Char('j') => app.next(),
-                        KeyCode::Up | KeyCode::Char('k') => app.previous(),
-                        KeyCode::Enter | KeyCode::Char('d') => app.kill_selected(),
-                        KeyCode::Char('r') => app.refresh_processes(),
-                        _ => {}
-                    }
-                }
+    fn poll_mdns(&mut self) -> bool {
+        let Some(result) = self.mdns_rx.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return false;
+        };
+        self.mdns_rx = None;
+        match result {
+            Ok(services) => {
+                self.message = Some(tr!("Found {} mDNS services", services.len()));
+                self.mdns = Some(services);
             }
+            Err(e) => self.message = Some(tr!("mDNS browse failed: {}", e)),
         }
+        true
+    }
 
-        if app.should_quit {
-            break;
+    /// The mDNS service advertised by this host for a listener, if any.
+    fn advertised(&self, process: &PortProcess) -> Option<&mdns::Service> {
+        let protocol = format!("_{}", process.protocol.to_lowercase());
+        self.mdns.as_deref()?.iter().find(|s| {
+            s.port == process.port
+                && s.kind.ends_with(&protocol)
+                && (s.host.is_loopback()
+                    || Some(s.host) == self.local_ip
+                    || s.host.to_string() == process.address)
+        })
+    }
+
+    fn save_diagnostics(&mut self) {
+        let Some(failure) = &self.scan_stats.failure else {
+            self.message = Some(tr!("No backend failure to report"));
+            return;
+        };
+        self.message = Some(
+            match diagnostics::write_bundle(failure, &self.scan_stats.raw, &self.log) {
+                Ok(path) => tr!("Diagnostic bundle written to {}", path.display()),
+                Err(e) => tr!("Failed to write diagnostic bundle: {}", e),
+            },
+        );
+    }
+
+    fn record_frame(&mut self, render_time: Duration) {
+        let now = Instant::now();
+        self.render_time = render_time;
+        self.frame_times.push_back(now);
+        while self
+            .frame_times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > Duration::from_secs(1))
+        {
+            self.frame_times.pop_front();
         }
     }
 
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
-    Ok(())
-}
+    /// Drain results from background work. Returns whether a redraw is needed.
+    fn poll_background(&mut self) -> bool {
+        // Non-short-circuiting: every source must be drained.
+        let changed = self.probes.poll()
+            | self.poll_upnp()
+            | self.poll_mdns()
+            | self.poll_escalation()
+            | self.poll_firewall()
+            | self.poll_scanner();
+        if changed {
+            self.invalidate_rows();
+        }
+        changed
+    }
 
-fn ui(frame: &mut Frame, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(5),
-            Constraint::Length(3),
-        ])
-        .split(frame.area());
+    fn has_background_work(&self) -> bool {
+        self.stale.is_some()
+            || self.probes.busy()
+            || self.upnp_rx.is_some()
+            || self.mdns_rx.is_some()
+            || self.firewall_rx.is_some()
+            || self.scanner_busy
+            || self.escalation.is_some()
+    }
 
-    let title = Paragraph::new("rip - Kill processes on ports")
-        .style(Style::default().fg(Color::Cyan).bold())
-        .block(Block::default().borders(Borders::ALL));
-    frame.render_widget(title, chunks[0]);
+    /// How long to wait for input before checking background work again.
+    /// With nothing in flight only input can change the screen, so the loop
+    /// mostly sleeps.
+    fn poll_timeout(&self) -> Duration {
+        let timeout = if !self.has_background_work() {
+            power::IDLE_POLL_TIMEOUT
+        } else if self.low_power {
+            power::LOW_POWER_POLL_TIMEOUT
+        } else {
+            power::POLL_TIMEOUT
+        };
+        let timeout = match &self.stale {
+            Some(stale) => timeout.min(stale.retry_at.saturating_duration_since(Instant::now())),
+            None => timeout,
+        };
+        match self.auto_refresh_interval() {
+            Some(interval) => timeout.min(interval.saturating_sub(self.last_refresh.elapsed())),
+            None => timeout,
+        }
+    }
 
-    let items: Vec<ListItem> = app
-        .processes
-        .iter()
-        .map(|p| {
-            let content = format!(
-                ":{:<6} {:4} {:>6}  {}",
-                p.port, p.protocol, p.pid, p.name
-            );
-            ListItem::new(content)
+    /// The configured auto-refresh interval, doubled in low-power mode.
+    fn auto_refresh_interval(&self) -> Option<Duration> {
+        let interval = self.config.refresh_interval()?;
+        Some(if self.low_power {
+            interval * 2
+        } else {
+            interval
         })
-        .collect();
+    }
 
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .title("Processes (PORT | PROTO | PID | NAME)")
-                .borders(Borders::ALL),
-        )
-        .highlight_style(
-            Style::default()
-                .bg(Color::DarkGray)
-                .fg(Color::White)
-                .bold(),
-        )
-        .highlight_symbol(">> ");
+    /// Never while a kill is waiting for confirmation: the rows must not
+    /// move under the question. A failed scan is retried on its own
+    /// schedule, even with auto-refresh off.
+    fn refresh_due(&self) -> bool {
+        self.pending_kill.is_none()
+            && self.pending_sudo.is_none()
+            && self.wizard.is_none()
+            && self.replay.is_none()
+            && match &self.stale {
+                Some(stale) => Instant::now() >= stale.retry_at,
+                None => self
+                    .auto_refresh_interval()
+                    .is_some_and(|interval| self.last_refresh.elapsed() >= interval),
+            }
+    }
 
-    frame.render_stateful_widget(list, chunks[1], &mut app.list_state);
+    /// A timed refresh keeps the current status message unless the scan
+    /// itself failed or has just recovered.
+    fn auto_refresh(&mut self) {
+        let message = self.message.take();
+        let was_stale = self.stale.is_some();
+        self.refresh_processes();
+        if self.scan_stats.failure.is_none() && !was_stale {
+            self.message = message;
+        }
+    }
 
-    let help_text = match &app.message {
-        Some(msg) => format!("{} | ↑/↓:Navigate  Enter/d:Kill  r:Refresh  q:Quit", msg),
-        None => "↑/↓:Navigate  Enter/d:Kill  r:Refresh  q:Quit".to_string(),
-    };
+    fn toggle_low_power(&mut self) {
+        self.low_power = !self.low_power;
+        if self.low_power && self.health_checks {
+            self.toggle_health_checks();
+        }
+        self.message = Some(if self.low_power {
+            tr!("Low-power mode on: slower polling, probes paused")
+        } else {
+            tr!("Low-power mode off")
+        });
+    }
 
-    let status = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::Yellow))
-        .block(Block::default().borders(Borders::ALL));
-    frame.render_widget(status, chunks[2]);
+    fn toggle_health_checks(&mut self) {
+        if self.low_power && !self.health_checks {
+            self.message = Some(tr!("Health checks are paused in low-power mode (L)"));
+            return;
+        }
+        self.health_checks = !self.health_checks;
+        self.invalidate_rows();
+        if self.health_checks {
+            self.message = Some(tr!("HTTP health checks on"));
+        } else {
+            self.probes.clear();
+            self.message = Some(tr!("HTTP health checks off"));
+        }
+    }
+
+    /// The list that navigation keys move through in the current view.
+    fn navigated_list(&mut self) -> (&mut ListState, usize) {
+        match self.view {
+            View::Processes | View::Heatmap => (&mut self.list_state, self.processes.len()),
+            View::Rollup(_) => (&mut self.group_state, self.groups.len()),
+            View::UnixSockets => (&mut self.unix_state, self.unix_sockets.len()),
+            View::Settings => (
+                &mut self.settings_state,
+                SETTINGS_ROWS + self.config.ignore.len(),
+            ),
+        }
+    }
+
+    /// Switch to the rollup by `by`, or back to the process list if it is
+    /// already shown. The rollup always covers every listener.
+    fn toggle_rollup(&mut self, by: rollup::GroupBy) {
+        if self.view == View::Rollup(by) {
+            self.view = View::Processes;
+            return;
+        }
+        self.view = View::Rollup(by);
+        if self.drill.take().is_some() {
+            self.refresh_processes();
+        } else {
+            self.regroup();
+        }
+    }
+
+    fn regroup(&mut self) {
+        let View::Rollup(by) = self.view else {
+            return;
+        };
+        self.groups = rollup::group(&self.processes, by);
+        let selected = self.group_state.selected().unwrap_or(0);
+        self.group_state
+            .select((!self.groups.is_empty()).then(|| selected.min(self.groups.len() - 1)));
+    }
+
+    /// Show the process list narrowed to the selected group.
+    fn drill_down(&mut self) {
+        let View::Rollup(by) = self.view else {
+            return;
+        };
+        let Some(group) = self.group_state.selected().and_then(|i| self.groups.get(i)) else {
+            return;
+        };
+        self.drill = Some((by, group.key.clone()));
+        self.view = View::Processes;
+        self.list_state.select(Some(0));
+        self.refresh_processes();
+    }
+
+    fn clear_drill(&mut self) {
+        self.drill = None;
+        self.refresh_processes();
+    }
+
+    /// `/`: open the filter bar, keeping any filter already typed.
+    fn open_filter(&mut self) {
+        self.editing_filter = true;
+    }
+
+    /// Add `c` to the filter, or with `None` take its last character off,
+    /// and narrow the list right away.
+    fn type_filter(&mut self, c: Option<char>) {
+        match c {
+            Some(c) => self.filter.push(c),
+            None => {
+                self.filter.pop();
+            }
+        }
+        self.filter_changed();
+    }
+
+    fn clear_filter(&mut self) {
+        self.editing_filter = false;
+        self.filter.clear();
+        self.filter_changed();
+    }
+
+    /// `t`: show TCP only, then UDP only, then both again.
+    fn cycle_protocol(&mut self) {
+        self.only_protocol = match self.only_protocol {
+            None => Some("TCP"),
+            Some("TCP") => Some("UDP"),
+            Some(_) => None,
+        };
+        self.filter_changed();
+        self.message = Some(match self.only_protocol {
+            Some(protocol) => tr!("Showing {} only (t: next)", protocol),
+            None => tr!("Showing TCP and UDP"),
+        });
+    }
+
+    /// `M`: show only the current user's processes, or everyone's again.
+    fn toggle_mine(&mut self) {
+        let Some(me) = self.whoami.clone() else {
+            self.message = Some(tr!("Could not tell which user rip runs as"));
+            return;
+        };
+        self.only_mine = !self.only_mine;
+        self.filter_changed();
+        self.message = Some(if self.only_mine {
+            tr!("Showing only processes of {}", me)
+        } else {
+            tr!("Showing every user's processes")
+        });
+    }
+
+    /// `W`: show only processes working under the `--cwd` directory (or
+    /// the one rip was started in), or those in every directory again.
+    fn toggle_workspace(&mut self) {
+        if self.workspace.take().is_none() {
+            let Some(dir) = self.workspace_root.clone() else {
+                self.message = Some(tr!("Could not tell which directory rip runs in"));
+                return;
+            };
+            self.workspace = Some(dir);
+            self.load_workspace();
+        }
+        self.filter_changed();
+        self.message = Some(match &self.workspace {
+            Some(dir) => tr!("Showing processes working under {}", dir.display()),
+            None => tr!("Showing processes in every directory"),
+        });
+    }
+
+    /// Find which scanned processes work under `workspace`.
+    fn load_workspace(&mut self) {
+        let Some(dir) = &self.workspace else {
+            return;
+        };
+        self.in_workspace = self
+            .unfiltered
+            .iter()
+            .map(|p| p.pid)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|pid| enrich::works_under(*pid, dir))
+            .collect();
+    }
+
+    /// Show the rows the filter now lets through, without scanning again.
+    fn filter_changed(&mut self) {
+        self.apply_filter();
+        self.invalidate_rows();
+        self.details = None;
+        self.clamp_selection();
+    }
+
+    /// `O`: sort by the next column, each one ascending and then descending.
+    fn cycle_sort(&mut self) {
+        let (key, descending) = self.sort;
+        self.sort = if descending {
+            (key.next(), false)
+        } else {
+            (key, true)
+        };
+        self.filter_changed();
+        self.message = Some(tr!("Sorted by {}", sort_label(self.sort)));
+    }
+
+    /// Narrow `unfiltered` to the rows matching the filter and the quick
+    /// filters into `processes`, sorted by the chosen column (ties stay in
+    /// port order), keeping the selection on the same socket when it is
+    /// still shown.
+    fn apply_filter(&mut self) {
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+            .map(|p| (p.pid, p.port, p.protocol.clone()));
+        let filter = self.filter.to_lowercase();
+        self.processes = self
+            .unfiltered
+            .iter()
+            .filter(|p| {
+                p.name.to_lowercase().contains(&filter)
+                    || p.port.to_string().contains(&filter)
+                    || p.pid.to_string().contains(&filter)
+            })
+            .filter(|p| {
+                self.only_protocol
+                    .is_none_or(|protocol| p.protocol == protocol)
+            })
+            .filter(|p| !self.only_mine || (p.user.is_some() && p.user == self.whoami))
+            .filter(|p| self.workspace.is_none() || self.in_workspace.contains(&p.pid))
+            .cloned()
+            .collect();
+        let (key, descending) = self.sort;
+        self.processes.sort_by(|a, b| {
+            let order = key.compare(a, b);
+            if descending { order.reverse() } else { order }
+        });
+        // The same socket if it is still there, else another of the same
+        // process.
+        let Some((pid, port, protocol)) = selected else {
+            return;
+        };
+        let position = self
+            .processes
+            .iter()
+            .position(|p| p.pid == pid && p.port == port && p.protocol == protocol)
+            .or_else(|| self.processes.iter().position(|p| p.pid == pid));
+        if let Some(i) = position {
+            self.list_state.select(Some(i));
+        }
+    }
+
+    fn next(&mut self) {
+        let (state, len) = self.navigated_list();
+        if len == 0 {
+            return;
+        }
+        let i = match state.selected() {
+            Some(i) => {
+                if i >= len - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        let (state, len) = self.navigated_list();
+        if len == 0 {
+            return;
+        }
+        let i = match state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        state.select(Some(i));
+    }
+
+    /// Format the rows in `window` that are not cached yet.
+    fn format_rows(&mut self, window: Range<usize>) {
+        self.rows.resize(self.processes.len(), None);
+        for i in window {
+            if self.rows[i].is_none() {
+                self.rows[i] = Some(process_row(self, &self.processes[i]));
+            }
+        }
+    }
+
+    /// Drop cached rows after anything they display has changed.
+    fn invalidate_rows(&mut self) {
+        self.rows.clear();
+    }
+
+    /// The rows that fit in `height` lines, scrolled so the selection stays
+    /// visible.
+    fn visible_window(&mut self, height: usize) -> Range<usize> {
+        let len = self.processes.len();
+        let mut offset = self.list_state.offset();
+        if let Some(selected) = self.list_state.selected() {
+            if selected < offset {
+                offset = selected;
+            } else if selected >= offset + height {
+                offset = selected + 1 - height;
+            }
+        }
+        offset = offset.min(len.saturating_sub(height));
+        *self.list_state.offset_mut() = offset;
+        offset..len.min(offset + height)
+    }
+
+    /// Whether killing `process` would need sudo: it belongs to someone else
+    /// and rip is not running as root.
+    fn needs_escalation(&self, process: &PortProcess) -> bool {
+        match (&self.whoami, &process.user) {
+            (Some(me), Some(owner)) => me != "root" && me != owner,
+            _ => false,
+        }
+    }
+
+    /// Why a kill of `process` would fail with a permission error, said
+    /// before trying rather than as a bare `kill` exit status afterwards.
+    fn permission_refusal(&self, process: &PortProcess) -> Option<String> {
+        self.needs_escalation(process).then(|| {
+            tr!(
+                "{} (PID: {}) belongs to {}; only they or root can kill it (run rip with sudo)",
+                process.name,
+                process.pid,
+                process.user.as_deref().unwrap_or_default()
+            )
+        })
+    }
+
+    fn conflict_for(&self, row: &PortProcess) -> Option<&BindConflict> {
+        self.conflicts.iter().find(|c| c.involves(row))
+    }
+
+    fn selected_conflict(&self) -> Option<&BindConflict> {
+        let process = self.processes.get(self.list_state.selected()?)?;
+        self.conflict_for(process)
+    }
+
+    fn open_selected(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+
+        if process.protocol != "TCP" || probe::http_status(process, probe::HTTP_TIMEOUT).is_err() {
+            self.message = Some(tr!("No HTTP server on :{}", process.port));
+            return;
+        }
+
+        let url = probe::http_url(process);
+        self.message = Some(match probe::open_url(&url) {
+            Ok(_) => tr!("Opened {}", url),
+            Err(e) => tr!("Failed to open {}: {}", url, e),
+        });
+    }
+
+    fn focus_selected_window(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+
+        self.message = Some(match window::find(process.pid) {
+            Some(window) => match window::focus(&window) {
+                Ok(_) => tr!("Focused \"{}\"", window.title),
+                Err(e) => tr!("Failed to focus \"{}\": {}", window.title, e),
+            },
+            None => tr!(
+                "No window found for {} (PID: {})",
+                process.name,
+                process.pid_label()
+            ),
+        });
+    }
+
+    /// Change the selected process's nice value by `delta` (positive is
+    /// lower priority). Raising priority needs root.
+    fn renice_selected(&mut self, delta: i32) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        let (pid, name) = (process.pid, process.name.clone());
+        let nice = (process.nice.unwrap_or(0) + delta).clamp(-20, 19);
+
+        self.message = Some(match renice(pid, nice) {
+            Ok(()) => {
+                self.refresh_processes();
+                tr!("Set nice of {} (PID: {}) to {}", name, pid, nice)
+            }
+            Err(e) => tr!("Failed to renice PID {}: {}", pid, e),
+        });
+    }
+
+    /// A kill key: act right away, or ask first if the config says so.
+    /// Risky processes always ask, and high-risk ones need a capital Y.
+    fn request_kill(&mut self, action: KillAction) {
+        if action == KillAction::SocketOwner {
+            return self.request_socket_owner_kill();
+        }
+        if action == KillAction::Marked || action == KillAction::Kill && !self.marked.is_empty() {
+            return self.request_marked_kill();
+        }
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if let Some(refusal) = self
+            .config
+            .never_kill_refusal(process)
+            .or_else(|| self.safe_mode_refusal(process))
+        {
+            self.message = Some(refusal);
+            return;
+        }
+        if self.needs_escalation(process)
+            && process.pid != UNKNOWN_PID
+            && let Some(signal) = self.plain_signal(action, process)
+        {
+            let reason = tr!(
+                "{} (PID: {}) belongs to {}",
+                process.name,
+                process.pid,
+                process.user.as_deref().unwrap_or_default()
+            );
+            let kill = SudoKill {
+                pid: process.pid,
+                name: process.name.clone(),
+                port: process.port,
+                signal,
+            };
+            return self.offer_sudo(reason, kill);
+        }
+        if let Some(refusal) = self.permission_refusal(process) {
+            self.message = Some(refusal);
+            return;
+        }
+        if action == KillAction::Kill
+            && let Some(wizard) = wizard::plan(process, &self.processes, |pid, name| {
+                self.kill_signal_for(pid, name).to_string()
+            })
+        {
+            let proxy = wizard.chosen().0 == wizard::Strategy::ContainerStop;
+            self.message = Some(match &process.context {
+                Some(container) if proxy => tr!(
+                    "{} (PID: {}) only forwards :{} to a container ({}); stop the container to free it",
+                    process.name,
+                    process.pid_label(),
+                    process.port,
+                    container
+                ),
+                _ => tr!(
+                    "{} (PID: {}) is not alone behind :{}; pick what to stop",
+                    process.name,
+                    process.pid_label(),
+                    process.port
+                ),
+            });
+            self.wizard = Some(wizard);
+            return;
+        }
+        if action == KillAction::Group
+            && let Some(refusal) = self.group_refusal(process)
+        {
+            self.message = Some(refusal);
+            return;
+        }
+        let risk = self.risks.get(&process.pid).cloned().unwrap_or_default();
+        let level = risk.level();
+        if level == risk::Level::Low && !self.config.confirm_kill && !self.safe {
+            return self.run_kill_action(action);
+        }
+
+        let question = match action {
+            KillAction::Kill => tr!(
+                "Kill {} (PID: {}) on port {}?",
+                process.name,
+                process.pid_label(),
+                process.port
+            ),
+            KillAction::CoreDumpThenKill => tr!(
+                "Dump core of {} (PID: {}) on port {}, then kill it?",
+                process.name,
+                process.pid_label(),
+                process.port
+            ),
+            KillAction::StacksThenKill => tr!(
+                "Save stacks of {} (PID: {}) on port {}, then kill it?",
+                process.name,
+                process.pid_label(),
+                process.port
+            ),
+            KillAction::Signal(signal) => tr!(
+                "Send SIG{} to {} (PID: {}) on port {}?",
+                signal,
+                process.name,
+                process.pid_label(),
+                process.port
+            ),
+            KillAction::Graceful => tr!(
+                "Stop {} (PID: {}) on port {}, with SIGKILL after {}s?",
+                process.name,
+                process.pid_label(),
+                process.port,
+                self.config.grace_secs
+            ),
+            KillAction::Group => {
+                let Some(group) = enrich::process_group(process.pid) else {
+                    self.message = Some(tr!(
+                        "{} (PID: {}) has no process group rip can signal",
+                        process.name,
+                        process.pid_label()
+                    ));
+                    return;
+                };
+                let names: Vec<String> = enrich::group_members(group.pgid)
+                    .into_iter()
+                    .map(|(pid, name)| format!("{} ({})", name, pid))
+                    .collect();
+                tr!(
+                    "Send SIG{} to process group {} on port {}: {}?",
+                    self.kill_signal_for(process.pid, &process.name),
+                    group.pgid,
+                    process.port,
+                    names.join(", ")
+                )
+            }
+            KillAction::Tree => {
+                match tree::deepest_first([process.pid], &risk::process_table()).len() - 1 {
+                    0 => tr!(
+                        "Kill {} (PID: {}) on port {}? It started no other processes",
+                        process.name,
+                        process.pid_label(),
+                        process.port
+                    ),
+                    started => tr!(
+                        "Kill {} (PID: {}) on port {} and the {} processes it started?",
+                        process.name,
+                        process.pid_label(),
+                        process.port,
+                        started
+                    ),
+                }
+            }
+            KillAction::SocketOwner | KillAction::Marked => unreachable!("handled above"),
+        };
+        let strict = level == risk::Level::High;
+        self.message = Some(match level {
+            risk::Level::High => tr!(
+                "{} High risk: {}. Press Y (capital) to go ahead",
+                question,
+                risk.reasons.join(", ")
+            ),
+            risk::Level::Medium => tr!("{} Risk: {}. [y/N]", question, risk.reasons.join(", ")),
+            risk::Level::Low => tr!("{} [y/N]", question),
+        });
+        self.pending_kill = Some((action, strict));
+    }
+
+    /// Space: mark or unmark the selected row for a batch kill, then move
+    /// down so a run of rows can be marked quickly.
+    fn toggle_mark(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        let mark = (process.pid, process.port);
+        if !self.marked.remove(&mark) {
+            self.marked.insert(mark);
+        }
+        self.invalidate_rows();
+        self.message = Some(tr!(
+            "{} marked (Enter/d: kill them, Esc: clear)",
+            self.marked.len()
+        ));
+        self.next();
+    }
+
+    fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.invalidate_rows();
+        self.message = Some(tr!("Marks cleared"));
+    }
+
+    /// The marked rows, including any the filter hides.
+    fn marked_processes(&self) -> Vec<&PortProcess> {
+        self.unfiltered
+            .iter()
+            .filter(|p| self.marked.contains(&(p.pid, p.port)))
+            .collect()
+    }
+
+    /// Like `request_kill`, for every marked row at once: it asks if the
+    /// config (or `--safe`) says so or any of them is risky, and needs a
+    /// capital Y if any is high-risk.
+    fn request_marked_kill(&mut self) {
+        let marked = self.marked_processes();
+        let level = marked
+            .iter()
+            .filter_map(|p| self.risks.get(&p.pid))
+            .map(risk::Risk::level)
+            .fold(risk::Level::Low, |a, b| if b > a { b } else { a });
+        if level == risk::Level::Low && !self.config.confirm_kill && !self.safe {
+            return self.kill_marked();
+        }
+        let names: Vec<String> = marked
+            .iter()
+            .map(|p| format!("{} ({})", p.name, p.pid))
+            .collect();
+        let question = tr!("Kill {} marked: {}?", marked.len(), names.join(", "));
+        self.message = Some(match level {
+            risk::Level::High => tr!(
+                "{} Some are high risk. Press Y (capital) to go ahead",
+                question
+            ),
+            _ => tr!("{} [y/N]", question),
+        });
+        self.pending_kill = Some((KillAction::Marked, level == risk::Level::High));
+    }
+
+    /// Signal every marked process, then refresh once and report how each
+    /// one went. `--safe` skips the ones it protects.
+    fn kill_marked(&mut self) {
+        let mut results = Vec::new();
+        let mut killed = 0;
+        let marked: Vec<PortProcess> = self.marked_processes().into_iter().cloned().collect();
+        for process in &marked {
+            let label = format!("{} (PID: {})", process.name, process.pid);
+            if let Some(refusal) = self
+                .config
+                .never_kill_refusal(process)
+                .or_else(|| self.safe_mode_refusal(process))
+                .or_else(|| self.permission_refusal(process))
+            {
+                results.push(refusal);
+                continue;
+            }
+            let signal = self.kill_signal_for(process.pid, &process.name).to_string();
+            let victim = history::Victim::capture(process.pid, &process.name, Some(process.port));
+            let result = self.send(process.pid, &process.name, Some(process.port), &signal);
+            results.push(match result {
+                Ok(()) => {
+                    killed += 1;
+                    self.history.push(victim);
+                    tr!("{}: SIG{} sent", label, signal)
+                }
+                Err(e) => tr!("{}: failed: {}", label, e),
+            });
+        }
+        self.marked.clear();
+        self.refresh_processes();
+        self.message = Some(tr!(
+            "Killed {} of {}: {}",
+            killed,
+            marked.len(),
+            results.join("; ")
+        ));
+    }
+
+    /// Why `--safe` will not let `process` be killed, if it will not.
+    fn safe_mode_refusal(&self, process: &PortProcess) -> Option<String> {
+        self.safe_mode_refusal_given(process, self.risks.get(&process.pid))
+    }
+
+    /// `safe_mode_refusal` for a process scored with `risk`, which need
+    /// not be in the list.
+    fn safe_mode_refusal_given(
+        &self,
+        process: &PortProcess,
+        risk: Option<&risk::Risk>,
+    ) -> Option<String> {
+        if !self.safe {
+            return None;
+        }
+        if process.user.as_deref() == Some("root") {
+            return Some(tr!(
+                "Safe mode: {} (PID: {}) runs as root; not killing it",
+                process.name,
+                process.pid_label()
+            ));
+        }
+        let risk = risk?;
+        risk.protected.then(|| {
+            tr!(
+                "Safe mode: {} (PID: {}) is protected ({}); not killing it",
+                process.name,
+                process.pid_label(),
+                risk.reasons.join(", ")
+            )
+        })
+    }
+
+    /// Why each of `members`, the processes a tree or group kill would
+    /// reach, would not be killed on its own: `never_kill`, `--safe` or
+    /// another user's. A listed process is judged by its row, any other by
+    /// one built from `ps`, on no port.
+    fn member_refusals(&self, members: &[(u32, String)]) -> Vec<(u32, String)> {
+        let rows: Vec<PortProcess> = members
+            .iter()
+            .map(|(pid, name)| {
+                self.unfiltered
+                    .iter()
+                    .find(|p| p.pid == *pid)
+                    .cloned()
+                    .unwrap_or_else(|| PortProcess {
+                        pid: *pid,
+                        name: name.clone(),
+                        user: enrich::process_user(*pid),
+                        ..PortProcess::default()
+                    })
+            })
+            .collect();
+        // Scoring runs ps; only --safe needs it.
+        let risks = if self.safe {
+            risk::assess(&rows, &self.config)
+        } else {
+            HashMap::new()
+        };
+        rows.iter()
+            .filter_map(|row| {
+                let refusal = self
+                    .config
+                    .never_kill_refusal(row)
+                    .or_else(|| self.safe_mode_refusal_given(row, risks.get(&row.pid)))
+                    .or_else(|| self.permission_refusal(row))?;
+                Some((row.pid, refusal))
+            })
+            .collect()
+    }
+
+    /// Why the group `process` leads or belongs to may not be signalled:
+    /// one signal reaches every member, so a single member that would be
+    /// refused on its own keeps the whole group alive.
+    fn group_refusal(&self, process: &PortProcess) -> Option<String> {
+        let group = enrich::process_group(process.pid)?;
+        let refusals = self.member_refusals(&enrich::group_members(group.pgid));
+        if refusals.is_empty() {
+            return None;
+        }
+        let reasons: Vec<String> = refusals.into_iter().map(|(_, refusal)| refusal).collect();
+        Some(tr!(
+            "Not signalling process group {}: {}",
+            group.pgid,
+            reasons.join("; ")
+        ))
+    }
+
+    fn run_kill_action(&mut self, action: KillAction) {
+        let killed = self.history.len();
+        self.run_kill_action_now(action);
+        self.hint_relaunch(killed);
+    }
+
+    fn run_kill_action_now(&mut self, action: KillAction) {
+        match action {
+            KillAction::Kill => self.kill_selected(),
+            KillAction::Signal(signal) => self.signal_selected(Some(signal)),
+            KillAction::Graceful => self.kill_selected_gracefully(),
+            KillAction::Marked => self.kill_marked(),
+            KillAction::CoreDumpThenKill => self.dump_core_and_kill(),
+            KillAction::StacksThenKill => self.capture_stacks_and_kill(),
+            KillAction::SocketOwner => self.kill_socket_owner(),
+            KillAction::Tree => self.kill_tree(),
+            KillAction::Group => self.kill_group(),
+        }
+    }
+
+    /// The signal `action` sends to `process` and nothing else, which
+    /// `sudo kill` can send just as well; none for actions that do more.
+    fn plain_signal(&self, action: KillAction, process: &PortProcess) -> Option<String> {
+        match action {
+            KillAction::Kill => Some(self.kill_signal_for(process.pid, &process.name).to_string()),
+            KillAction::Signal(signal) => Some(signal.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Say why `kill` could not be sent and ask whether to send it with
+    /// sudo instead.
+    fn offer_sudo(&mut self, reason: String, kill: SudoKill) {
+        self.message = Some(tr!("{}; send SIG{} with sudo? [y/N]", reason, kill.signal));
+        self.pending_sudo = Some(kill);
+    }
+
+    /// Send `kill` through `sudo kill`, once the main loop has handed sudo
+    /// the terminal for its password prompt.
+    fn sudo_kill(&mut self, kill: SudoKill) {
+        let killed = self.history.len();
+        let victim = history::ends_process(&kill.signal)
+            .then(|| history::Victim::capture(kill.pid, &kill.name, Some(kill.port)));
+        let result = self.signaller.signal_as_root(kill.pid, &kill.signal);
+        let entry = audit::Entry {
+            pid: Some(kill.pid),
+            name: &kill.name,
+            port: Some(kill.port),
+            signal: &kill.signal,
+            group: None,
+            via: "sudo",
+        };
+        self.audit(&entry, &result);
+        match result {
+            Ok(()) => {
+                self.history.extend(victim);
+                self.refresh_processes();
+                self.message = Some(tr!(
+                    "Sent SIG{} to {} (PID: {}) with sudo",
+                    kill.signal,
+                    kill.name,
+                    kill.pid
+                ));
+            }
+            Err(e) => {
+                self.message = Some(tr!("Failed to kill PID {} with sudo: {}", kill.pid, e));
+            }
+        }
+        self.hint_relaunch(killed);
+    }
+
+    /// The signal a plain kill sends to process `pid` called `name`.
+    fn kill_signal_for(&self, pid: u32, name: &str) -> &str {
+        graceful::signal_for(&self.config, pid, name).unwrap_or(&self.config.signal)
+    }
+
+    /// Enter in the kill wizard: show the chosen strategy's steps, or run
+    /// them if they are already shown.
+    fn advance_wizard(&mut self) {
+        let Some(wizard) = &mut self.wizard else {
+            return;
+        };
+        if !wizard.reviewing {
+            wizard.reviewing = true;
+            return;
+        }
+        let Some(wizard) = self.wizard.take() else {
+            return;
+        };
+        let killed = self.history.len();
+        let (strategy, steps) = wizard.chosen();
+        let mut failure = None;
+        for (index, step) in steps.iter().enumerate() {
+            // The processes behind the port are remembered; what they
+            // started is theirs to start again.
+            let victim = match step {
+                wizard::Step::Signal { pid, name, signal }
+                    if history::ends_process(signal)
+                        && self.processes.iter().any(|p| p.pid == *pid) =>
+                {
+                    Some(history::Victim::capture(*pid, name, Some(wizard.port)))
+                }
+                _ => None,
+            };
+            let result = match step {
+                wizard::Step::Signal { pid, name, signal } => {
+                    self.send(*pid, name, Some(wizard.port), signal)
+                }
+                wizard::Step::Run(argv) => {
+                    let result = wizard::run(argv);
+                    let entry = audit::Entry {
+                        pid: None,
+                        name: &argv.join(" "),
+                        port: Some(wizard.port),
+                        signal: "run",
+                        group: None,
+                        via: "tui",
+                    };
+                    self.audit(&entry, &result);
+                    result
+                }
+            };
+            if let Err(e) = result {
+                failure = Some((index + 1, e));
+                break;
+            }
+            self.history.extend(victim);
+        }
+        self.refresh_processes();
+        let held = self.processes.iter().any(|p| p.port == wizard.port);
+        self.message = Some(match failure {
+            Some((step, e)) => tr!("{}: step {} failed: {}", strategy.label(), step, e),
+            None if held => tr!(
+                "{}: done, but :{} is still held",
+                strategy.label(),
+                wizard.port
+            ),
+            None => tr!("{}: done, :{} is free", strategy.label(), wizard.port),
+        });
+        self.hint_relaunch(killed);
+    }
+
+    /// The first owner of the row selected in the Unix socket view.
+    fn selected_socket_owner(&self) -> Option<(u32, String)> {
+        let socket = self.unix_sockets.get(self.unix_state.selected()?)?;
+        socket.owners.first().cloned()
+    }
+
+    /// Kill the socket's owner, asking first if the config says so.
+    fn request_socket_owner_kill(&mut self) {
+        let Some((pid, name)) = self.selected_socket_owner() else {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        };
+        if !self.config.confirm_kill && !self.safe {
+            return self.kill_socket_owner();
+        }
+        let path = &self.unix_sockets[self.unix_state.selected().unwrap_or(0)].path;
+        self.message = Some(tr!(
+            "Kill {} (PID: {}), which holds {}? [y/N]",
+            name,
+            pid,
+            path
+        ));
+        self.pending_kill = Some((KillAction::SocketOwner, false));
+    }
+
+    fn kill_socket_owner(&mut self) {
+        let Some((pid, name)) = self.selected_socket_owner() else {
+            return;
+        };
+        let signal = self.kill_signal_for(pid, &name).to_string();
+        let victim = history::Victim::capture(pid, &name, None);
+        let result = self.send(pid, &name, None, &signal);
+        if result.is_ok() {
+            self.history.push(victim);
+        }
+        self.refresh_unix_sockets();
+        self.message = Some(match result {
+            Ok(()) if signal == "KILL" => tr!("Killed process {} (PID: {})", name, pid),
+            Ok(()) => tr!("Sent SIG{} to {} (PID: {})", signal, name, pid),
+            Err(e) => tr!("Failed to kill PID {}: {}", pid, e),
+        });
+    }
+
+    /// Save the selected process's thread stacks, then kill it. If the
+    /// capture fails the process is left running.
+    fn capture_stacks_and_kill(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        let (pid, name) = (process.pid, process.name.clone());
+
+        match postmortem::capture_stacks(pid, &name) {
+            Ok(path) => {
+                self.kill_selected();
+                self.message = Some(tr!(
+                    "Stacks saved to {}. {}",
+                    path.display(),
+                    self.message.take().unwrap_or_default()
+                ));
+            }
+            Err(e) => {
+                self.message = Some(tr!(
+                    "Stack capture of PID {} failed, not killed: {}",
+                    pid,
+                    e
+                ))
+            }
+        }
+    }
+
+    /// Save a core dump of the selected process, then kill it, so the port
+    /// is freed without losing the evidence of why it hung.
+    fn dump_core_and_kill(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        let (pid, name, port) = (process.pid, process.name.clone(), process.port);
+
+        match postmortem::dump_core(pid, &name) {
+            Ok(postmortem::CoreDump::Saved(path)) => {
+                self.kill_selected();
+                self.message = Some(tr!(
+                    "Core dump saved to {}. {}",
+                    path.display(),
+                    self.message.take().unwrap_or_default()
+                ));
+            }
+            Ok(postmortem::CoreDump::Aborted) => {
+                let entry = audit::Entry {
+                    pid: Some(pid),
+                    name: &name,
+                    port: Some(port),
+                    signal: "ABRT",
+                    group: None,
+                    via: "tui",
+                };
+                self.audit(&entry, &Ok(()));
+                self.refresh_processes();
+                self.message = Some(tr!(
+                    "No gcore or procdump found: sent SIGABRT to {} (PID: {}); whether a core file is written depends on the system's core settings",
+                    name,
+                    pid
+                ));
+            }
+            Err(e) => {
+                self.message = Some(tr!("Core dump of PID {} failed, not killed: {}", pid, e))
+            }
+        }
+    }
+
+    /// s: choose the signal for the selected row from a popup.
+    fn open_signal_picker(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        let current = config::SIGNALS
+            .iter()
+            .position(|s| *s == self.config.signal);
+        self.signal_picker = Some(current.unwrap_or(0));
+    }
+
+    /// Move the highlight in the signal picker, wrapping around.
+    fn move_signal_picker(&mut self, down: bool) {
+        let count = config::SIGNALS.len();
+        if let Some(index) = &mut self.signal_picker {
+            *index = if down {
+                (*index + 1) % count
+            } else {
+                (*index + count - 1) % count
+            };
+        }
+    }
+
+    /// Send the highlighted signal, after the same confirmation as a kill.
+    fn pick_signal(&mut self) {
+        if let Some(index) = self.signal_picker.take() {
+            self.request_kill(KillAction::Signal(config::SIGNALS[index]));
+        }
+    }
+
+    /// `m`: open the context menu on the selected row.
+    fn open_menu(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        let stopped = process.pid != UNKNOWN_PID
+            && enrich::process_status(process.pid).is_some_and(|(state, _)| state.starts_with('T'));
+        let protection = if self.config.protect.contains(&process.name) {
+            Some(true)
+        } else if self.risks.get(&process.pid).is_some_and(|r| r.protected) {
+            None
+        } else {
+            Some(false)
+        };
+        let never_kill = self.config.never_kill.contains(&process.name);
+        self.menu = Some(menu::Menu::new(process, stopped, protection, never_kill));
+    }
+
+    /// Close the context menu with its highlighted entry picked, selecting
+    /// the row it was opened on again in case a refresh moved it. `None`
+    /// when that row is gone.
+    fn close_menu(&mut self) -> Option<menu::Item> {
+        let menu = self.menu.take()?;
+        let p = &menu.process;
+        match self
+            .processes
+            .iter()
+            .position(|q| q.pid == p.pid && q.port == p.port && q.protocol == p.protocol)
+        {
+            Some(i) => {
+                self.list_state.select(Some(i));
+                Some(menu.chosen())
+            }
+            None => {
+                self.message = Some(tr!(
+                    "{} (PID: {}) is no longer listening on :{}",
+                    p.name,
+                    p.pid_label(),
+                    p.port
+                ));
+                None
+            }
+        }
+    }
+
+    /// Copy the selected row's PID, or the address to reach it at.
+    fn copy_selected(&mut self, address: bool) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        let text = if address {
+            format!("{}:{}", probe::connect_host(process), process.port)
+        } else {
+            process.pid.to_string()
+        };
+        self.message = Some(match clipboard::copy(&text) {
+            Ok(()) => tr!("Copied {}", text),
+            Err(e) => tr!("Could not copy {}: {}", text, e),
+        });
+    }
+
+    /// Check once whether the selected row answers HTTP, without turning
+    /// on health checks for the whole list.
+    fn probe_selected(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        let url = probe::http_url(process);
+        self.message = Some(match probe::http_status(process, probe::HTTP_TIMEOUT) {
+            Ok(status) => tr!("{} answered HTTP {}", url, status),
+            Err(e) => tr!("No HTTP answer from {}: {}", url, e),
+        });
+    }
+
+    /// Put the selected process's name on the config's protect list, so
+    /// killing it takes a capital Y, or take it off again.
+    fn protect_selected(&mut self, protect: bool) {
+        let Some(name) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+            .map(|p| p.name.clone())
+        else {
+            return;
+        };
+        if !protect {
+            self.config.protect.retain(|entry| *entry != name);
+        } else if !self.config.protect.contains(&name) {
+            self.config.protect.push(name.clone());
+        }
+        self.risks = risk::assess(&self.unfiltered, &self.config);
+        self.invalidate_rows();
+        self.message = Some(match (protect, self.save_config()) {
+            (true, Ok(())) => tr!("Protected {}: killing it needs a capital Y", name),
+            (false, Ok(())) => tr!("{} is no longer protected", name),
+            (true, Err(e)) => tr!("Protected {} for this session only: {}", name, e),
+            (false, Err(e)) => tr!("{} is unprotected for this session only: {}", name, e),
+        });
+    }
+
+    /// Put the selected process's name on the config's never_kill list, so
+    /// rip refuses to kill it anywhere, or take it off again.
+    fn never_kill_selected(&mut self, never: bool) {
+        let Some(name) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+            .map(|p| p.name.clone())
+        else {
+            return;
+        };
+        if !never {
+            self.config.never_kill.retain(|entry| *entry != name);
+        } else if !self.config.never_kill.contains(&name) {
+            self.config.never_kill.push(name.clone());
+        }
+        self.message = Some(match (never, self.save_config()) {
+            (true, Ok(())) => tr!("rip will never kill {}", name),
+            (false, Ok(())) => tr!("rip may kill {} again", name),
+            (true, Err(e)) => tr!("Not killing {} for this session only: {}", name, e),
+            (false, Err(e)) => tr!("{} may be killed for this session only: {}", name, e),
+        });
+    }
+
+    /// The process list row drawn at `(x, y)` on screen, if any.
+    fn row_at(&self, x: u16, y: u16) -> Option<usize> {
+        let area = self.list_area;
+        let inside = x > area.x && x + 1 < area.right() && y > area.y && y + 1 < area.bottom();
+        inside
+            .then(|| self.list_state.offset() + (y - area.y - 1) as usize)
+            .filter(|i| *i < self.processes.len())
+    }
+
+    /// SIGTERM the selected process, escalating to SIGKILL once the grace
+    /// period is up; `poll_escalation` watches for that and reports each step
+    /// in the status bar.
+    fn kill_selected_gracefully(&mut self) {
+        if self.escalation.is_some() {
+            self.message = Some(tr!("A graceful kill is already in progress"));
+            return;
+        }
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        let victim = history::Victim::capture(process.pid, &process.name, Some(process.port));
+        let escalation = escalate::Escalation::new(&process, self.config.grace_period());
+        match self.send(process.pid, &process.name, Some(process.port), "TERM") {
+            Ok(()) => self.escalation = Some((escalation, victim)),
+            Err(e) => {
+                self.message = Some(tr!(
+                    "Failed to stop {}: {}",
+                    format!("{} (PID: {})", process.name, process.pid),
+                    e
+                ))
+            }
+        }
+    }
+
+    /// Show the latest step of a graceful kill. Returns whether anything
+    /// changed.
+    fn poll_escalation(&mut self) -> bool {
+        let Some((escalation, _)) = &mut self.escalation else {
+            return false;
+        };
+        let Some(step) = escalation.poll() else {
+            return false;
+        };
+        let process = escalation.process.clone();
+        let label = format!("{} (PID: {})", process.name, process.pid);
+        let gone = match step {
+            escalate::Step::Waiting(left) => {
+                self.message = Some(tr!(
+                    "Sent SIGTERM to {}; SIGKILL in {}s unless it exits",
+                    label,
+                    left
+                ));
+                return true;
+            }
+            escalate::Step::Exited(after) => {
+                self.message = Some(tr!(
+                    "{} exited {}s after SIGTERM",
+                    label,
+                    format!("{:.1}", after.as_secs_f64())
+                ));
+                true
+            }
+            escalate::Step::Expired => {
+                match self.send(process.pid, &process.name, Some(process.port), "KILL") {
+                    Ok(()) => {
+                        self.message = Some(tr!(
+                            "{} ignored SIGTERM for {}s; killed with SIGKILL",
+                            label,
+                            self.config.grace_secs
+                        ));
+                        true
+                    }
+                    Err(e) => {
+                        self.message = Some(tr!("Failed to stop {}: {}", label, e));
+                        false
+                    }
+                }
+            }
+        };
+        if let Some((_, victim)) = self.escalation.take()
+            && gone
+        {
+            self.history.push(victim);
+        }
+        let message = self.message.take();
+        self.refresh_processes();
+        self.message = message;
+        true
+    }
+
+    fn kill_selected(&mut self) {
+        self.signal_selected(None);
+    }
+
+    /// Send `signal` to `pid` (SIGKILL also waits for it to exit), and note
+    /// it in the kill log.
+    fn send(&self, pid: u32, name: &str, port: Option<u16>, signal: &str) -> io::Result<()> {
+        let result = if signal == "KILL" {
+            self.signaller.kill(pid)
+        } else {
+            self.signaller.signal(pid, signal)
+        };
+        let entry = audit::Entry {
+            pid: Some(pid),
+            name,
+            port,
+            signal,
+            group: None,
+            via: "tui",
+        };
+        self.audit(&entry, &result);
+        result
+    }
+
+    fn audit(&self, entry: &audit::Entry, result: &io::Result<()>) {
+        if self.audit {
+            audit::record(entry, result);
+        }
+    }
+
+    /// Send `chosen` to the selected process or, by default, its graceful
+    /// stop signal if it has one and the configured signal otherwise.
+    fn signal_selected(&mut self, chosen: Option<&'static str>) {
+        if let Some(selected) = self.list_state.selected()
+            && let Some(process) = self.processes.get(selected)
+        {
+            let pid = process.pid;
+            let name = process.name.clone();
+            let port = process.port;
+            let protocol = process.protocol.clone();
+            // Looked up first: the container may be gone after the kill.
+            let lifecycle = container::of_listener(process).and_then(|c| c.lifecycle());
+
+            let graceful = match chosen {
+                Some(_) => None,
+                None => graceful::signal_for(&self.config, pid, &name).map(str::to_string),
+            };
+            let signal = chosen
+                .map(str::to_string)
+                .or_else(|| graceful.clone())
+                .unwrap_or_else(|| self.config.signal.clone());
+            let victim = history::ends_process(&signal)
+                .then(|| history::Victim::capture(pid, &name, Some(port)));
+            let result = self.send(pid, &name, Some(port), &signal);
+
+            match result {
+                Ok(_) => {
+                    self.history.extend(victim);
+                    self.refresh_processes();
+                    // Children that inherited the socket keep the port busy
+                    // after the parent is gone.
+                    let holder = self
+                        .processes
+                        .iter()
+                        .find(|p| p.port == port && p.protocol == protocol);
+                    self.message = Some(match (signal.as_str(), holder) {
+                        ("KILL", Some(holder)) => tr!(
+                            "Killed {} (PID: {}), but :{} is still held by {} (PID: {})",
+                            name,
+                            pid,
+                            port,
+                            holder.name,
+                            holder.pid_label()
+                        ),
+                        ("KILL", None) => tr!("Killed process {} (PID: {})", name, pid),
+                        (signal, _) if graceful.is_some() => tr!(
+                            "Sent SIG{} to {} (PID: {}) for graceful shutdown",
+                            signal,
+                            name,
+                            pid
+                        ),
+                        (signal, _) => tr!("Sent SIG{} to {} (PID: {})", signal, name, pid),
+                    });
+                    if let Some(lifecycle) = lifecycle {
+                        let message = self.message.take().unwrap_or_default();
+                        self.message = Some(match lifecycle.file {
+                            Some(file) => tr!(
+                                "{}. Defined in {}; restart with: {}",
+                                message,
+                                file.display(),
+                                lifecycle.restart
+                            ),
+                            None => tr!("{}. Restart with: {}", message, lifecycle.restart),
+                        });
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                    let reason = tr!("Failed to kill PID {}: {}", pid, e);
+                    let kill = SudoKill {
+                        pid,
+                        name,
+                        port,
+                        signal,
+                    };
+                    self.offer_sudo(reason, kill);
+                }
+                Err(e) => {
+                    self.message = Some(tr!("Failed to kill PID {}: {}", pid, e));
+                }
+            }
+        }
+    }
+
+    /// Signal the selected process's process group, so a supervisor
+    /// (foreman, honcho, `npm run`) goes with the workers it would respawn.
+    fn kill_group(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(group) = enrich::process_group(process.pid) else {
+            self.message = Some(tr!(
+                "{} (PID: {}) has no process group rip can signal",
+                process.name,
+                process.pid_label()
+            ));
+            return;
+        };
+        let members = enrich::group_members(group.pgid);
+        // The group's leader (foreman, npm) is what starts the rest again.
+        let victim = match members.iter().find(|(pid, _)| *pid == group.pgid) {
+            Some((pid, name)) => history::Victim::capture(*pid, name, Some(process.port)),
+            None => history::Victim::capture(process.pid, &process.name, Some(process.port)),
+        };
+        let members = members.len();
+        let signal = self.kill_signal_for(process.pid, &process.name).to_string();
+        let result = self.signaller.signal_group(group.pgid, &signal);
+        let entry = audit::Entry {
+            pid: Some(process.pid),
+            name: &process.name,
+            port: Some(process.port),
+            signal: &signal,
+            group: Some(group.pgid),
+            via: "tui",
+        };
+        self.audit(&entry, &result);
+        match result {
+            Ok(()) => {
+                self.history.push(victim);
+                self.refresh_processes();
+                self.message = Some(tr!(
+                    "Sent SIG{} to process group {} of {} (PID: {}), {} processes",
+                    signal,
+                    group.pgid,
+                    process.name,
+                    process.pid,
+                    members
+                ));
+            }
+            Err(e) => {
+                self.message = Some(tr!("Failed to signal process group {}: {}", group.pgid, e));
+            }
+        }
+    }
+
+    /// Point at the history after a kill that can be undone by starting
+    /// the process again: one that added a victim with a known command.
+    fn hint_relaunch(&mut self, killed: usize) {
+        let relaunchable = self.history[killed..].iter().any(|v| !v.argv.is_empty());
+        if relaunchable
+            && let Some(key) = self.keymap.key_for(keymap::Action::History)
+            && let Some(message) = &mut self.message
+        {
+            message.push_str(&tr!(" ({}: history, to relaunch)", key));
+        }
+    }
+
+    /// `H`: open or close the kill history.
+    fn toggle_history(&mut self) {
+        if self.history_popup.take().is_some() {
+            return;
+        }
+        if self.history.is_empty() {
+            self.message = Some(tr!("Nothing killed yet this session"));
+            return;
+        }
+        self.history_popup = Some(0);
+    }
+
+    /// Move the highlight in the kill history, wrapping around.
+    fn move_history(&mut self, down: bool) {
+        let count = self.history.len();
+        if let Some(selected) = &mut self.history_popup {
+            *selected = if down {
+                (*selected + 1) % count
+            } else {
+                (*selected + count - 1) % count
+            };
+        }
+    }
+
+    /// Enter in the kill history: run the highlighted victim's command
+    /// again in its old directory.
+    fn relaunch_selected(&mut self) {
+        let Some(selected) = self.history_popup else {
+            return;
+        };
+        let index = self.history.len() - 1 - selected;
+        let victim = &mut self.history[index];
+        if let (Some(me), Some(owner)) = (&self.whoami, &victim.user)
+            && me != owner
+        {
+            self.message = Some(tr!(
+                "{} ran as {}; relaunching it from rip would run it as {}",
+                victim.name,
+                owner,
+                me
+            ));
+            return;
+        }
+        // A second copy would only fail to bind the port, or worse, not.
+        if let Some(pid) = victim.relaunched
+            && !process::has_exited(pid, None)
+        {
+            self.message = Some(tr!(
+                "{} is already relaunched as PID {}; kill that first",
+                victim.name,
+                pid
+            ));
+            return;
+        }
+        self.message = Some(match victim.relaunch() {
+            Ok((pid, log)) => {
+                victim.relaunched = Some(pid);
+                tr!(
+                    "Relaunched {} as PID {}; output goes to {}",
+                    victim.name,
+                    pid,
+                    log.display()
+                )
+            }
+            Err(e) => tr!("Could not relaunch {}: {}", victim.name, e),
+        });
+        self.history_popup = None;
+    }
+
+    /// `P`: show or hide where the selected process sits in the process
+    /// tree, and what it started.
+    fn toggle_tree(&mut self) {
+        if self.tree.take().is_some() {
+            return;
+        }
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+        else {
+            return;
+        };
+        if process.pid == UNKNOWN_PID {
+            self.message = Some(tr!("owning process is unknown"));
+            return;
+        }
+        self.tree = Some(tree::Tree::of(
+            process,
+            &self.processes,
+            &risk::process_table(),
+        ));
+    }
+
+    /// Signal what the selected process started, deepest first so no
+    /// supervisor (nodemon, a reloader) respawns a child before it goes
+    /// itself, then the process. Each gets its graceful stop signal if it
+    /// has one, the configured signal otherwise.
+    fn kill_tree(&mut self) {
+        let Some(process) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.processes.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let table = risk::process_table();
+        let order = tree::deepest_first([process.pid], &table);
+        let members: Vec<(u32, String)> = order
+            .iter()
+            .map(|pid| {
+                let name = if *pid == process.pid {
+                    process.name.clone()
+                } else {
+                    tree::name(*pid, &table)
+                };
+                (*pid, name)
+            })
+            .collect();
+        // Each descendant is refused the way it would be on its own; the
+        // rest of the tree still goes.
+        let refusals = self.member_refusals(&members);
+        let mut victim = Some(history::Victim::capture(
+            process.pid,
+            &process.name,
+            Some(process.port),
+        ));
+        let mut failures = Vec::new();
+        for (pid, name) in &members {
+            if refusals.iter().any(|(refused, _)| refused == pid) {
+                continue;
+            }
+            let signal = self.kill_signal_for(*pid, name).to_string();
+            let result = self.send(*pid, name, Some(process.port), &signal);
+            match result {
+                Ok(()) if *pid == process.pid => self.history.extend(victim.take()),
+                Ok(()) => {}
+                Err(e) => failures.push(tr!("{} (PID: {}): {}", name, pid, e)),
+            }
+        }
+        self.tree = None;
+        self.refresh_processes();
+        let killed = order.len() - failures.len() - refusals.len();
+        let mut skipped: Vec<String> = refusals.into_iter().map(|(_, refusal)| refusal).collect();
+        self.message = Some(match (skipped.is_empty(), failures.is_empty()) {
+            (true, true) if order.len() == 1 => tr!(
+                "Killed {} (PID: {}), which started no other processes",
+                process.name,
+                process.pid
+            ),
+            (true, true) => tr!(
+                "Killed {} (PID: {}) and the {} processes it started",
+                process.name,
+                process.pid,
+                order.len() - 1
+            ),
+            (true, false) => tr!(
+                "Killed {} of {} in the tree of {} (PID: {}); failed: {}",
+                killed,
+                order.len(),
+                process.name,
+                process.pid,
+                failures.join("; ")
+            ),
+            (false, _) => {
+                if !failures.is_empty() {
+                    skipped.push(tr!("failed: {}", failures.join("; ")));
+                }
+                tr!(
+                    "Killed {} of {} in the tree of {} (PID: {}); skipped: {}",
+                    killed,
+                    order.len(),
+                    process.name,
+                    process.pid,
+                    skipped.join("; ")
+                )
+            }
+        });
+    }
+}
+
+/// How far one renice keypress moves the nice value.
+const RENICE_STEP: i32 = 5;
+
+fn renice(pid: u32, nice: i32) -> io::Result<()> {
+    let output = Command::new("renice")
+        .args(["-n", &nice.to_string(), "-p", &pid.to_string()])
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let safe = cli::take_flag(&mut args, "--safe");
+    let command = cli::take_global(&mut args, "--lang")
+        .and_then(|lang| i18n::init(lang.as_deref()))
+        .and_then(|_| cli::take_global(&mut args, "--backend"))
+        .and_then(|name| backend::init(name.as_deref()))
+        .and_then(|_| cli::parse(args));
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("rip: {}\n\n{}", e, i18n::tr(cli::USAGE));
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        cli::Command::Tui(options) if options.a11y => a11y::run(cli::TuiOptions {
+            safe: safe || options.safe,
+            ..options
+        }),
+        cli::Command::Tui(options) => run_tui(cli::TuiOptions {
+            safe: safe || options.safe,
+            ..options
+        }),
+        cli::Command::Guard(options) => guard::run(cli::GuardOptions {
+            dry_run: safe,
+            ..options
+        }),
+        cli::Command::Bench(options) => bench::run(options),
+        cli::Command::Monitor(options) => monitor::run(options),
+        cli::Command::MonitorReport(options) => {
+            if let Err(e) = monitor::report(options) {
+                eprintln!("rip: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        cli::Command::List(format, workspace) => list(format, workspace),
+        cli::Command::Free(port) if safe => {
+            eprintln!("{}", tr!("Safe mode: only showing what holds :{}", port));
+            lookup(port)
+        }
+        cli::Command::Free(port) => free(port),
+        cli::Command::Lookup(port) => lookup(port),
+        cli::Command::Pick(query) => pick::run(query, safe),
+        cli::Command::RegisterUri => match uri::register() {
+            Ok(registered) => {
+                println!("{}", tr!("rip:// links now open rip ({})", registered));
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("rip: {}", e);
+                std::process::exit(1);
+            }
+        },
+        cli::Command::Doctor => doctor::run(),
+        cli::Command::RulesTest => rules::test(),
+        cli::Command::Replay(path) => match session::Replay::load(&path) {
+            Ok(replay) => run_tui(cli::TuiOptions {
+                replay: Some(replay),
+                ..cli::TuiOptions::default()
+            }),
+            Err(e) => {
+                eprintln!("rip: {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        cli::Command::Keepalive(options) => keepalive::run(options),
+        cli::Command::Cleanup(options) => cleanup::run(options, safe),
+        cli::Command::ServeStdio => rpc::serve_stdio(safe),
+        cli::Command::Help => {
+            println!("{}", i18n::tr(cli::USAGE));
+            Ok(())
+        }
+    }
+}
+
+/// Print every listener and exit, in the format picked with `--format`;
+/// with `--cwd`, only those working under that directory.
+fn list(format: &dyn format::Formatter, workspace: Option<PathBuf>) -> io::Result<()> {
+    let mut processes = get_port_processes();
+    if let Some(dir) = workspace {
+        processes.retain(|p| enrich::works_under(p.pid, &dir));
+    }
+    if format.name() == "text" && stdout().is_terminal() {
+        for (p, link) in processes.iter().zip(http_links(&processes)) {
+            print_row(p, link.as_deref());
+        }
+        return Ok(());
+    }
+    format.write(&mut stdout().lock(), &processes)
+}
+
+/// Kill everything listening on `port`, for scripts. Exits 1 when nothing
+/// was, or when a holder could not be killed.
+fn free(port: u16) -> io::Result<()> {
+    let holders: Vec<PortProcess> = get_port_processes()
+        .into_iter()
+        .filter(|p| p.port == port)
+        .collect();
+    if holders.is_empty() {
+        eprintln!("{}", tr!("Nothing is listening on :{}", port));
+        std::process::exit(1);
+    }
+    if !free_holders(holders, &config::load_or_exit(), &process::System) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Kill or stop each of `holders` for `free`, printing how it went.
+/// Returns whether they all went.
+fn free_holders(
+    holders: Vec<PortProcess>,
+    config: &config::Config,
+    signaller: &dyn process::Signaller,
+) -> bool {
+    let mut failed = false;
+    // Containers already stopped, by id: one may publish through several
+    // proxies (IPv4 and IPv6).
+    let mut stopped = HashSet::new();
+    for p in holders {
+        if let Some(refusal) = risk::kill_refusal(&p, config) {
+            eprintln!("{}", refusal);
+            failed = true;
+            continue;
+        }
+        // A proxy's container, which is what holds the port.
+        let container = if container::is_port_proxy(&p.name) {
+            container::of_listener(&p)
+        } else {
+            None
+        };
+        let result = if p.pid == UNKNOWN_PID {
+            Err(io::Error::other(tr!("owner unknown")))
+        } else if let Some(container) = &container {
+            if !stopped.insert(container.id.clone()) {
+                continue;
+            }
+            let result = container.stop();
+            let entry = audit::Entry {
+                pid: Some(p.pid),
+                name: &format!("{} stop {}", container.runtime, container.name),
+                port: Some(p.port),
+                signal: "run",
+                group: None,
+                via: "cli",
+            };
+            audit::record(&entry, &result);
+            result
+        } else {
+            audit::kill(signaller, &p, "cli")
+        };
+        match (result, &container) {
+            (Ok(()), Some(container)) => println!(
+                "{}",
+                tr!(
+                    "Stopped container {} ({}), which published :{}/{}",
+                    container.name,
+                    container.image,
+                    p.port,
+                    p.protocol
+                )
+            ),
+            (Ok(()), None) => println!(
+                "{}",
+                tr!(
+                    "Killed {} (PID: {}) on :{}/{}",
+                    p.name,
+                    p.pid,
+                    p.port,
+                    p.protocol
+                )
+            ),
+            (Err(e), _) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "Could not kill {} (PID: {}) on :{}: {}",
+                        p.name,
+                        p.pid_label(),
+                        p.port,
+                        e
+                    )
+                );
+                failed = true;
+            }
+        }
+    }
+    !failed
+}
+
+/// `:8080 TCP nginx (PID: 1234) *`, the `:8080` an OSC 8 hyperlink to
+/// `link` if there is one.
+fn print_row(p: &PortProcess, link: Option<&str>) {
+    let port = match link {
+        Some(url) => format!("\x1b]8;;{}\x1b\\:{}\x1b]8;;\x1b\\", url, p.port),
+        None => format!(":{}", p.port),
+    };
+    println!(
+        "{} {} {} (PID: {}) {}",
+        port,
+        p.protocol,
+        p.name,
+        p.pid_label(),
+        p.address
+    );
+}
+
+/// For each row, its URL if it answers HTTP, for terminals to show as a
+/// link. All `None` when stdout is not a terminal, where the escapes would
+/// only get in the way; the rows are probed in parallel otherwise.
+fn http_links(processes: &[PortProcess]) -> Vec<Option<String>> {
+    if !stdout().is_terminal() {
+        return vec![None; processes.len()];
+    }
+    std::thread::scope(|scope| {
+        let probes: Vec<_> = processes
+            .iter()
+            .map(|p| {
+                scope.spawn(move || {
+                    let http = p.listening
+                        && p.protocol == "TCP"
+                        && probe::http_status(p, probe::HTTP_TIMEOUT).is_ok();
+                    http.then(|| probe::http_url(p))
+                })
+            })
+            .collect();
+        probes
+            .into_iter()
+            .map(|probe| probe.join().unwrap_or(None))
+            .collect()
+    })
+}
+
+/// Print the listeners on `port`; exit status 1 if there are none.
+fn lookup(port: u16) -> io::Result<()> {
+    let holders: Vec<PortProcess> = get_port_processes()
+        .into_iter()
+        .filter(|p| p.port == port)
+        .collect();
+    if holders.is_empty() {
+        eprintln!("{}", tr!("Nothing is listening on :{}", port));
+        std::process::exit(1);
+    }
+    for (p, link) in holders.iter().zip(http_links(&holders)) {
+        print_row(p, link.as_deref());
+    }
+    Ok(())
+}
+
+fn run_tui(options: cli::TuiOptions) -> io::Result<()> {
+    enable_raw_mode()?;
+    stdout()
+        .execute(EnterAlternateScreen)?
+        .execute(EnableMouseCapture)?;
+    shutdown::install();
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let mut app = App::new(options);
+
+    let mut dirty = true;
+    loop {
+        dirty |= app.poll_background();
+        if app.refresh_due() {
+            app.auto_refresh();
+            dirty = true;
+        }
+        app.log.record(app.message.as_ref());
+        app.record(|recorder, app| recorder.message(app.message.as_ref()));
+        if dirty {
+            let started = Instant::now();
+            terminal.draw(|frame| ui(frame, &mut app))?;
+            app.record_frame(started.elapsed());
+            dirty = false;
+        }
+
+        if !event::poll(app.poll_timeout())? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                app.record(|recorder, app| {
+                    let selected = app.list_state.selected().and_then(|i| app.processes.get(i));
+                    recorder.key(&key_name(key.code), selected)
+                });
+                handle_key(&mut app, key.code);
+                dirty = true;
+                if std::mem::take(&mut app.wants_sudo) {
+                    let result = with_terminal_released(&mut terminal, || {
+                        println!("{}", tr!("rip needs sudo to see other users' processes."));
+                        backend::authenticate()
+                    })?;
+                    app.finish_sudo(result);
+                }
+                if let Some(kill) = app.wants_sudo_kill.take() {
+                    with_terminal_released(&mut terminal, || {
+                        println!(
+                            "{}",
+                            tr!(
+                                "rip needs sudo to send SIG{} to {} (PID: {}).",
+                                kill.signal,
+                                kill.name,
+                                kill.pid
+                            )
+                        );
+                        app.sudo_kill(kill);
+                    })?;
+                }
+            }
+            Event::Mouse(mouse) => {
+                handle_mouse(&mut app, mouse);
+                dirty = true;
+            }
+            Event::Resize(_, _) => dirty = true,
+            _ => {}
+        }
+
+        if app.should_quit {
+            break;
+        }
+        if let Some((signal, name)) = shutdown::caught() {
+            app.message = Some(tr!("Stopped by SIG{}", name));
+            app.record(|recorder, app| recorder.message(app.message.as_ref()));
+            // After SIGHUP the terminal may be gone; leave anyway.
+            let _ = disable_raw_mode();
+            let _ = stdout().execute(DisableMouseCapture);
+            let _ = stdout().execute(LeaveAlternateScreen);
+            std::process::exit(128 + signal);
+        }
+    }
+
+    disable_raw_mode()?;
+    stdout()
+        .execute(DisableMouseCapture)?
+        .execute(LeaveAlternateScreen)?;
+    Ok(())
+}
+
+/// Leave the TUI while `f` runs, so a prompt such as sudo's can use the
+/// terminal, then come back to it.
+fn with_terminal_released<T>(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    f: impl FnOnce() -> T,
+) -> io::Result<T> {
+    disable_raw_mode()?;
+    stdout()
+        .execute(DisableMouseCapture)?
+        .execute(LeaveAlternateScreen)?;
+    let result = f();
+    enable_raw_mode()?;
+    stdout()
+        .execute(EnterAlternateScreen)?
+        .execute(EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+/// How a key is written to a session recording: the character itself, or
+/// crossterm's name for it (`Enter`, `F(12)`).
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(c) => c.to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn handle_key(app: &mut App, code: KeyCode) {
+    if app.replay.is_some() {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => app.step_replay(true),
+            KeyCode::Left | KeyCode::Char('h') => app.step_replay(false),
+            _ => {}
+        }
+        return;
+    }
+
+    if app.view == View::Heatmap {
+        match code {
+            KeyCode::Char('q') => app.should_quit = true,
+            KeyCode::Esc | KeyCode::Char('p') => app.view = View::Processes,
+            KeyCode::Char('r') => app.refresh_processes(),
+            _ => {}
+        }
+        return;
+    }
+
+    if app.view == View::Settings {
+        match code {
+            KeyCode::Char('q') => app.should_quit = true,
+            KeyCode::Esc | KeyCode::Char('S') => app.toggle_settings(),
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Right => app.change_setting(),
+            KeyCode::Char('d') | KeyCode::Delete => app.unhide_selected(),
+            _ => {}
+        }
+        return;
+    }
+
+    if let Some(wizard) = &mut app.wizard {
+        match code {
+            KeyCode::Down | KeyCode::Char('j') => wizard.move_selection(true),
+            KeyCode::Up | KeyCode::Char('k') => wizard.move_selection(false),
+            KeyCode::Enter => app.advance_wizard(),
+            KeyCode::Esc if wizard.reviewing => wizard.reviewing = false,
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.wizard = None;
+                app.message = Some(tr!("Not killed."));
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    if app.editing_filter {
+        match code {
+            KeyCode::Char(c) => app.type_filter(Some(c)),
+            KeyCode::Backspace => app.type_filter(None),
+            KeyCode::Enter => app.editing_filter = false,
+            KeyCode::Esc => app.clear_filter(),
+            KeyCode::Down => app.next(),
+            KeyCode::Up => app.previous(),
+            _ => {}
+        }
+        return;
+    }
+
+    if app.signal_picker.is_some() {
+        match code {
+            KeyCode::Down | KeyCode::Char('j') => app.move_signal_picker(true),
+            KeyCode::Up | KeyCode::Char('k') => app.move_signal_picker(false),
+            KeyCode::Enter => app.pick_signal(),
+            KeyCode::Esc | KeyCode::Char('s') | KeyCode::Char('q') => app.signal_picker = None,
+            _ => {}
+        }
+        return;
+    }
+
+    if let Some(menu) = &mut app.menu {
+        match code {
+            KeyCode::Down | KeyCode::Char('j') => menu.move_selection(true),
+            KeyCode::Up | KeyCode::Char('k') => menu.move_selection(false),
+            KeyCode::Enter => run_menu_item(app),
+            KeyCode::Esc | KeyCode::Char('q') => app.menu = None,
+            code if app.keymap.action(code) == Some(keymap::Action::Menu) => app.menu = None,
+            _ => {}
+        }
+        return;
+    }
+
+    if let Some(kill) = app.pending_sudo.take() {
+        match code {
+            KeyCode::Char('y' | 'Y') => app.wants_sudo_kill = Some(kill),
+            _ => app.message = Some(tr!("Not killed.")),
+        }
+        return;
+    }
+
+    if let Some((action, strict)) = app.pending_kill.take() {
+        match code {
+            KeyCode::Char('Y') => app.run_kill_action(action),
+            KeyCode::Char('y') if !strict => app.run_kill_action(action),
+            _ => app.message = Some(tr!("Not killed.")),
+        }
+        return;
+    }
+
+    if app.history_popup.is_some() {
+        match code {
+            KeyCode::Char('q') => app.should_quit = true,
+            KeyCode::Esc => app.history_popup = None,
+            KeyCode::Down | KeyCode::Char('j') => app.move_history(true),
+            KeyCode::Up | KeyCode::Char('k') => app.move_history(false),
+            KeyCode::Enter => app.relaunch_selected(),
+            code if app.keymap.action(code) == Some(keymap::Action::History) => {
+                app.history_popup = None
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    if app.tree.is_some() {
+        match code {
+            KeyCode::Char('q') => app.should_quit = true,
+            KeyCode::Esc => app.tree = None,
+            KeyCode::Enter => app.request_kill(KillAction::Tree),
+            code => match app.keymap.action(code) {
+                Some(keymap::Action::KillTree) => app.request_kill(KillAction::Tree),
+                Some(keymap::Action::Tree) => app.tree = None,
+                _ => {}
+            },
+        }
+        return;
+    }
+
+    if app.view == View::UnixSockets {
+        match code {
+            KeyCode::Char('q') => app.should_quit = true,
+            KeyCode::Esc | KeyCode::Char('U') => app.toggle_unix_sockets(),
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Enter | KeyCode::Char('d') => app.request_kill(KillAction::SocketOwner),
+            KeyCode::Char('r') => app.refresh_unix_sockets(),
+            _ => {}
+        }
+        return;
+    }
+
+    if let View::Rollup(by) = app.view {
+        match code {
+            KeyCode::Char('q') => app.should_quit = true,
+            KeyCode::Esc => app.toggle_rollup(by),
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Enter => app.drill_down(),
+            KeyCode::Char('r') => app.refresh_processes(),
+            KeyCode::Char('u') => app.toggle_rollup(rollup::GroupBy::User),
+            KeyCode::Char('a') => app.toggle_rollup(rollup::GroupBy::App),
+            _ => {}
+        }
+        return;
+    }
+
+    match code {
+        KeyCode::Esc if !app.marked.is_empty() => app.clear_marks(),
+        KeyCode::Esc if !app.filter.is_empty() => app.clear_filter(),
+        KeyCode::Esc if app.drill.is_some() => app.clear_drill(),
+        _ => {
+            if let Some(action) = app.keymap.action(code) {
+                run_action(app, action);
+            }
+        }
+    }
+}
+
+/// What a key bound in the keymap does in the process list.
+fn run_action(app: &mut App, action: keymap::Action) {
+    use keymap::Action;
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::Down => app.next(),
+        Action::Up => app.previous(),
+        Action::Kill => app.request_kill(KillAction::Kill),
+        Action::Menu => app.open_menu(),
+        Action::CoreKill => app.request_kill(KillAction::CoreDumpThenKill),
+        Action::StacksKill => app.request_kill(KillAction::StacksThenKill),
+        Action::Signal => app.open_signal_picker(),
+        Action::Mark => app.toggle_mark(),
+        Action::Filter => app.open_filter(),
+        Action::Sort => app.cycle_sort(),
+        Action::Protocol => app.cycle_protocol(),
+        Action::Mine => app.toggle_mine(),
+        Action::Workspace => app.toggle_workspace(),
+        Action::Graceful => app.request_kill(KillAction::Graceful),
+        Action::Refresh => app.refresh_processes(),
+        Action::Open => app.open_selected(),
+        Action::Window => app.focus_selected_window(),
+        Action::Health => app.toggle_health_checks(),
+        Action::LowPower => app.toggle_low_power(),
+        Action::Firewall => app.toggle_firewall(),
+        Action::Groups => app.toggle_process_groups(),
+        Action::Upnp => app.toggle_upnp(),
+        Action::Mdns => app.toggle_mdns(),
+        Action::Ide => app.toggle_ide_helpers(),
+        Action::Os => app.toggle_system_services(),
+        Action::Ephemeral => app.toggle_ephemeral(),
+        Action::Debug => app.debug = !app.debug,
+        Action::Diagnostics => app.save_diagnostics(),
+        Action::Sudo => app.toggle_sudo(),
+        Action::Users => app.toggle_rollup(rollup::GroupBy::User),
+        Action::Apps => app.toggle_rollup(rollup::GroupBy::App),
+        Action::Heatmap => app.view = View::Heatmap,
+        Action::Sockets => app.toggle_unix_sockets(),
+        Action::NiceUp => app.renice_selected(RENICE_STEP),
+        Action::NiceDown => app.renice_selected(-RENICE_STEP),
+        Action::Hide => app.hide_selected(false),
+        Action::HideForever => app.hide_selected(true),
+        Action::Settings => app.toggle_settings(),
+        Action::Details => app.toggle_details(),
+        Action::Tree => app.toggle_tree(),
+        Action::KillTree => app.request_kill(KillAction::Tree),
+        Action::KillGroup => app.request_kill(KillAction::Group),
+        Action::History => app.toggle_history(),
+    }
+}
+
+/// Enter or a click in the context menu: do the picked entry to the row
+/// the menu was opened on.
+fn run_menu_item(app: &mut App) {
+    use menu::Item;
+    let Some(item) = app.close_menu() else {
+        return;
+    };
+    match item {
+        Item::Reload => app.request_kill(KillAction::Signal("HUP")),
+        Item::Pause => app.request_kill(KillAction::Signal("STOP")),
+        Item::Resume => app.request_kill(KillAction::Signal("CONT")),
+        Item::Probe => app.probe_selected(),
+        Item::CopyPid => app.copy_selected(false),
+        Item::CopyAddress => app.copy_selected(true),
+        Item::Protect => app.protect_selected(true),
+        Item::Unprotect => app.protect_selected(false),
+        Item::NeverKill => app.never_kill_selected(true),
+        Item::AllowKill => app.never_kill_selected(false),
+        other => {
+            if let Some(action) = other.action() {
+                run_action(app, action);
+            }
+        }
+    }
+}
+
+/// The mouse in the process list: a click selects a row and a right click
+/// also opens its context menu; in the menu a click picks an entry, and
+/// one anywhere else closes it. The wheel moves the selection.
+fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    let busy = app.wizard.is_some()
+        || app.tree.is_some()
+        || app.history_popup.is_some()
+        || app.signal_picker.is_some()
+        || app.pending_kill.is_some()
+        || app.pending_sudo.is_some()
+        || app.editing_filter;
+    if app.view != View::Processes || app.replay.is_some() || busy {
+        return;
+    }
+    if let Some(menu) = &mut app.menu {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                match menu.item_at(mouse.column, mouse.row) {
+                    Some(index) => {
+                        menu.selected = index;
+                        run_menu_item(app);
+                    }
+                    None => app.menu = None,
+                }
+                return;
+            }
+            // Another right click moves the menu to the row it lands on.
+            MouseEventKind::Down(MouseButton::Right) => app.menu = None,
+            MouseEventKind::ScrollDown => return menu.move_selection(true),
+            MouseEventKind::ScrollUp => return menu.move_selection(false),
+            _ => return,
+        }
+    }
+    let selected = app.list_state.selected();
+    match mouse.kind {
+        MouseEventKind::Down(button) => {
+            if let Some(row) = app.row_at(mouse.column, mouse.row) {
+                app.list_state.select(Some(row));
+                if button == MouseButton::Right {
+                    app.open_menu();
+                }
+            }
+        }
+        MouseEventKind::ScrollDown if selected.is_some_and(|i| i + 1 < app.processes.len()) => {
+            app.next()
+        }
+        MouseEventKind::ScrollUp if selected.is_some_and(|i| i > 0) => app.previous(),
+        _ => {}
+    }
+}
+
+fn ui(frame: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    let mut title_text = if let Some(replay) = &app.replay {
+        tr!(
+            "rip - Replay of {} (started {}), event {}/{}",
+            replay.path.display(),
+            replay.started,
+            replay.position + 1,
+            replay.events.len()
+        )
+    } else if app.low_power {
+        tr!("rip - Kill processes on ports  [low power]")
+    } else {
+        tr!("rip - Kill processes on ports")
+    };
+    if app.safe {
+        title_text.push_str("  ");
+        title_text.push_str(&tr!("[safe mode]"));
+    }
+    if app.replay.is_none() {
+        title_text.push_str("  ");
+        title_text.push_str(&if app.elevated {
+            tr!("[backend: {} via sudo]", app.scan_stats.backend)
+        } else {
+            tr!("[backend: {}]", app.scan_stats.backend)
+        });
+    }
+    let title = Paragraph::new(title_text)
+        .style(Style::default().fg(app.theme.accent).bold())
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(title, chunks[0]);
+
+    let (list_area, detail_area) = if app.show_details && app.view == View::Processes {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[1]);
+        (halves[0], Some(halves[1]))
+    } else {
+        (chunks[1], None)
+    };
+
+    // Only rows that fit on screen are formatted, so draw time stays flat
+    // however many sockets the host has.
+    let window = app.visible_window(list_area.height.saturating_sub(2) as usize);
+    if app.health_checks && app.probes.follow(&app.processes[window.clone()]) {
+        app.invalidate_rows();
+    }
+    app.format_rows(window.clone());
+    let items: Vec<ListItem> = app.rows[window.clone()]
+        .iter()
+        .flatten()
+        .map(|line| ListItem::new(borrow_line(line)))
+        .collect();
+
+    let mut columns = vec![
+        tr!("PORT"),
+        tr!("PROTO"),
+        tr!("ADDRESS"),
+        tr!("PID"),
+        tr!("USER"),
+        tr!("NI"),
+        tr!("CONN"),
+    ];
+    if app.process_groups.is_some() {
+        columns.push(tr!("PGID"));
+        columns.push(tr!("SID"));
+    }
+    columns.push(tr!("RISK"));
+    if app.health_checks {
+        columns.push(tr!("HTTP"));
+    }
+    if app.firewall.is_some() {
+        columns.push(tr!("FW"));
+    }
+    columns.push(tr!("NAME"));
+    let sorted = match app.sort.0 {
+        SortKey::Port => 0,
+        SortKey::Protocol => 1,
+        SortKey::Pid => 3,
+        SortKey::Name => columns.len() - 1,
+    };
+    columns[sorted].push_str(if app.sort.1 { " ▼" } else { " ▲" });
+    let title = match &app.drill {
+        Some((by, key)) => tr!(
+            "Processes of {} {} ({}) - Esc: all",
+            by.label(),
+            key,
+            columns.join(" | ")
+        ),
+        None => tr!("Processes ({})", columns.join(" | ")),
+    };
+    let title = if app.sort == (SortKey::Port, false) {
+        title
+    } else {
+        tr!("{} - sorted by {}", title, sort_label(app.sort))
+    };
+    let title = if app.system_hidden == 0 {
+        title
+    } else {
+        tr!(
+            "{} - {} OS services hidden (v: show)",
+            title,
+            app.system_hidden
+        )
+    };
+    let title = if app.marked.is_empty() {
+        title
+    } else {
+        tr!("{} - {} marked", title, app.marked.len())
+    };
+    let mut narrowed = Vec::new();
+    if let Some(protocol) = app.only_protocol {
+        narrowed.push(tr!("{} only", protocol));
+    }
+    if app.only_mine {
+        narrowed.push(tr!("mine only"));
+    }
+    if let Some(dir) = &app.workspace {
+        narrowed.push(tr!("under {}", dir.display()));
+    }
+    if !app.filter.is_empty() {
+        narrowed.push(tr!("matching '{}'", app.filter));
+    }
+    let title = if narrowed.is_empty() {
+        title
+    } else {
+        tr!(
+            "{} - {} of {}: {}",
+            title,
+            app.processes.len(),
+            app.unfiltered.len(),
+            narrowed.join(", ")
+        )
+    };
+    let mut list_block = Block::default().title(title).borders(Borders::ALL);
+    if let Some(stale) = &app.stale {
+        list_block = list_block
+            .title_bottom(
+                Line::from(format!(
+                    " {} ",
+                    tr!(
+                        "STALE: scanned {}s ago; {} scans failed ({}), retrying",
+                        stale.since.elapsed().as_secs(),
+                        stale.attempts,
+                        stale.reason
+                    )
+                ))
+                .style(app.theme.alert()),
+            )
+            .border_style(Style::default().fg(app.theme.danger));
+    } else if let Some(conflict) = app.selected_conflict() {
+        list_block = list_block.title_bottom(
+            Line::from(format!(" {} ", conflict.explain()))
+                .style(Style::default().fg(app.theme.notice)),
+        );
+    }
+
+    let list = List::new(items)
+        .block(list_block)
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+
+    let mut state = ListState::default().with_selected(
+        app.list_state
+            .selected()
+            .filter(|selected| window.contains(selected))
+            .map(|selected| selected - window.start),
+    );
+    app.list_area = list_area;
+    match app.view {
+        View::Processes => frame.render_stateful_widget(list, list_area, &mut state),
+        View::Rollup(by) => render_rollup(frame, app, by, chunks[1]),
+        View::Heatmap => render_heatmap(frame, app, chunks[1]),
+        View::Settings => render_settings(frame, app, chunks[1]),
+        View::UnixSockets => render_unix_sockets(frame, app, chunks[1]),
+    }
+
+    if let Some(area) = detail_area {
+        render_details(frame, app, area);
+    }
+
+    let keys = if app.replay.is_some() {
+        tr!("←/→:Step  ↑/↓:Navigate  q:Quit")
+    } else {
+        app.keymap.help_line()
+    };
+    // A kill question is asked in its own popup instead.
+    let help_text = match &app.message {
+        _ if app.editing_filter => format!(
+            "/{} | {}",
+            app.filter,
+            tr!("Filter by name, port or PID  ↑/↓:Navigate  Enter:Keep  Esc:Clear")
+        ),
+        Some(msg) if app.pending_kill.is_none() && app.pending_sudo.is_none() => {
+            format!("{} | {}", msg, keys)
+        }
+        _ => keys,
+    };
+
+    let mut status_block = Block::default().borders(Borders::ALL);
+    if !app.refreshed_at.is_empty() && app.replay.is_none() {
+        let refreshed = match app.auto_refresh_interval() {
+            Some(interval) => tr!(
+                " Refreshed {}, every {}s ",
+                app.refreshed_at,
+                interval.as_secs()
+            ),
+            None => tr!(" Refreshed {} ", app.refreshed_at),
+        };
+        status_block = status_block.title_top(Line::raw(refreshed).right_aligned());
+    }
+    let status = Paragraph::new(help_text)
+        .style(Style::default().fg(app.theme.status))
+        .block(status_block);
+    frame.render_widget(status, chunks[2]);
+    if app.editing_filter {
+        let typed = 1 + app.filter.chars().count() as u16;
+        frame.set_cursor_position((chunks[2].x + 1 + typed, chunks[2].y + 1));
+    }
+
+    if app.debug {
+        render_debug_overlay(frame, app, chunks[1]);
+    }
+    if let Some(index) = app.signal_picker {
+        render_signal_picker(frame, &app.theme, index, chunks[1]);
+    }
+    if let Some(wizard) = &app.wizard {
+        render_wizard(frame, &app.theme, wizard, chunks[1]);
+    }
+    if let Some(tree) = &app.tree {
+        render_tree(frame, app, tree, chunks[1]);
+    }
+    if let Some(selected) = app.history_popup {
+        render_history(frame, app, selected, chunks[1]);
+    }
+    if app.menu.is_some() {
+        render_menu(frame, app, chunks[1]);
+    }
+    if (app.pending_kill.is_some() || app.pending_sudo.is_some())
+        && let Some(question) = &app.message
+    {
+        render_kill_confirmation(frame, &app.theme, question, chunks[1]);
+    }
+}
+
+/// The kill question, centred over the list so a stray Enter cannot go
+/// unnoticed.
+fn render_kill_confirmation(frame: &mut Frame, theme: &theme::Theme, question: &str, area: Rect) {
+    let width = 64.min(area.width);
+    let inner = usize::from(width.saturating_sub(2)).max(1);
+    // One spare line for words pushed down by wrapping.
+    let lines = question.chars().count().div_ceil(inner) as u16 + 3;
+    let height = (lines + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let text = vec![
+        Line::from(question.to_string()).bold(),
+        Line::from(""),
+        Line::from(tr!("Any other key cancels")).fg(theme.muted),
+    ];
+    let dialog = Paragraph::new(text).wrap(Wrap { trim: true }).block(
+        Block::default()
+            .title(tr!("Confirm kill"))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.danger)),
+    );
+    frame.render_widget(Clear, popup);
+    frame.render_widget(dialog, popup);
+}
+
+/// The kill wizard: first the strategies with what each would signal, then
+/// the chosen one's steps, in order, for confirmation.
+fn render_wizard(frame: &mut Frame, theme: &theme::Theme, wizard: &wizard::Wizard, area: Rect) {
+    let (title, items, selected) = if wizard.reviewing {
+        let (strategy, steps) = wizard.chosen();
+        let items: Vec<ListItem> = steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| ListItem::new(format!("{}. {}", i + 1, step.describe())))
+            .collect();
+        let title = tr!("{} - Enter: go ahead, Esc: back", strategy.label());
+        (title, items, None)
+    } else {
+        let items: Vec<ListItem> = wizard
+            .choices
+            .iter()
+            .map(|(strategy, steps)| {
+                let summary = match steps.as_slice() {
+                    [step] => step.describe(),
+                    steps => tr!("{} processes, children first", steps.len()),
+                };
+                ListItem::new(format!("{} - {}", strategy.label(), summary))
+            })
+            .collect();
+        let title = tr!("Free :{} - Enter: review steps, Esc: cancel", wizard.port);
+        (title, items, Some(wizard.selected))
+    };
+
+    let width = 72.min(area.width);
+    let height = (items.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(theme.selection())
+        .highlight_symbol(">> ");
+    let mut state = ListState::default();
+    state.select(selected);
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+/// The `P` popup: the selected process's ancestors, dimmed, then it and
+/// its descendants, each with the ports it listens on.
+fn render_tree(frame: &mut Frame, app: &App, tree: &tree::Tree, area: Rect) {
+    let items: Vec<ListItem> = tree
+        .nodes
+        .iter()
+        .map(|node| {
+            let mut text = format!("{}{} ({})", "  ".repeat(node.depth), node.name, node.pid);
+            if !node.ports.is_empty() {
+                let ports: Vec<String> = node.ports.iter().map(|p| format!(":{}", p)).collect();
+                text.push_str(&format!("  {}", ports.join(" ")));
+            }
+            let item = ListItem::new(text);
+            if node.ancestor {
+                item.fg(app.theme.muted)
+            } else {
+                item
+            }
+        })
+        .collect();
+    let selected = tree.nodes.iter().position(|n| n.pid == tree.pid);
+
+    let key = app
+        .keymap
+        .key_for(keymap::Action::KillTree)
+        .unwrap_or_else(|| "Enter".to_string());
+    let title = tr!(
+        "Process tree of {} - {}: kill it and what it started, Esc: close",
+        tree.name,
+        key
+    );
+    let width = 72.min(area.width);
+    let height = (items.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+    let mut state = ListState::default();
+    state.select(selected);
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+/// The `H` popup: what was killed this session, most recent first, with
+/// the command and directory a relaunch would use.
+fn render_history(frame: &mut Frame, app: &App, selected: usize, area: Rect) {
+    let items: Vec<ListItem> = app
+        .history
+        .iter()
+        .rev()
+        .map(|victim| {
+            let port = victim.port.map(|p| format!(":{}", p)).unwrap_or_default();
+            let mut lines = vec![Line::from(format!(
+                "{}  {:<6} {} (PID: {})",
+                victim.at, port, victim.name, victim.pid
+            ))];
+            let command = if victim.argv.is_empty() {
+                tr!("command line unknown")
+            } else {
+                victim.argv.join(" ")
+            };
+            lines.push(Line::from(format!("    {}", command)).fg(app.theme.muted));
+            let mut place = victim
+                .cwd
+                .as_ref()
+                .map(|cwd| tr!("in {}", cwd.display()))
+                .unwrap_or_default();
+            if let Some(pid) = victim.relaunched {
+                place.push_str(&tr!(" - relaunched as PID {}", pid));
+            }
+            if !place.is_empty() {
+                lines.push(Line::from(format!("    {}", place)).fg(app.theme.muted));
+            }
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let width = 96.min(area.width);
+    let height = area.height.min(20);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(tr!("Killed this session - Enter: relaunch, Esc: close"))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+/// The `s` popup: each signal with what it asks of the process.
+fn render_signal_picker(frame: &mut Frame, theme: &theme::Theme, selected: usize, area: Rect) {
+    let items: Vec<ListItem> = config::SIGNALS
+        .iter()
+        .map(|signal| {
+            let meaning = match *signal {
+                "TERM" => tr!("ask it to exit, letting it clean up"),
+                "INT" => tr!("interrupt, like Ctrl-C"),
+                "HUP" => tr!("hang up; many servers reload instead"),
+                _ => tr!("end it at once, no cleanup"),
+            };
+            ListItem::new(format!("SIG{:<5} {}", signal, meaning))
+        })
+        .collect();
+
+    let width = 52.min(area.width);
+    let height = (items.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(tr!("Send signal - Enter: send, Esc: cancel"))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(theme.selection())
+        .highlight_symbol(">> ");
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+/// The context menu, just below its row (above it near the bottom of
+/// `area`), with the key for each entry that has one.
+fn render_menu(frame: &mut Frame, app: &mut App, area: Rect) {
+    let Some(menu) = &app.menu else {
+        return;
+    };
+    let entries: Vec<(String, String)> = menu
+        .items
+        .iter()
+        .map(|item| {
+            let key = item.action().and_then(|action| app.keymap.key_for(action));
+            (item.label(&menu.process), key.unwrap_or_default())
+        })
+        .collect();
+    let label_width = entries
+        .iter()
+        .map(|(l, _)| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let key_width = entries
+        .iter()
+        .map(|(_, k)| k.chars().count())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|(label, key)| ListItem::new(format!("{:<label_width$}  {:>key_width$}", label, key)))
+        .collect();
+
+    // Borders, the highlight symbol and the gap before the keys.
+    let width = ((label_width + key_width + 7) as u16).min(area.width);
+    let height = (items.len() as u16 + 2).min(area.height);
+    let row = app.list_state.selected().unwrap_or(0) - app.list_state.offset();
+    let row_y = app.list_area.y + 1 + row as u16;
+    let y = if row_y + 1 + height <= area.bottom() {
+        row_y + 1
+    } else {
+        row_y.saturating_sub(height).max(area.y)
+    };
+    let popup = Rect {
+        x: (app.list_area.x + 6).min(area.right().saturating_sub(width)),
+        y,
+        width,
+        height,
+    };
+    let title = format!("{} (PID: {})", menu.process.name, menu.process.pid_label());
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+    let mut state = ListState::default().with_selected(Some(menu.selected));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+    if let Some(menu) = &mut app.menu {
+        menu.area = popup;
+    }
+}
+
+fn render_details(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let mut lines = Vec::new();
+    for section in app.selected_details() {
+        if !lines.is_empty() {
+            lines.push(Line::raw(""));
+        }
+        lines.push(Line::styled(
+            section.title.clone(),
+            Style::default().fg(theme.accent).bold(),
+        ));
+        for (label, value) in &section.rows {
+            if label.is_empty() {
+                lines.push(Line::raw(value.clone()));
+                continue;
+            }
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}: ", label), Style::default().fg(theme.muted)),
+                Span::raw(value.clone()),
+            ]));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::raw(tr!("Nothing selected.")));
+    }
+    let pane = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(tr!("Details - Tab: hide"))
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(pane, area);
+}
+
+fn render_rollup(frame: &mut Frame, app: &mut App, by: rollup::GroupBy, area: Rect) {
+    let items: Vec<ListItem> = app
+        .groups
+        .iter()
+        .map(|group| {
+            let ports: Vec<String> = group.ports.iter().map(|p| format!(":{}", p)).collect();
+            ListItem::new(format!(
+                "{:<16} {:>9} {:>9}  {}",
+                group.key,
+                group.listeners,
+                group.processes,
+                ports.join(" ")
+            ))
+        })
+        .collect();
+    let columns = [
+        by.column(),
+        tr!("LISTENERS"),
+        tr!("PROCESSES"),
+        tr!("PORTS"),
+    ];
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(tr!(
+                    "Listeners by {} ({}) - Enter: show rows",
+                    by.label(),
+                    columns.join(" | ")
+                ))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(list, area, &mut app.group_state);
+}
+
+fn render_unix_sockets(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .unix_sockets
+        .iter()
+        .map(|socket| {
+            let owner = match socket.owners.as_slice() {
+                [] => "?".to_string(),
+                [(pid, name)] => format!("{} ({})", name, pid),
+                [(pid, name), rest @ ..] => format!("{} ({}) +{}", name, pid, rest.len()),
+            };
+            let path = Span::styled(
+                socket.path.clone(),
+                if socket.is_abstract() {
+                    Style::default().fg(app.theme.notice)
+                } else {
+                    Style::default()
+                },
+            );
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<9} {:<24} ", socket.kind, owner)),
+                path,
+            ]))
+        })
+        .collect();
+    let columns = [tr!("TYPE"), tr!("OWNER"), tr!("PATH")];
+    let title = if cfg!(windows) {
+        tr!(
+            "Named pipes ({}) - Enter: kill owner, Esc: back",
+            columns.join(" | ")
+        )
+    } else {
+        tr!(
+            "Unix sockets and named pipes ({}) - @: abstract, Enter: kill owner, Esc: back",
+            columns.join(" | ")
+        )
+    };
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(list, area, &mut app.unix_state);
+}
+
+/// "every 5s", or "off".
+/// "name, descending"
+fn sort_label((key, descending): (SortKey, bool)) -> String {
+    if descending {
+        tr!("{}, descending", key.label())
+    } else {
+        tr!("{}, ascending", key.label())
+    }
+}
+
+fn system_services_label(hidden: bool) -> String {
+    if hidden {
+        tr!("OS services (rapportd, systemd-resolved, svchost, ...): hidden")
+    } else {
+        tr!("OS services (rapportd, systemd-resolved, svchost, ...): shown")
+    }
+}
+
+fn theme_label(config: &config::Config) -> String {
+    if !config.palette.is_empty() {
+        tr!("Theme: {} with [theme] colours", config.theme)
+    } else {
+        tr!("Theme: {}", config.theme)
+    }
+}
+
+/// The config's theme, or monochrome under `NO_COLOR`.
+fn theme_for(config: &config::Config) -> theme::Theme {
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        theme::Theme::MONOCHROME
+    } else {
+        config.theme()
+    }
+}
+
+fn refresh_label(secs: u64) -> String {
+    if secs == 0 {
+        tr!("Auto-refresh: off")
+    } else {
+        tr!("Auto-refresh: every {}s", secs)
+    }
+}
+
+fn render_settings(frame: &mut Frame, app: &mut App, area: Rect) {
+    let mut items = vec![
+        ListItem::new(tr!("Kill signal: SIG{}", app.config.signal)),
+        ListItem::new(if app.config.confirm_kill {
+            tr!("Asking before each kill")
+        } else {
+            tr!("Killing without asking")
+        }),
+        ListItem::new(tr!(
+            "Graceful kill: SIGKILL after {}s",
+            app.config.grace_secs
+        )),
+        ListItem::new(refresh_label(app.config.refresh_secs)),
+        ListItem::new(system_services_label(app.config.hide_system)),
+        ListItem::new(theme_label(&app.config)),
+    ];
+    if app.config.ignore.is_empty() {
+        items.push(ListItem::new(tr!(
+            "Nothing hidden. Press x on a row to hide its process, X to hide its port."
+        )));
+    }
+    items.extend(
+        app.config
+            .ignore
+            .iter()
+            .map(|entry| ListItem::new(tr!("Hidden: {}", entry))),
+    );
+    let title = match config::path() {
+        Some(path) => tr!(
+            "Settings in {} - Enter: change/show again, d: show again, Esc: back",
+            path.display()
+        ),
+        None => tr!("Settings - Enter: change/show again, d: show again, Esc: back"),
+    };
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(app.theme.selection())
+        .highlight_symbol(">> ");
+    frame.render_stateful_widget(list, area, &mut app.settings_state);
+}
+
+/// One strip per configured range; each cell covers an equal slice of the
+/// range and is coloured by how many occupied ports fall into it.
+fn render_heatmap(frame: &mut Frame, app: &App, area: Rect) {
+    let cells = area.width.saturating_sub(2) as usize;
+    let mut lines = Vec::new();
+    for range in &app.heatmap_ranges {
+        let counts = heatmap::buckets(&app.processes, range, cells);
+        let span = usize::from(range.end() - range.start()) + 1;
+        lines.push(Line::from(tr!(
+            "{}-{}: {} occupied, {} ports per cell",
+            range.start(),
+            range.end(),
+            counts.iter().sum::<usize>(),
+            span.div_ceil(counts.len())
+        )));
+        lines.push(Line::from(
+            counts
+                .iter()
+                .map(|count| match count {
+                    0 => Span::styled("·", Style::default().fg(app.theme.faint)),
+                    1 => Span::styled("█", Style::default().fg(app.theme.good)),
+                    2..=3 => Span::styled("█", Style::default().fg(app.theme.warning)),
+                    _ => Span::styled("█", Style::default().fg(app.theme.danger)),
+                })
+                .collect::<Vec<_>>(),
+        ));
+        lines.push(Line::default());
+    }
+    lines.push(Line::from(vec![
+        Span::styled("█", Style::default().fg(app.theme.good)),
+        Span::raw(" 1  "),
+        Span::styled("█", Style::default().fg(app.theme.warning)),
+        Span::raw(" 2-3  "),
+        Span::styled("█", Style::default().fg(app.theme.danger)),
+        Span::raw(tr!(" 4+ occupied ports per cell")),
+    ]));
+
+    let heatmap = Paragraph::new(lines).block(
+        Block::default()
+            .title(tr!("Port heatmap - p/Esc: list"))
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(heatmap, area);
+}
+
+fn render_debug_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let ms = |d: Duration| format!("{:.1} ms", d.as_secs_f64() * 1000.0);
+    let stats = &app.scan_stats;
+    let lines = vec![
+        format!("backend  {}", stats.backend),
+        format!("refresh  {}", ms(app.refresh_time)),
+        format!("  exec   {}", ms(stats.command)),
+        format!("  parse  {}", ms(stats.parse)),
+        format!("  enrich {}", ms(stats.enrich)),
+        format!("rows     {}", app.processes.len()),
+        format!("render   {}", ms(app.render_time)),
+        format!("fps      {}", app.frame_times.len()),
+    ];
+
+    let width = 26.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect {
+        x: area.right().saturating_sub(width + 1),
+        y: area.y + 1,
+        width,
+        height,
+    };
+
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    let widget = Paragraph::new(text)
+        .style(Style::default().fg(app.theme.good))
+        .block(Block::default().title("debug").borders(Borders::ALL));
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(widget, overlay);
+}
+
+fn process_row(app: &App, p: &PortProcess) -> Line<'static> {
+    let conflicted = app.conflict_for(p).is_some();
+    let marked = app.marked.contains(&(p.pid, p.port));
+    let marker = if conflicted { "!" } else { " " };
+    // Loopback-only listeners in green; the rest are reachable from the
+    // network.
+    let theme = &app.theme;
+    let address_style = if conflict::is_loopback(&p.address) {
+        Style::default().fg(theme.good)
+    } else {
+        Style::default()
+    };
+    let mut spans = vec![
+        Span::raw(format!(
+            "{}{}:{:<6} {:4} ",
+            if marked { "*" } else { " " },
+            marker,
+            p.port,
+            p.protocol
+        )),
+        Span::styled(format!("{:<15}", p.address), address_style),
+        Span::raw(format!(
+            " {:>6} {:<8} {:>3} {:>4} ",
+            p.pid_label(),
+            user_cell(p.user.as_deref()),
+            p.nice.map(|n| n.to_string()).unwrap_or_default(),
+            p.connections.map(|n| n.to_string()).unwrap_or_default()
+        )),
+    ];
+    if let Some(groups) = &app.process_groups {
+        spans.push(match groups.get(&p.pid) {
+            Some(group) => Span::styled(
+                format!("{:>6} {:>6} ", group.pgid, group.sid),
+                if group.leader_gone {
+                    Style::default().fg(theme.danger)
+                } else {
+                    Style::default()
+                },
+            ),
+            None => Span::raw(format!("{:>6} {:>6} ", "?", "?")),
+        });
+    }
+    spans.push(risk_span(theme, app.risks.get(&p.pid)));
+    if app.health_checks {
+        spans.push(health_span(theme, app.probes.health((p.pid, p.port))));
+    }
+    if let Some(ruleset) = &app.firewall {
+        let verdict = match ruleset {
+            Ok(rules) => rules.verdict(p),
+            Err(_) => firewall::Verdict::Unknown,
+        };
+        spans.push(verdict_span(theme, verdict));
+    }
+    spans.push(Span::raw(p.name.clone()));
+    if let Some(context) = &p.context {
+        spans.push(Span::styled(
+            format!(" ({})", context),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    if let Some(service) = app.advertised(p) {
+        spans.push(Span::styled(
+            format!(" \"{}\"", service.name),
+            Style::default().fg(theme.accent),
+        ));
+    }
+    if let Some(external) = app
+        .upnp
+        .as_deref()
+        .and_then(|mappings| app.forwarded_port(p, mappings))
+    {
+        spans.push(Span::styled(
+            tr!(" [internet :{}]", external),
+            Style::default().fg(theme.danger).bold(),
+        ));
+    }
+    if let Some(tag) = p.tag {
+        spans.push(Span::styled(
+            format!(" [{}]", tag),
+            Style::default().fg(theme.info),
+        ));
+    }
+    let escalate = app.needs_escalation(p);
+    if escalate {
+        spans.push(Span::styled(
+            tr!(" [sudo: {}]", p.user.as_deref().unwrap_or_default()),
+            Style::default().fg(theme.faint),
+        ));
+    }
+    let line = Line::from(spans);
+    if marked {
+        return line.style(Style::default().fg(theme.warning).bold());
+    }
+    match (conflicted, escalate) {
+        (true, _) => line.style(Style::default().fg(theme.notice)),
+        (false, true) => line.style(Style::default().add_modifier(Modifier::DIM)),
+        (false, false) => line,
+    }
+}
+
+/// The kill-risk score, coloured by how strict the kill confirmation is.
+/// A user name cut to eight characters the way `ps` does, with `+` marking
+/// the cut; `?` when the backend could not tell.
+fn user_cell(user: Option<&str>) -> String {
+    let user = user.unwrap_or("?");
+    if user.chars().count() > 8 {
+        format!("{}+", user.chars().take(7).collect::<String>())
+    } else {
+        user.to_string()
+    }
+}
+
+fn risk_span(theme: &theme::Theme, risk: Option<&risk::Risk>) -> Span<'static> {
+    let Some(risk) = risk.filter(|r| r.score > 0) else {
+        return Span::raw("   ");
+    };
+    let color = match risk.level() {
+        risk::Level::Low => theme.muted,
+        risk::Level::Medium => theme.warning,
+        risk::Level::High => theme.danger,
+    };
+    Span::styled(format!("{:>2} ", risk.score), Style::default().fg(color))
+}
+
+/// A view of a cached row that borrows its text instead of copying it.
+fn borrow_line<'a>(line: &'a Line<'static>) -> Line<'a> {
+    Line::from(
+        line.spans
+            .iter()
+            .map(|span| Span::styled(span.content.as_ref(), span.style))
+            .collect::<Vec<_>>(),
+    )
+    .style(line.style)
+}
+
+fn health_span(theme: &theme::Theme, health: Option<&Health>) -> Span<'static> {
+    let label = format!("{:<8} ", health.map(Health::label).unwrap_or_default());
+    let color = match health {
+        Some(Health::Status(code)) if *code < 400 => theme.good,
+        Some(Health::Status(_)) | Some(Health::Timeout) | Some(Health::Unreachable) => theme.danger,
+        _ => theme.muted,
+    };
+    Span::styled(label, Style::default().fg(color))
+}
+
+fn verdict_span(theme: &theme::Theme, verdict: firewall::Verdict) -> Span<'static> {
+    let color = match verdict {
+        firewall::Verdict::Allowed => theme.danger,
+        firewall::Verdict::Blocked | firewall::Verdict::LocalOnly => theme.good,
+        firewall::Verdict::Unknown => theme.muted,
+    };
+    Span::styled(
+        format!("{:<6} ", verdict.label()),
+        Style::default().fg(color),
+    )
 }
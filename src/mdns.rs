@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::time::{Duration, Instant};
+
+const MDNS_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(224, 0, 0, 251), 5353);
+const SERVICE_ENUMERATION: &str = "_services._dns-sd._udp.local";
+const ROUND_TIMEOUT: Duration = Duration::from_millis(800);
+
+const TYPE_PTR: u16 = 12;
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+
+/// A DNS-SD service instance advertised over mDNS.
+#[derive(Clone, Debug)]
+pub struct Service {
+    /// Friendly instance name, e.g. "My App".
+    pub name: String,
+    /// Service type, e.g. "_http._tcp".
+    pub kind: String,
+    pub port: u16,
+    /// Address of the host that answered for this service.
+    pub host: IpAddr,
+}
+
+/// Browse every advertised service type and resolve instances to ports.
+///
+/// Queries are sent from an ephemeral port, so responders answer with
+/// unicast "legacy" responses straight back to us (RFC 6762 section 6.7).
+pub fn browse() -> Result<Vec<Service>, String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|e| e.to_string())?;
+
+    let types: Vec<String> = query(&socket, &[(SERVICE_ENUMERATION, TYPE_PTR)])?
+        .into_iter()
+        .filter_map(|(_, record)| match record {
+            Record::Ptr { target, .. } => Some(target),
+            _ => None,
+        })
+        .collect();
+    if types.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let questions: Vec<(&str, u16)> = types.iter().map(|t| (t.as_str(), TYPE_PTR)).collect();
+    let mut records = query(&socket, &questions)?;
+
+    // Responders usually include SRV records as additional answers; ask
+    // explicitly for instances that came back without one.
+    let resolved: Vec<String> = records
+        .iter()
+        .filter_map(|(_, r)| match r {
+            Record::Srv { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let unresolved: Vec<String> = records
+        .iter()
+        .filter_map(|(_, r)| match r {
+            Record::Ptr { target, .. } if !resolved.contains(target) => Some(target.clone()),
+            _ => None,
+        })
+        .collect();
+    if !unresolved.is_empty() {
+        let questions: Vec<(&str, u16)> =
+            unresolved.iter().map(|n| (n.as_str(), TYPE_SRV)).collect();
+        records.extend(query(&socket, &questions)?);
+    }
+
+    let mut services: HashMap<(String, IpAddr), Service> = HashMap::new();
+    for (host, record) in records {
+        if let Record::Srv { name, port } = record {
+            let (instance, kind) = split_instance(&name);
+            services.insert(
+                (name.clone(), host),
+                Service {
+                    name: instance,
+                    kind,
+                    port,
+                    host,
+                },
+            );
+        }
+    }
+    Ok(services.into_values().collect())
+}
+
+/// "My App._http._tcp.local" -> ("My App", "_http._tcp")
+fn split_instance(name: &str) -> (String, String) {
+    let name = name.trim_end_matches(".local");
+    match name.find("._") {
+        Some(i) => (name[..i].to_string(), name[i + 1..].to_string()),
+        None => (name.to_string(), String::new()),
+    }
+}
+
+#[derive(Debug)]
+enum Record {
+    Ptr { target: String },
+    Srv { name: String, port: u16 },
+}
+
+fn query(socket: &UdpSocket, questions: &[(&str, u16)]) -> Result<Vec<(IpAddr, Record)>, String> {
+    let mut packet = vec![0, 0, 0, 0];
+    packet.extend_from_slice(&(questions.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+    for (name, qtype) in questions {
+        for label in name.split('.') {
+            packet.push(label.len() as u8);
+            packet.extend_from_slice(label.as_bytes());
+        }
+        packet.push(0);
+        packet.extend_from_slice(&qtype.to_be_bytes());
+        packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    }
+    socket
+        .send_to(&packet, MDNS_ADDR)
+        .map_err(|e| e.to_string())?;
+
+    let mut records = Vec::new();
+    let deadline = Instant::now() + ROUND_TIMEOUT;
+    let mut buf = [0u8; 9000];
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        socket
+            .set_read_timeout(Some(remaining.max(Duration::from_millis(1))))
+            .map_err(|e| e.to_string())?;
+        let Ok((n, from)) = socket.recv_from(&mut buf) else {
+            break;
+        };
+        for record in parse_response(&buf[..n]).unwrap_or_default() {
+            records.push((from.ip(), record));
+        }
+    }
+    Ok(records)
+}
+
+fn parse_response(packet: &[u8]) -> Option<Vec<Record>> {
+    let u16_at = |i: usize| Some(u16::from_be_bytes([*packet.get(i)?, *packet.get(i + 1)?]));
+
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)? as usize + u16_at(8)? as usize + u16_at(10)? as usize;
+    let mut pos = 12;
+
+    for _ in 0..questions {
+        pos = read_name(packet, pos)?.1 + 4;
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        let (name, next) = read_name(packet, pos)?;
+        let rtype = u16_at(next)?;
+        let rdlength = u16_at(next + 8)? as usize;
+        let rdata = next + 10;
+        match rtype {
+            TYPE_PTR => records.push(Record::Ptr {
+                target: read_name(packet, rdata)?.0,
+            }),
+            TYPE_SRV => records.push(Record::Srv {
+                name,
+                port: u16_at(rdata + 4)?,
+            }),
+            _ => {}
+        }
+        pos = rdata + rdlength;
+    }
+    Some(records)
+}
+
+/// Read a possibly compressed domain name, returning it and the offset just
+/// past it in the original position.
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *packet.get(pos)? as usize;
+        if len == 0 {
+            end.get_or_insert(pos + 1);
+            break;
+        }
+        if len & 0xC0 == 0xC0 {
+            end.get_or_insert(pos + 2);
+            pos = ((len & 0x3F) << 8) | *packet.get(pos + 1)? as usize;
+            jumps += 1;
+            if jumps > 32 {
+                return None;
+            }
+            continue;
+        }
+        let label = packet.get(pos + 1..pos + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+
+    Some((labels.join("."), end?))
+}
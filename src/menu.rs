@@ -0,0 +1,186 @@
+use ratatui::layout::Rect;
+
+use crate::keymap::Action;
+use crate::{PortProcess, UNKNOWN_PID};
+
+/// Something the context menu can do to the selected row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item {
+    Kill,
+    Graceful,
+    Signal,
+    /// SIGHUP, which most servers take as "reload your config".
+    Reload,
+    Pause,
+    Resume,
+    CoreKill,
+    StacksKill,
+    /// Kill it and everything it started.
+    KillTree,
+    /// Signal its whole process group.
+    KillGroup,
+    Tree,
+    Open,
+    Probe,
+    Window,
+    CopyPid,
+    CopyAddress,
+    Details,
+    Mark,
+    Hide,
+    HidePort,
+    Protect,
+    Unprotect,
+    /// Put it on the config's `never_kill` list.
+    NeverKill,
+    /// Take it off the `never_kill` list.
+    AllowKill,
+    /// Not about the row: the way into the settings view for those who
+    /// look for it here rather than behind a key.
+    Settings,
+}
+
+impl Item {
+    pub fn label(self, process: &PortProcess) -> String {
+        match self {
+            Item::Kill => tr!("Kill"),
+            Item::Graceful => tr!("Stop gracefully (TERM, then KILL)"),
+            Item::Signal => tr!("Send signal..."),
+            Item::Reload => tr!("Reload (SIGHUP)"),
+            Item::Pause => tr!("Pause (SIGSTOP)"),
+            Item::Resume => tr!("Resume (SIGCONT)"),
+            Item::CoreKill => tr!("Dump core, then kill"),
+            Item::StacksKill => tr!("Save stacks, then kill"),
+            Item::KillTree => tr!("Kill with everything it started"),
+            Item::KillGroup => tr!("Kill its process group"),
+            Item::Tree => tr!("Show process tree"),
+            Item::Open => tr!("Open in browser"),
+            Item::Probe => tr!("Check HTTP health"),
+            Item::Window => tr!("Focus its window"),
+            Item::CopyPid => tr!("Copy PID"),
+            Item::CopyAddress => tr!("Copy address"),
+            Item::Details => tr!("Details"),
+            Item::Mark => tr!("Mark for a batch kill"),
+            Item::Hide => tr!("Hide {}", process.name),
+            Item::HidePort => tr!("Hide :{}", process.port),
+            Item::Protect => tr!("Protect (kills need a capital Y)"),
+            Item::Unprotect => tr!("Stop protecting"),
+            Item::NeverKill => tr!("Never kill {}", process.name),
+            Item::AllowKill => tr!("Allow killing {} again", process.name),
+            Item::Settings => tr!("Settings..."),
+        }
+    }
+
+    /// The process list action behind the item, whose key the menu shows
+    /// next to it.
+    pub fn action(self) -> Option<Action> {
+        Some(match self {
+            Item::Kill => Action::Kill,
+            Item::Graceful => Action::Graceful,
+            Item::Signal => Action::Signal,
+            Item::CoreKill => Action::CoreKill,
+            Item::StacksKill => Action::StacksKill,
+            Item::KillTree => Action::KillTree,
+            Item::KillGroup => Action::KillGroup,
+            Item::Tree => Action::Tree,
+            Item::Open => Action::Open,
+            Item::Window => Action::Window,
+            Item::Details => Action::Details,
+            Item::Mark => Action::Mark,
+            Item::Hide => Action::Hide,
+            Item::HidePort => Action::HideForever,
+            Item::Settings => Action::Settings,
+            _ => return None,
+        })
+    }
+}
+
+/// The context menu for one row: what applies to it, which entry is
+/// highlighted, and where it was last drawn (for mouse clicks).
+pub struct Menu {
+    pub process: PortProcess,
+    pub items: Vec<Item>,
+    pub selected: usize,
+    pub area: Rect,
+}
+
+impl Menu {
+    /// `stopped`: the process is paused (SIGSTOP). `protection`: whether
+    /// the config's protect list names it (`Some(true)`), it can be added
+    /// (`Some(false)`) or it is protected some other way (`None`).
+    /// `never_kill`: whether the config's never_kill list names it.
+    pub fn new(
+        process: &PortProcess,
+        stopped: bool,
+        protection: Option<bool>,
+        never_kill: bool,
+    ) -> Self {
+        let known = process.pid != UNKNOWN_PID;
+        let tcp = process.protocol == "TCP";
+        let mut items = Vec::new();
+        if known {
+            items.extend([Item::Kill, Item::Graceful, Item::Signal, Item::Reload]);
+            if cfg!(unix) {
+                items.push(if stopped { Item::Resume } else { Item::Pause });
+            }
+            items.extend([Item::CoreKill, Item::StacksKill, Item::KillTree]);
+            if cfg!(unix) {
+                items.push(Item::KillGroup);
+            }
+            items.push(Item::Tree);
+        }
+        if tcp {
+            items.extend([Item::Open, Item::Probe]);
+        }
+        if known {
+            items.extend([Item::Window, Item::CopyPid]);
+        }
+        items.extend([Item::CopyAddress, Item::Details]);
+        if known {
+            items.push(Item::Mark);
+        }
+        items.extend([Item::Hide, Item::HidePort]);
+        match protection {
+            Some(true) => items.push(Item::Unprotect),
+            Some(false) if known => items.push(Item::Protect),
+            _ => {}
+        }
+        if never_kill {
+            items.push(Item::AllowKill);
+        } else if known {
+            items.push(Item::NeverKill);
+        }
+        items.push(Item::Settings);
+        Menu {
+            process: process.clone(),
+            items,
+            selected: 0,
+            area: Rect::default(),
+        }
+    }
+
+    pub fn chosen(&self) -> Item {
+        self.items[self.selected]
+    }
+
+    /// Move the highlight, wrapping around.
+    pub fn move_selection(&mut self, down: bool) {
+        let count = self.items.len();
+        self.selected = if down {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    /// The item drawn at `(x, y)` on screen, if any.
+    pub fn item_at(&self, x: u16, y: u16) -> Option<usize> {
+        let inside = x > self.area.x
+            && x + 1 < self.area.right()
+            && y > self.area.y
+            && y + 1 < self.area.bottom();
+        inside
+            .then(|| (y - self.area.y - 1) as usize)
+            .filter(|i| *i < self.items.len())
+    }
+}
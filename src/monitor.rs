@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::{Alert, MonitorOptions, ReportOptions};
+use crate::clock::{format_utc, utc_timestamp};
+use crate::json::{self, Value};
+use crate::{PortProcess, conflict, get_port_processes, state};
+
+/// Who held a port when it was seen taken.
+#[derive(Clone, Debug, PartialEq)]
+struct Owner {
+    pid: u32,
+    name: String,
+    protocol: String,
+}
+
+/// A line of the timeline file.
+enum Event {
+    /// The monitor (re)started; whatever was open before ended unseen.
+    Start,
+    Taken(u16, Owner),
+    Freed(u16),
+}
+
+/// The default timeline file, next to the state file.
+pub fn default_path() -> Option<PathBuf> {
+    Some(state::path()?.with_file_name("timeline"))
+}
+
+/// Poll the listener list until interrupted and append every change in
+/// which process holds which port to the timeline file, as JSON lines of
+/// `[unix_seconds, kind, data]`.
+pub fn run(options: MonitorOptions) -> io::Result<()> {
+    let path = timeline_path(options.file)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    log(&tr!(
+        "monitoring all ports (checking every {}, Ctrl-C to stop), writing to {}",
+        format!("{:?}", options.interval),
+        path.display()
+    ));
+    if let Some(alert) = &options.alert {
+        log(&tr!(
+            "alerting when more than {} ports are listened on",
+            alert.over
+        ));
+    }
+    if options.notify_new {
+        log(&tr!(
+            "notifying when a port starts being listened on from other machines"
+        ));
+    }
+    write(&mut file, "start", Value::Null)?;
+
+    let mut held: BTreeMap<u16, Owner> = BTreeMap::new();
+    let mut alerting = false;
+    // `None` until the first scan, whose listeners are not new.
+    let mut was_exposed: Option<BTreeSet<u16>> = None;
+    loop {
+        let processes = get_port_processes();
+        if let Some(alert) = &options.alert {
+            alerting = check(alert, &processes, alerting);
+        }
+        if options.notify_new {
+            was_exposed = Some(notify_exposed(&processes, was_exposed.as_ref()));
+        }
+        let now = owners(&processes);
+        for (port, owner) in &now {
+            if held.get(port) == Some(owner) {
+                continue;
+            }
+            log(&tr!(
+                ":{} taken by {} (PID: {})",
+                port,
+                owner.name,
+                owner.pid
+            ));
+            let data = Value::object([
+                ("port", (*port).into()),
+                ("pid", owner.pid.into()),
+                ("name", owner.name.as_str().into()),
+                ("protocol", owner.protocol.as_str().into()),
+            ]);
+            write(&mut file, "taken", data)?;
+        }
+        for port in held.keys().filter(|port| !now.contains_key(port)) {
+            log(&tr!(":{} freed", port));
+            write(
+                &mut file,
+                "freed",
+                Value::object([("port", (*port).into())]),
+            )?;
+        }
+        held = now;
+        thread::sleep(options.interval);
+    }
+}
+
+/// Print the occupied intervals of one port recorded by `run`, and how
+/// often, and by how many different programs, it was taken.
+pub fn report(options: ReportOptions) -> io::Result<()> {
+    let path = timeline_path(options.file)?;
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    let mut first = None;
+    let mut intervals: Vec<(u64, Option<u64>, Owner)> = Vec::new();
+    let mut open: Option<(u64, Owner)> = None;
+    for (number, line) in contents.lines().enumerate() {
+        let (secs, event) = parse_line(line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), tr!("line {}: {}", number + 1, e)),
+            )
+        })?;
+        first.get_or_insert(secs);
+        match event {
+            Event::Start => {
+                if let Some((from, owner)) = open.take() {
+                    intervals.push((from, None, owner));
+                }
+            }
+            Event::Taken(port, owner) if port == options.port => {
+                if let Some((from, previous)) = open.replace((secs, owner)) {
+                    intervals.push((from, Some(secs), previous));
+                }
+            }
+            Event::Freed(port) if port == options.port => {
+                if let Some((from, owner)) = open.take() {
+                    intervals.push((from, Some(secs), owner));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let Some(first) = first else {
+        println!("{}", tr!("{} is empty", path.display()));
+        return Ok(());
+    };
+    println!(
+        "{}",
+        tr!(
+            "Timeline of :{} (monitored since {}, from {})",
+            options.port,
+            format_utc(first),
+            path.display()
+        )
+    );
+    if intervals.is_empty() && open.is_none() {
+        println!("{}", tr!("Nothing held :{} while monitored", options.port));
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "{:<19}  {:<19}  {:>9}  {}",
+        tr!("FROM"),
+        tr!("TO"),
+        tr!("HELD FOR"),
+        tr!("OWNER")
+    );
+    for (from, to, owner) in &intervals {
+        let (to, held) = match to {
+            Some(to) => (format_utc(*to), duration(to - from)),
+            // The monitor stopped, so the end was not seen.
+            None => ("?".to_string(), "?".to_string()),
+        };
+        println!(
+            "{:<19}  {:<19}  {:>9}  {} (PID: {}) {}",
+            format_utc(*from),
+            to,
+            held,
+            owner.name,
+            owner.pid,
+            owner.protocol
+        );
+    }
+    if let Some((from, owner)) = &open {
+        println!(
+            "{:<19}  {:<19}  {:>9}  {} (PID: {}) {}",
+            format_utc(*from),
+            tr!("(log ends)"),
+            "",
+            owner.name,
+            owner.pid,
+            owner.protocol
+        );
+    }
+
+    let owners: Vec<&Owner> = intervals
+        .iter()
+        .map(|(_, _, owner)| owner)
+        .chain(open.iter().map(|(_, owner)| owner))
+        .collect();
+    let names: BTreeSet<&str> = owners.iter().map(|owner| owner.name.as_str()).collect();
+    println!();
+    println!(
+        "{}",
+        tr!(
+            "Taken {} times by {} programs: {}",
+            owners.len(),
+            names.len(),
+            names.into_iter().collect::<Vec<_>>().join(", ")
+        )
+    );
+    Ok(())
+}
+
+/// Alert once when the count of matching listening ports rises above the
+/// threshold, and again only after it has dropped back. Returns whether
+/// the count is above it.
+fn check(alert: &Alert, processes: &[PortProcess], alerting: bool) -> bool {
+    let ports: BTreeSet<u16> = processes
+        .iter()
+        .filter(|p| p.listening && alert.filter.as_ref().is_none_or(|f| f.matches(p)))
+        .map(|p| p.port)
+        .collect();
+    let count = ports.len();
+    if count <= alert.over {
+        if alerting {
+            log(&tr!(
+                "{} ports listened on, back within the threshold of {}",
+                count,
+                alert.over
+            ));
+        }
+        return false;
+    }
+    if alerting {
+        return true;
+    }
+
+    let message = tr!(
+        "{} ports listened on, more than the threshold of {}",
+        count,
+        alert.over
+    );
+    log(&message);
+    let result = match &alert.exec {
+        Some(command) => shell(command)
+            .env("RIP_LISTENERS", count.to_string())
+            .env("RIP_THRESHOLD", alert.over.to_string())
+            .status(),
+        None => notify(&message),
+    };
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => log(&tr!("alert failed with status: {}", status)),
+        Err(e) => log(&tr!("alert failed: {}", e)),
+    }
+    true
+}
+
+/// Notify about each port listened on from other machines that was not in
+/// `before`, with how many such ports there are now. Returns them all.
+fn notify_exposed(processes: &[PortProcess], before: Option<&BTreeSet<u16>>) -> BTreeSet<u16> {
+    let exposed: BTreeMap<u16, &PortProcess> = processes
+        .iter()
+        .filter(|p| p.listening && !conflict::is_loopback(&p.address))
+        .map(|p| (p.port, p))
+        .collect();
+    for (port, process) in &exposed {
+        if before.is_none_or(|before| before.contains(port)) {
+            continue;
+        }
+        let message = tr!(
+            "{} (PID: {}) listens on {}:{}, {} ports exposed",
+            process.name,
+            process.pid,
+            process.address,
+            port,
+            exposed.len()
+        );
+        log(&message);
+        match notify(&message) {
+            Ok(status) if status.success() => {}
+            Ok(status) => log(&tr!("notification failed with status: {}", status)),
+            Err(e) => log(&tr!("notification failed: {}", e)),
+        }
+    }
+    exposed.into_keys().collect()
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+/// A desktop notification: `notify-send` on Linux and the BSDs,
+/// `osascript` on macOS.
+fn notify(message: &str) -> io::Result<ExitStatus> {
+    if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"rip\"",
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        Command::new("osascript").args(["-e", &script]).status()
+    } else if cfg!(windows) {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("no desktop notifications on Windows; use --alert-exec"),
+        ))
+    } else {
+        Command::new("notify-send").args(["rip", message]).status()
+    }
+}
+
+fn timeline_path(file: Option<PathBuf>) -> io::Result<PathBuf> {
+    file.or_else(default_path)
+        .ok_or_else(|| io::Error::other(tr!("cannot find a home directory")))
+}
+
+/// port -> the lowest-PID process listening on it.
+fn owners(processes: &[PortProcess]) -> BTreeMap<u16, Owner> {
+    let mut owners: BTreeMap<u16, Owner> = BTreeMap::new();
+    for process in processes.iter().filter(|p| p.listening) {
+        let owner = Owner {
+            pid: process.pid,
+            name: process.name.clone(),
+            protocol: process.protocol.clone(),
+        };
+        owners
+            .entry(process.port)
+            .and_modify(|held| {
+                if owner.pid < held.pid {
+                    *held = owner.clone();
+                }
+            })
+            .or_insert(owner);
+    }
+    owners
+}
+
+fn write(file: &mut fs::File, kind: &str, data: Value) -> io::Result<()> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let line = Value::Array(vec![secs.into(), kind.into(), data]);
+    writeln!(file, "{}", line)
+}
+
+fn parse_line(line: &str) -> Result<(u64, Event), String> {
+    let value = json::parse(line)?;
+    let Value::Array(fields) = value else {
+        return Err(tr!("expected [seconds, kind, data]"));
+    };
+    let [secs, Value::String(kind), data] = fields.as_slice() else {
+        return Err(tr!("expected [seconds, kind, data]"));
+    };
+    let secs = secs
+        .as_u64()
+        .ok_or_else(|| tr!("expected [seconds, kind, data]"))?;
+    let port = || {
+        data.get("port")
+            .and_then(Value::as_u64)
+            .and_then(|port| u16::try_from(port).ok())
+            .ok_or_else(|| tr!("missing port"))
+    };
+    let event = match kind.as_str() {
+        "start" => Event::Start,
+        "freed" => Event::Freed(port()?),
+        "taken" => {
+            let text = |key| data.get(key).and_then(Value::as_str).unwrap_or_default();
+            Event::Taken(
+                port()?,
+                Owner {
+                    pid: data.get("pid").and_then(Value::as_u64).unwrap_or(0) as u32,
+                    name: text("name").to_string(),
+                    protocol: text("protocol").to_string(),
+                },
+            )
+        }
+        other => return Err(tr!("unknown event '{}'", other)),
+    };
+    Ok((secs, event))
+}
+
+/// `3d 4h`, `2h 5m`, `5m 3s` or `42s`.
+fn duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+fn log(message: &str) {
+    println!("[{}] {}", utc_timestamp(), message);
+}
@@ -0,0 +1,85 @@
+use std::path::{Component, Path};
+use std::process::Command;
+
+/// The system package a file was installed by.
+pub struct Package {
+    /// "dpkg", "rpm", "pacman" or "brew".
+    pub manager: &'static str,
+    pub name: String,
+}
+
+/// Ask each package manager present on the system which package owns
+/// `path`. `None` if none does (or none is installed), i.e. the binary did
+/// not come from the OS or a package manager.
+pub fn owner(path: &Path) -> Option<Package> {
+    if let Some(name) = homebrew(path) {
+        return Some(Package {
+            manager: "brew",
+            name,
+        });
+    }
+
+    // Merged-/usr systems record files under either /bin or /usr/bin.
+    let mut candidates = vec![path.to_path_buf()];
+    if let Ok(rest) = path.strip_prefix("/usr") {
+        candidates.push(Path::new("/").join(rest));
+    }
+
+    for candidate in &candidates {
+        let file = candidate.to_string_lossy();
+        if let Some(name) = query("dpkg", &["-S", &file], |out| {
+            // "python3.11-minimal: /usr/bin/python3.11" (possibly several,
+            // comma-separated, and ":arch" qualified).
+            let (packages, _) = out.split_once(": ")?;
+            Some(packages.split(", ").next()?.to_string())
+        }) {
+            return Some(Package {
+                manager: "dpkg",
+                name,
+            });
+        }
+        if let Some(name) = query("rpm", &["-qf", &file], |out| {
+            Some(out.lines().next()?.to_string())
+        }) {
+            return Some(Package {
+                manager: "rpm",
+                name,
+            });
+        }
+        if let Some(name) = query("pacman", &["-Qqo", &file], |out| {
+            Some(out.lines().next()?.to_string())
+        }) {
+            return Some(Package {
+                manager: "pacman",
+                name,
+            });
+        }
+    }
+    None
+}
+
+/// Homebrew installs into `<prefix>/Cellar/<formula>/<version>/...` (and
+/// links into `<prefix>/bin`), so the formula is read off the resolved path
+/// without running `brew`, which is slow to start.
+fn homebrew(path: &Path) -> Option<String> {
+    let resolved = path.canonicalize().ok()?;
+    let mut components = resolved.components();
+    components.find(|c| *c == Component::Normal("Cellar".as_ref()))?;
+    Some(
+        components
+            .next()?
+            .as_os_str()
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// Run a package manager query; `None` if the tool is missing or the file
+/// is not owned by any of its packages.
+fn query(program: &str, args: &[&str], parse: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse(String::from_utf8_lossy(&output.stdout).trim())
+}
@@ -0,0 +1,172 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{PortProcess, UNKNOWN_PID, config, process, risk};
+
+/// `rip pick`: a numbered list of listeners on stderr, then a line from
+/// stdin picks one to kill, by number or by a fuzzy query that narrows the
+/// list first. The list and prompts go to stderr so stdout only carries
+/// what was killed, as with `rip <PORT>`.
+pub fn run(query: Option<String>, safe: bool) -> io::Result<()> {
+    let processes: Vec<PortProcess> = crate::get_port_processes()
+        .into_iter()
+        .filter(|p| p.pid != UNKNOWN_PID)
+        .collect();
+    if processes.is_empty() {
+        eprintln!("{}", tr!("No listeners."));
+        std::process::exit(1);
+    }
+    let mut shown = match &query {
+        Some(query) => matching(&processes, query),
+        None => processes.iter().collect(),
+    };
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    loop {
+        if shown.is_empty() {
+            eprintln!("{}", tr!("Nothing matches; showing every listener."));
+            shown = processes.iter().collect();
+        }
+        let picked = if let [only] = shown[..] {
+            eprint!(
+                "{}",
+                tr!(
+                    "Kill {} (PID: {}) on port {}? [y/N] ",
+                    only.name,
+                    only.pid,
+                    only.port
+                )
+            );
+            let answer = read_line(&mut input)?;
+            if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+                eprintln!("{}", tr!("Not killed."));
+                std::process::exit(1);
+            }
+            only
+        } else {
+            for (i, p) in shown.iter().enumerate() {
+                eprintln!(
+                    "{:>3}) :{} {} {} (PID: {}) {}",
+                    i + 1,
+                    p.port,
+                    p.protocol,
+                    p.name,
+                    p.pid,
+                    p.address
+                );
+            }
+            eprint!(
+                "{}",
+                tr!("Number to kill, or text to narrow the list (Enter quits): ")
+            );
+            let answer = read_line(&mut input)?;
+            if answer.is_empty() {
+                eprintln!("{}", tr!("Not killed."));
+                std::process::exit(1);
+            }
+            match answer.parse::<usize>() {
+                Ok(n) if (1..=shown.len()).contains(&n) => shown[n - 1],
+                _ => {
+                    shown = matching(&processes, &answer);
+                    continue;
+                }
+            }
+        };
+        return kill(picked, safe);
+    }
+}
+
+/// The next line of input, trimmed; empty at end of input.
+fn read_line(input: &mut impl BufRead) -> io::Result<String> {
+    io::stderr().flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+fn kill(p: &PortProcess, safe: bool) -> io::Result<()> {
+    if let Some(refusal) = risk::kill_refusal(p, &config::load_or_exit()) {
+        eprintln!("{}", refusal);
+        std::process::exit(1);
+    }
+    if safe {
+        eprintln!(
+            "{}",
+            tr!(
+                "Safe mode: would kill {} (PID: {}) on :{}",
+                p.name,
+                p.pid,
+                p.port
+            )
+        );
+        return Ok(());
+    }
+    match crate::audit::kill(&process::System, p, "pick") {
+        Ok(()) => {
+            println!(
+                "{}",
+                tr!(
+                    "Killed {} (PID: {}) on :{}/{}",
+                    p.name,
+                    p.pid,
+                    p.port,
+                    p.protocol
+                )
+            );
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr!(
+                    "Could not kill {} (PID: {}) on :{}: {}",
+                    p.name,
+                    p.pid,
+                    p.port,
+                    e
+                )
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The listeners `query` fuzzy-matches, best first. A row matches when the
+/// query's characters appear in order in its port, name and PID, the way
+/// fzf matches; runs of consecutive characters and matches at the start of
+/// a word rank higher.
+fn matching<'a>(processes: &'a [PortProcess], query: &str) -> Vec<&'a PortProcess> {
+    let query = query.to_lowercase();
+    let mut scored: Vec<(usize, &PortProcess)> = processes
+        .iter()
+        .filter_map(|p| {
+            let text = format!(":{} {} {}", p.port, p.name, p.pid).to_lowercase();
+            fuzzy_score(&text, &query).map(|score| (score, p))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, p)| p).collect()
+}
+
+/// How well `needle`'s characters match `haystack` in order, or `None` if
+/// some of them do not appear.
+fn fuzzy_score(haystack: &str, needle: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut chars = haystack.chars();
+    let mut adjacent = false;
+    for wanted in needle.chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let c = chars.next()?;
+            let at_word = previous.is_none_or(|p| !p.is_alphanumeric());
+            previous = Some(c);
+            if c == wanted {
+                score += 1 + usize::from(adjacent) * 2 + usize::from(at_word) * 3;
+                adjacent = true;
+                break;
+            }
+            adjacent = false;
+        }
+    }
+    Some(score)
+}
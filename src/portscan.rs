@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::{PortProcess, UNKNOWN_PID};
+
+/// What the bind probe checks unless told otherwise: the well-known ports
+/// and where servers usually listen, not the ephemeral ports above, which
+/// are mostly outgoing connections.
+pub const DEFAULT_RANGE: RangeInclusive<u16> = 1..=10000;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// IANA's suggested ephemeral range, used when the OS doesn't say.
+const IANA_EPHEMERAL: RangeInclusive<u16> = 49152..=65535;
+
+/// The OS ephemeral (outgoing connection) port range.
+pub fn ephemeral_range() -> RangeInclusive<u16> {
+    fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range")
+        .ok()
+        .and_then(|contents| {
+            let mut bounds = contents.split_whitespace().map(|n| n.parse::<u16>().ok());
+            Some(bounds.next()??..=bounds.next()??)
+        })
+        .unwrap_or(IANA_EPHEMERAL)
+}
+
+/// Find occupied ports without any socket-to-process mapping, by trying to
+/// bind each port in `range` ourselves. Ports we may not bind (privileged
+/// ports when not root) are checked with a loopback connect instead.
+/// `(port, protocol)` pairs in `known` are already accounted for and left
+/// alone.
+pub fn occupied_ports(
+    range: &RangeInclusive<u16>,
+    known: &HashSet<(u16, String)>,
+) -> Vec<PortProcess> {
+    let mut processes = Vec::new();
+    let unknown = |port: u16, protocol: &str| !known.contains(&(port, protocol.to_string()));
+
+    for port in range.clone() {
+        if unknown(port, "TCP") && tcp_occupied(port) {
+            processes.push(unknown_owner(port, "TCP"));
+        }
+        if unknown(port, "UDP") && udp_occupied(port) {
+            processes.push(unknown_owner(port, "UDP"));
+        }
+    }
+
+    processes
+}
+
+fn tcp_occupied(port: u16) -> bool {
+    match TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)) {
+        Ok(_) => false,
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => true,
+        Err(_) => {
+            let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+            TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok()
+        }
+    }
+}
+
+fn udp_occupied(port: u16) -> bool {
+    matches!(
+        UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)),
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse
+    )
+}
+
+fn unknown_owner(port: u16, protocol: &str) -> PortProcess {
+    PortProcess {
+        pid: UNKNOWN_PID,
+        port,
+        protocol: protocol.to_string(),
+        name: "(unknown owner)".to_string(),
+        address: String::new(),
+        listening: true,
+        user: None,
+        nice: None,
+        connections: None,
+        context: None,
+        tag: None,
+    }
+}
+
+pub fn parse_range(value: &str) -> Result<RangeInclusive<u16>, String> {
+    let invalid = || tr!("invalid port range '{}' (expected START-END)", value);
+    let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+    let start: u16 = start.parse().map_err(|_| invalid())?;
+    let end: u16 = end.parse().map_err(|_| invalid())?;
+    if start == 0 || start > end {
+        return Err(invalid());
+    }
+    Ok(start..=end)
+}
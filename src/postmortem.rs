@@ -0,0 +1,122 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How a core dump was taken.
+pub enum CoreDump {
+    /// Written by a dumper while the process kept running.
+    Saved(PathBuf),
+    /// No dumper is installed; the process was sent SIGABRT instead, and the
+    /// kernel writes a core file if the system's core settings allow it.
+    Aborted,
+}
+
+/// Dump the memory of `pid` to a file for post-mortem debugging, using the
+/// first dumper found: `gcore` (gdb, and macOS), then `procdump`
+/// (Sysinternals, Linux and Windows). Without either, fall back to SIGABRT,
+/// which ends the process (with SIGKILL if it survives the abort).
+pub fn dump_core(pid: u32, name: &str) -> io::Result<CoreDump> {
+    let base = evidence_path("core", name, pid);
+
+    if let Some(output) = run("gcore", &["-o", &base.to_string_lossy(), &pid.to_string()]) {
+        // gdb's gcore appends ".PID" to the name it is given; macOS's does not.
+        let suffixed = PathBuf::from(format!("{}.{}", base.display(), pid));
+        let path = if suffixed.exists() { suffixed } else { base };
+        return finish(output, path);
+    }
+    if let Some(output) = run(
+        "procdump",
+        &["-n", "1", &pid.to_string(), &base.to_string_lossy()],
+    ) {
+        return finish(output, base);
+    }
+
+    if !crate::process::signal_and_wait(pid, "ABRT", crate::process::KILL_TIMEOUT)? {
+        crate::process::kill(pid)?;
+    }
+    Ok(CoreDump::Aborted)
+}
+
+/// Save the thread stacks of `pid` to a text file, with the runtime's own
+/// tool where there is one (`jstack` for the JVM, `py-spy dump` for
+/// Python), otherwise a native sampler: `sample` on macOS, `eu-stack` or
+/// gdb on Linux. Returns the file written.
+pub fn capture_stacks(pid: u32, name: &str) -> io::Result<PathBuf> {
+    let pid_arg = pid.to_string();
+    let lower = name.to_lowercase();
+    let mut tools: Vec<(&str, Vec<&str>)> = Vec::new();
+    if lower.starts_with("java") {
+        tools.push(("jstack", vec![&pid_arg]));
+    }
+    if lower.starts_with("python") {
+        tools.push(("py-spy", vec!["dump", "--pid", &pid_arg]));
+    }
+    if cfg!(target_os = "macos") {
+        tools.push(("sample", vec![&pid_arg, "1"]));
+    }
+    tools.push(("eu-stack", vec!["-p", &pid_arg]));
+    tools.push((
+        "gdb",
+        vec!["-p", &pid_arg, "-batch", "-ex", "thread apply all bt"],
+    ));
+
+    for (program, args) in &tools {
+        let Some(output) = run(program, args) else {
+            continue;
+        };
+        let output = output?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err(io::Error::other(tr!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let mut path = evidence_path("stacks", name, pid);
+        path.set_extension("txt");
+        let mut contents = format!("$ {} {}\n\n", program, args.join(" ")).into_bytes();
+        contents.extend_from_slice(&output.stdout);
+        fs::write(&path, contents)?;
+        return Ok(path);
+    }
+
+    let names: Vec<&str> = tools.iter().map(|(program, _)| *program).collect();
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        tr!("no stack capture tool found (tried {})", names.join(", ")),
+    ))
+}
+
+/// `$TMPDIR/rip-KIND-NAME-PID-SECS`, alongside the diagnostic bundles.
+fn evidence_path(kind: &str, name: &str, pid: u32) -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    std::env::temp_dir().join(format!("rip-{}-{}-{}-{}", kind, name, pid, secs))
+}
+
+/// Run `program`, or `None` if it is not installed.
+fn run(program: &str, args: &[&str]) -> Option<io::Result<std::process::Output>> {
+    match Command::new(program).args(args).output() {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        result => Some(result),
+    }
+}
+
+fn finish(output: io::Result<std::process::Output>, path: PathBuf) -> io::Result<CoreDump> {
+    let output = output?;
+    if output.status.success() {
+        Ok(CoreDump::Saved(path))
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
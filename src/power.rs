@@ -0,0 +1,44 @@
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+/// Event poll timeout while background work (probes, lookups) is in flight,
+/// in normal and low-power mode, and when nothing is pending at all.
+pub const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+pub const LOW_POWER_POLL_TIMEOUT: Duration = Duration::from_millis(1000);
+pub const IDLE_POLL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether the machine is currently running on battery.
+pub fn on_battery() -> bool {
+    if cfg!(target_os = "macos") {
+        Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains("'Battery Power'"))
+            .unwrap_or(false)
+    } else {
+        linux_on_battery()
+    }
+}
+
+/// On battery when no mains adapter is online and a battery is discharging.
+fn linux_on_battery() -> bool {
+    let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+
+    let mut discharging = false;
+    for supply in supplies.flatten() {
+        let read = |file: &str| {
+            fs::read_to_string(supply.path().join(file))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        match read("type").as_str() {
+            "Mains" if read("online") == "1" => return false,
+            "Battery" if read("status") == "Discharging" => discharging = true,
+            _ => {}
+        }
+    }
+    discharging
+}
@@ -0,0 +1,324 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::PortProcess;
+use crate::conflict::is_wildcard;
+
+pub const HTTP_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Most probes in flight at once; rows past that wait for a free slot.
+const HEALTH_WORKERS: usize = 8;
+
+/// Longest a probe may take from start to finish, name lookup included,
+/// before its row is marked `timeout` and the task is abandoned.
+const PROBE_DEADLINE: Duration = Duration::from_millis(1500);
+
+/// Outcome of an HTTP health check against a listener.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Health {
+    Pending,
+    Status(u16),
+    Timeout,
+    Tls,
+    NotHttp,
+    Unreachable,
+}
+
+impl Health {
+    pub fn label(&self) -> String {
+        match self {
+            Health::Pending => "...".to_string(),
+            Health::Status(code) => code.to_string(),
+            Health::Timeout => "timeout".to_string(),
+            Health::Tls => "tls".to_string(),
+            Health::NotHttp => "-".to_string(),
+            Health::Unreachable => "error".to_string(),
+        }
+    }
+}
+
+/// Host to use when connecting to a listener: loopback for wildcard binds,
+/// otherwise the bound address itself.
+pub fn connect_host(process: &PortProcess) -> String {
+    let address = process
+        .address
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    if is_wildcard(&process.address) || address.is_empty() {
+        "localhost".to_string()
+    } else if address.contains(':') {
+        format!("[{}]", address)
+    } else {
+        address.to_string()
+    }
+}
+
+/// The address this host uses on the LAN. Connecting a UDP socket sends
+/// nothing; it only makes the OS pick the outgoing interface.
+pub fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket
+        .connect((Ipv4Addr::new(239, 255, 255, 250), 1900))
+        .ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+pub fn http_url(process: &PortProcess) -> String {
+    format!("http://{}:{}/", connect_host(process), process.port)
+}
+
+/// Send a `HEAD /` request and return the response status code.
+pub fn http_status(process: &PortProcess, timeout: Duration) -> io::Result<u16> {
+    http_probe(process, timeout, &AtomicBool::new(false))
+}
+
+/// `http_status`, giving up between steps once `cancelled` is set. A TLS
+/// record in reply fails with `ErrorKind::Unsupported`.
+fn http_probe(process: &PortProcess, timeout: Duration, cancelled: &AtomicBool) -> io::Result<u16> {
+    let check = || {
+        if cancelled.load(Ordering::Relaxed) {
+            Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "probe cancelled",
+            ))
+        } else {
+            Ok(())
+        }
+    };
+    let host = connect_host(process);
+    let addr: SocketAddr = (
+        host.trim_start_matches('[').trim_end_matches(']'),
+        process.port,
+    )
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address to connect to"))?;
+    check()?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    check()?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    write!(
+        stream,
+        "HEAD / HTTP/1.0\r\nHost: {}:{}\r\nUser-Agent: rip\r\n\r\n",
+        host, process.port
+    )?;
+
+    let mut buf = [0u8; 64];
+    let n = stream.read(&mut buf)?;
+    // A TLS server answers plain text with an alert or handshake record.
+    if matches!(buf[..n], [0x15 | 0x16, 0x03, ..]) {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "TLS listener"));
+    }
+    let head = String::from_utf8_lossy(&buf[..n]);
+    head.strip_prefix("HTTP/")
+        .and_then(|rest| rest.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not an HTTP response"))
+}
+
+fn check_health(process: &PortProcess, cancelled: &AtomicBool) -> Health {
+    match http_probe(process, HTTP_TIMEOUT, cancelled) {
+        Ok(code) => Health::Status(code),
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Health::Tls,
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+            ) =>
+        {
+            Health::Timeout
+        }
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Health::NotHttp,
+        Err(_) => Health::Unreachable,
+    }
+}
+
+type Key = (u32, u16);
+
+/// A probe in flight. `id` tells its result from a cancelled earlier
+/// probe of the same row.
+struct Task {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+}
+
+/// Health checks for the rows on screen, one task per row. Each task runs
+/// on its own thread with a cancellation flag and a deadline: once a row
+/// scrolls away or disappears its task is cancelled and whatever it
+/// returns is dropped, and a task past `PROBE_DEADLINE` counts as a
+/// timeout, so a hung lookup or connect never holds up refreshing or
+/// drawing. Threads stuck in a blocking call end at their own socket
+/// timeout.
+pub struct Probes {
+    results: HashMap<Key, Health>,
+    running: HashMap<Key, Task>,
+    /// Rows probed since the last refresh, finished or not.
+    checked: HashSet<Key>,
+    next_id: u64,
+    tx: Sender<(Key, u64, Health)>,
+    rx: Receiver<(Key, u64, Health)>,
+}
+
+impl Probes {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Probes {
+            results: HashMap::new(),
+            running: HashMap::new(),
+            checked: HashSet::new(),
+            next_id: 0,
+            tx,
+            rx,
+        }
+    }
+
+    pub fn health(&self, key: Key) -> Option<&Health> {
+        self.results.get(&key)
+    }
+
+    pub fn busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Cancel everything and forget every result.
+    pub fn clear(&mut self) {
+        self.cancel_where(|_| true);
+        self.results.clear();
+        self.checked.clear();
+    }
+
+    /// After a rescan: drop the rows in `present` no longer lists, killed
+    /// or not, and probe the rest again as they come on screen. Old results
+    /// stay up until the new ones arrive.
+    pub fn rescanned(&mut self, present: &[PortProcess]) {
+        let present: HashSet<Key> = present.iter().map(|p| (p.pid, p.port)).collect();
+        self.cancel_where(|key| !present.contains(key));
+        self.results.retain(|key, _| present.contains(key));
+        self.checked.clear();
+    }
+
+    /// Probe the TCP rows in `shown`, the ones on screen, that have not
+    /// been probed since the last rescan, and cancel probes of rows that
+    /// scrolled away. Returns whether a row's label changed.
+    pub fn follow(&mut self, shown: &[PortProcess]) -> bool {
+        let targets: Vec<&PortProcess> = shown.iter().filter(|p| p.protocol == "TCP").collect();
+        let on_screen: HashSet<Key> = targets.iter().map(|p| (p.pid, p.port)).collect();
+        let mut changed = false;
+        for key in self.cancel_where(|key| !on_screen.contains(key)) {
+            // Probed again if it comes back.
+            self.checked.remove(&key);
+            if self.results.get(&key) == Some(&Health::Pending) {
+                self.results.remove(&key);
+            }
+        }
+        for process in targets {
+            let key = (process.pid, process.port);
+            if self.checked.contains(&key) {
+                continue;
+            }
+            if let Entry::Vacant(entry) = self.results.entry(key) {
+                entry.insert(Health::Pending);
+                changed = true;
+            }
+            if self.running.len() < HEALTH_WORKERS {
+                self.start(process);
+            }
+        }
+        changed
+    }
+
+    fn start(&mut self, process: &PortProcess) {
+        let key = (process.pid, process.port);
+        let id = self.next_id;
+        self.next_id += 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let task = Task {
+            id,
+            cancelled: Arc::clone(&cancelled),
+            started: Instant::now(),
+        };
+        let process = process.clone();
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let health = check_health(&process, &cancelled);
+            if !cancelled.load(Ordering::Relaxed) {
+                let _ = tx.send((key, id, health));
+            }
+        });
+        self.running.insert(key, task);
+        self.checked.insert(key);
+    }
+
+    /// Cancel the running probes whose row `cancel` picks, returning their
+    /// rows.
+    fn cancel_where(&mut self, cancel: impl Fn(&Key) -> bool) -> Vec<Key> {
+        let keys: Vec<Key> = self
+            .running
+            .keys()
+            .filter(|key| cancel(key))
+            .copied()
+            .collect();
+        for key in &keys {
+            if let Some(task) = self.running.remove(key) {
+                task.cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+        keys
+    }
+
+    /// Collect finished probes and time out overdue ones. Returns whether
+    /// anything changed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((key, id, health)) = self.rx.try_recv() {
+            if self.running.get(&key).is_some_and(|task| task.id == id) {
+                self.running.remove(&key);
+                self.results.insert(key, health);
+                changed = true;
+            }
+        }
+        let overdue: HashSet<Key> = self
+            .running
+            .iter()
+            .filter(|(_, task)| task.started.elapsed() > PROBE_DEADLINE)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in self.cancel_where(|key| overdue.contains(key)) {
+            self.results.insert(key, Health::Timeout);
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Open a URL with the platform's default handler.
+pub fn open_url(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
@@ -0,0 +1,228 @@
+use std::io;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::{UNKNOWN_PID, enrich};
+
+/// How long `kill` waits for a SIGKILLed process to go away.
+pub const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Something that can signal processes: the system itself, or a stand-in
+/// for tests that must not touch real ones.
+pub trait Signaller {
+    /// SIGKILL `pid` and wait until it has exited, like `kill`.
+    fn kill(&self, pid: u32) -> io::Result<()>;
+
+    /// Send `signal` (`TERM`, `HUP`, ...) without waiting, like `signal`.
+    fn signal(&self, pid: u32, signal: &str) -> io::Result<()>;
+
+    /// Send `signal` to every process in group `pgid`, like `signal_group`.
+    fn signal_group(&self, pgid: u32, signal: &str) -> io::Result<()>;
+
+    /// Send `signal` as root, like `signal_as_root`.
+    fn signal_as_root(&self, pid: u32, signal: &str) -> io::Result<()>;
+}
+
+/// Signals real processes with `kill` and `signal`.
+pub struct System;
+
+impl Signaller for System {
+    fn kill(&self, pid: u32) -> io::Result<()> {
+        kill(pid)
+    }
+
+    fn signal(&self, pid: u32, signal: &str) -> io::Result<()> {
+        self::signal(pid, signal)
+    }
+
+    fn signal_group(&self, pgid: u32, signal: &str) -> io::Result<()> {
+        self::signal_group(pgid, signal)
+    }
+
+    fn signal_as_root(&self, pid: u32, signal: &str) -> io::Result<()> {
+        self::signal_as_root(pid, signal)
+    }
+}
+
+/// SIGKILL `pid` and wait until it has actually exited. A successful `kill`
+/// only means the signal was delivered; a process stuck in uninterruptible
+/// sleep keeps running (and keeps its sockets) regardless.
+pub fn kill(pid: u32) -> io::Result<()> {
+    if signal_and_wait(pid, "KILL", KILL_TIMEOUT)? {
+        Ok(())
+    } else {
+        Err(io::Error::other(tr!(
+            "PID {} is still running after SIGKILL",
+            pid
+        )))
+    }
+}
+
+/// Send `signal` and wait up to `timeout` for the process to exit. Returns
+/// whether it did.
+pub fn signal_and_wait(pid: u32, signal: &str, timeout: Duration) -> io::Result<bool> {
+    let started = enrich::process_status(pid).map(|(_, started)| started);
+    self::signal(pid, signal)?;
+
+    let deadline = Instant::now() + timeout;
+    while !has_exited(pid, started.as_deref()) {
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Ok(true)
+}
+
+/// A zombie has already closed its sockets, and a different start time
+/// means the PID now belongs to some other process.
+pub fn has_exited(pid: u32, started: Option<&str>) -> bool {
+    match enrich::process_status(pid) {
+        None => true,
+        Some((state, start)) => state.starts_with('Z') || started.is_some_and(|s| s != start),
+    }
+}
+
+pub fn signal(pid: u32, signal: &str) -> io::Result<()> {
+    if pid == UNKNOWN_PID {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            tr!("owning process is unknown"),
+        ));
+    }
+
+    // Windows has no signals: taskkill asks the process to close, and /F
+    // ends it with TerminateProcess.
+    let status = if cfg!(windows) {
+        let mut taskkill = Command::new("taskkill");
+        if signal == "KILL" {
+            taskkill.arg("/F");
+        }
+        taskkill.args(["/PID", &pid.to_string()]).status()?
+    } else {
+        Command::new("kill")
+            .arg(format!("-{}", signal))
+            .arg(pid.to_string())
+            .status()?
+    };
+
+    if status.success() {
+        Ok(())
+    } else if is_someone_elses(pid) {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            tr!("PID {} belongs to another user", pid),
+        ))
+    } else {
+        Err(io::Error::other(tr!(
+            "kill command failed with status: {}",
+            status
+        )))
+    }
+}
+
+/// Send `signal` through `sudo kill`, for a process `signal` was not
+/// allowed to touch. sudo may ask for a password, so the caller must hand
+/// it the terminal first. SIGKILL waits for the exit, like `kill`.
+pub fn signal_as_root(pid: u32, signal: &str) -> io::Result<()> {
+    if cfg!(windows) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("Windows has no sudo"),
+        ));
+    }
+    if pid == UNKNOWN_PID {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            tr!("owning process is unknown"),
+        ));
+    }
+
+    let started = enrich::process_status(pid).map(|(_, started)| started);
+    let status = Command::new("sudo")
+        .arg("kill")
+        .arg(format!("-{}", signal))
+        .arg(pid.to_string())
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(tr!("sudo exited with {}", status)));
+    }
+    if signal != "KILL" {
+        return Ok(());
+    }
+    let deadline = Instant::now() + KILL_TIMEOUT;
+    while !has_exited(pid, started.as_deref()) {
+        if Instant::now() >= deadline {
+            return Err(io::Error::other(tr!(
+                "PID {} is still running after SIGKILL",
+                pid
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+/// Whether a failed `kill` was refused because `pid` is someone else's
+/// (EPERM), rather than gone or unknown.
+#[cfg(unix)]
+fn is_someone_elses(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks whether the signal could be sent.
+    let refused = unsafe { libc::kill(pid, 0) } == -1;
+    refused && io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_someone_elses(_pid: u32) -> bool {
+    false
+}
+
+/// Send `signal` to a whole process group (`kill -SIG -- -PGID`), so a
+/// supervisor such as foreman or `npm run` goes together with the workers
+/// it would otherwise respawn. Refuses init's group and rip's own, which
+/// would take rip (and often the shell it runs in) down with it.
+pub fn signal_group(pgid: u32, signal: &str) -> io::Result<()> {
+    if cfg!(windows) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("Windows has no process groups to signal"),
+        ));
+    }
+    if pgid <= 1 || Some(pgid) == own_group() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            tr!(
+                "refusing to signal process group {}, which rip belongs to",
+                pgid
+            ),
+        ));
+    }
+
+    let status = Command::new("kill")
+        .arg(format!("-{}", signal))
+        .arg("--")
+        .arg(format!("-{}", pgid))
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(tr!(
+            "kill command failed with status: {}",
+            status
+        )))
+    }
+}
+
+#[cfg(unix)]
+fn own_group() -> Option<u32> {
+    // SAFETY: getpgrp cannot fail.
+    Some(unsafe { libc::getpgrp() } as u32)
+}
+
+#[cfg(not(unix))]
+fn own_group() -> Option<u32> {
+    None
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::netlink::DiagSocket;
+
+/// `TCP_LISTEN` and `TCP_ESTABLISHED` in the `st` column.
+const LISTEN: &str = "0A";
+const ESTABLISHED: &str = "01";
+
+/// Listening TCP and all UDP sockets from `/proc/net/{tcp,udp}{,6}`, which
+/// every user may read: the same as a netlink dump, for kernels or sandboxes
+/// without `NETLINK_SOCK_DIAG`. Missing IPv6 tables (IPv6 disabled) are
+/// skipped.
+pub fn sockets() -> io::Result<Vec<DiagSocket>> {
+    let mut sockets = Vec::new();
+    for (file, protocol, required) in [
+        ("tcp", "TCP", true),
+        ("tcp6", "TCP", false),
+        ("udp", "UDP", true),
+        ("udp6", "UDP", false),
+    ] {
+        let table = match fs::read_to_string(format!("/proc/net/{}", file)) {
+            Ok(table) => table,
+            Err(e) if required => return Err(e),
+            Err(_) => continue,
+        };
+        sockets.extend(parse_table(&table, protocol));
+    }
+    Ok(sockets)
+}
+
+/// The listening TCP or all UDP sockets in one `/proc/net` table, after
+/// its header line. Lines that cannot be read are skipped.
+pub fn parse_table(table: &str, protocol: &'static str) -> Vec<DiagSocket> {
+    let mut sockets = Vec::new();
+    for line in table.lines().skip(1) {
+        // sl local rem st tx:rx tr:when retrnsmt uid timeout inode
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, local, _, state, _, _, _, uid, _, inode, ..] = fields.as_slice() else {
+            continue;
+        };
+        let tcp = protocol == "TCP";
+        if tcp && *state != LISTEN {
+            continue;
+        }
+        let (Some((address, port)), Ok(uid), Ok(inode)) =
+            (parse_address(local), uid.parse(), inode.parse())
+        else {
+            continue;
+        };
+        sockets.push(DiagSocket {
+            protocol,
+            address,
+            port,
+            listening: tcp || *state != ESTABLISHED,
+            uid,
+            inode,
+        });
+    }
+    sockets
+}
+
+/// Established TCP connections per local address and port, from
+/// `/proc/net/tcp{,6}`: those accepted by whatever listens there, whoever
+/// owns it.
+pub fn established() -> io::Result<HashMap<(String, u16), usize>> {
+    let mut counts = HashMap::new();
+    for (file, required) in [("tcp", true), ("tcp6", false)] {
+        let table = match fs::read_to_string(format!("/proc/net/{}", file)) {
+            Ok(table) => table,
+            Err(e) if required => return Err(e),
+            Err(_) => continue,
+        };
+        count_established(&table, &mut counts);
+    }
+    Ok(counts)
+}
+
+/// Add the established connections in one `/proc/net/tcp{,6}` table to
+/// `counts`, by local address and port.
+pub fn count_established(table: &str, counts: &mut HashMap<(String, u16), usize>) {
+    for line in table.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, local, _, state, ..] = fields.as_slice() else {
+            continue;
+        };
+        if *state != ESTABLISHED {
+            continue;
+        }
+        if let Some(local) = parse_address(local) {
+            *counts.entry(local).or_insert(0) += 1;
+        }
+    }
+}
+
+/// `0100007F:1F90` -> ("127.0.0.1", 8080). Addresses are printed as
+/// native-endian 32-bit words, so each word's bytes are in memory order.
+pub fn parse_address(field: &str) -> Option<(String, u16)> {
+    let (hex, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for word in 0..hex.len() / 8 {
+        let word = u32::from_str_radix(hex.get(word * 8..word * 8 + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    let address = match bytes.len() {
+        4 => {
+            let ip = Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]);
+            (!ip.is_unspecified()).then(|| ip.to_string())
+        }
+        16 => {
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?);
+            (!ip.is_unspecified()).then(|| format!("[{}]", ip))
+        }
+        _ => return None,
+    };
+    Some((address.unwrap_or_else(|| "*".to_string()), port))
+}
+
+/// Every socket inode open in a process rip may look at, with the (pid,
+/// name) of the lowest PID holding it. Other users' processes are only
+/// visible to root.
+pub fn socket_owners() -> HashMap<u64, (u32, String)> {
+    let mut owners: HashMap<u64, (u32, String)> = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return owners;
+    };
+    for pid in entries.filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u32>().ok()) {
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        let mut name = None;
+        for fd in fds.flatten() {
+            let Some(inode) = fs::read_link(fd.path()).ok().and_then(|target| {
+                target
+                    .to_str()?
+                    .strip_prefix("socket:[")?
+                    .strip_suffix(']')?
+                    .parse()
+                    .ok()
+            }) else {
+                continue;
+            };
+            if owners.get(&inode).is_some_and(|(owner, _)| *owner < pid) {
+                continue;
+            }
+            let name = name.get_or_insert_with(|| process_name(pid).unwrap_or_default());
+            owners.insert(inode, (pid, name.clone()));
+        }
+    }
+    owners
+}
+
+/// The kernel's short name for a process (`comm`, at most 15 bytes).
+pub fn process_name(pid: u32) -> Option<String> {
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end().to_string())
+}
+
+/// Real user ID of a process, from the `Uid:` line of its status file.
+pub fn process_uid(pid: u32) -> Option<u32> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// uid -> login name, from `/etc/passwd`.
+pub fn user_names() -> HashMap<u32, String> {
+    let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::{PortProcess, UNKNOWN_PID, container};
+
+/// Where system daemons live; killing one of these can take the machine's
+/// networking, login or display down with it.
+const SYSTEM_DIRS: &[&str] = &[
+    "/sbin/",
+    "/usr/sbin/",
+    "/usr/libexec/",
+    "/lib/systemd/",
+    "/usr/lib/systemd/",
+    "/System/",
+    "/Library/Apple/",
+];
+
+/// Process managers that restart what they supervise, so a kill is either
+/// undone or fights the supervisor. Matched by exact name, and never as
+/// PID 1: the init system is everything's parent and supervises none of it
+/// in this sense.
+const SUPERVISORS: &[&str] = &[
+    "systemd",
+    "launchd",
+    "supervisord",
+    "runsv",
+    "s6-supervise",
+    "pm2",
+    "containerd-shim",
+    "tini",
+    "dumb-init",
+];
+
+/// Processes whose loss can cut off the machine or the user's way into
+/// it. They always need a capital Y, and `--safe` never kills them.
+const PROTECTED: &[&str] = &[
+    "sshd",
+    "sshd-session",
+    "dropbear",
+    "mosh-server",
+    "tailscaled",
+    "systemd",
+    "init",
+    "launchd",
+    "dbus-daemon",
+    "NetworkManager",
+    "wpa_supplicant",
+    "loginwindow",
+    "WindowServer",
+    "lsass.exe",
+    "winlogon.exe",
+    "services.exe",
+];
+
+/// Established connections at which killing a listener is likely to drop
+/// someone's live traffic.
+const BUSY_CONNECTIONS: usize = 5;
+
+/// How much can go wrong when killing a process, and why.
+#[derive(Clone, Debug, Default)]
+pub struct Risk {
+    pub score: u8,
+    pub reasons: Vec<String>,
+    /// A system binary, supervised or protected process or Docker Desktop's
+    /// VM, which `--safe` refuses to kill.
+    pub protected: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Level {
+    /// Kill without asking unless the config asks for confirmation.
+    Low,
+    /// Always ask y/N.
+    Medium,
+    /// Ask for a capital Y and say why.
+    High,
+}
+
+impl Risk {
+    pub fn level(&self) -> Level {
+        match self.score {
+            0..=1 => Level::Low,
+            2..=3 => Level::Medium,
+            _ => Level::High,
+        }
+    }
+}
+
+/// Score every process in `processes` from one `ps` run: root-owned (2),
+/// a system binary (1), busy with the established connections the scan
+/// counted on its ports (1), and supervised by a process manager (1). One on the protected list,
+/// built in or the config's `protect`, scores high whatever else is true.
+pub fn assess(processes: &[PortProcess], config: &Config) -> HashMap<u32, Risk> {
+    assess_with(processes, config, &process_table())
+}
+
+/// `assess` against a `process_table` already taken.
+pub fn assess_with(
+    processes: &[PortProcess],
+    config: &Config,
+    table: &HashMap<u32, (u32, String)>,
+) -> HashMap<u32, Risk> {
+    let mut risks = HashMap::new();
+    for process in processes {
+        if process.pid == UNKNOWN_PID || risks.contains_key(&process.pid) {
+            continue;
+        }
+        let mut risk = Risk::default();
+        let mut add = |points: u8, reason: String| {
+            risk.score += points;
+            risk.reasons.push(reason);
+        };
+
+        if let Some(reason) = processes
+            .iter()
+            .filter(|p| p.pid == process.pid)
+            .find_map(|p| protection(p, config))
+        {
+            add(4, reason);
+            risk.protected = true;
+        }
+        if process.user.as_deref() == Some("root") {
+            add(2, tr!("runs as root"));
+        }
+        let entry = table.get(&process.pid);
+        let binary = fs::read_link(format!("/proc/{}/exe", process.pid))
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned())
+            .or_else(|| entry.map(|(_, command)| command.clone()));
+        if let Some(binary) = binary.filter(|b| SYSTEM_DIRS.iter().any(|dir| b.starts_with(dir))) {
+            add(1, tr!("system binary {}", binary));
+            risk.protected = true;
+        }
+        if container::is_desktop_vm(&process.name) {
+            add(
+                2,
+                tr!("Docker Desktop's VM: killing it stops every container"),
+            );
+            risk.protected = true;
+        }
+        let live: usize = processes
+            .iter()
+            .filter(|p| p.pid == process.pid)
+            .filter_map(|p| p.connections)
+            .sum();
+        if live >= BUSY_CONNECTIONS {
+            add(1, tr!("{} live connections", live));
+        }
+        if let Some((_, parent)) = entry
+            .filter(|(ppid, _)| *ppid != 1)
+            .and_then(|(ppid, _)| table.get(ppid))
+        {
+            let parent = parent.rsplit('/').next().unwrap_or(parent);
+            if SUPERVISORS.contains(&parent) {
+                add(1, tr!("supervised by {} (may be restarted)", parent));
+                risk.protected = true;
+            }
+        }
+        risks.insert(process.pid, risk);
+    }
+    risks
+}
+
+/// Why `process` is on the protected list, built in or the config's
+/// `protect`, if it is.
+pub fn protection(process: &PortProcess, config: &Config) -> Option<String> {
+    if PROTECTED.contains(&process.name.as_str()) {
+        Some(tr!(
+            "{} is protected: killing it can lock you out",
+            process.name
+        ))
+    } else if config.protects(process) {
+        Some(tr!("on the protect list in the config"))
+    } else {
+        None
+    }
+}
+
+/// Why a kill that cannot ask for a capital Y (`rip <PORT>`, `pick`,
+/// `guard`, `serve`) will not touch `process`: the config's `never_kill`,
+/// or the protected list.
+pub fn kill_refusal(process: &PortProcess, config: &Config) -> Option<String> {
+    config.never_kill_refusal(process).or_else(|| {
+        protection(process, config).map(|reason| {
+            tr!(
+                "{} (PID: {}) is protected ({}); not killing it, the TUI can with a capital Y",
+                process.name,
+                process.pid_label(),
+                reason
+            )
+        })
+    })
+}
+
+/// pid -> (parent pid, command). The command is a full path on macOS and
+/// the bare name elsewhere.
+pub fn process_table() -> HashMap<u32, (u32, String)> {
+    let Ok(output) = Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,comm="])
+        .output()
+    else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let command = fields.collect::<Vec<_>>().join(" ");
+            Some((pid, (ppid, command)))
+        })
+        .collect()
+}
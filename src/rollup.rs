@@ -0,0 +1,66 @@
+use std::collections::BTreeSet;
+
+use crate::PortProcess;
+
+/// What the rollup views aggregate listeners by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GroupBy {
+    User,
+    App,
+}
+
+impl GroupBy {
+    /// "user", as in "listeners by user".
+    pub fn label(&self) -> String {
+        match self {
+            GroupBy::User => tr!("user"),
+            GroupBy::App => tr!("application"),
+        }
+    }
+
+    pub fn column(&self) -> String {
+        match self {
+            GroupBy::User => tr!("USER"),
+            GroupBy::App => tr!("APP"),
+        }
+    }
+
+    /// The group a listener belongs to.
+    pub fn key(&self, process: &PortProcess) -> String {
+        match self {
+            GroupBy::User => process.user.clone().unwrap_or_else(|| "?".to_string()),
+            GroupBy::App => process.name.clone(),
+        }
+    }
+}
+
+/// One line of a rollup: every listener sharing a key.
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub key: String,
+    pub listeners: usize,
+    pub processes: usize,
+    pub ports: Vec<u16>,
+}
+
+/// Aggregate `processes`, biggest groups first.
+pub fn group(processes: &[PortProcess], by: GroupBy) -> Vec<Group> {
+    let keys: BTreeSet<String> = processes.iter().map(|p| by.key(p)).collect();
+    let mut groups: Vec<Group> = keys
+        .into_iter()
+        .map(|key| {
+            let members: Vec<&PortProcess> =
+                processes.iter().filter(|p| by.key(p) == key).collect();
+            let pids: BTreeSet<u32> = members.iter().map(|p| p.pid).collect();
+            let ports: BTreeSet<u16> = members.iter().map(|p| p.port).collect();
+            Group {
+                key,
+                listeners: members.len(),
+                processes: pids.len(),
+                ports: ports.into_iter().collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.listeners.cmp(&a.listeners).then(a.key.cmp(&b.key)));
+    groups
+}
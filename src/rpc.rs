@@ -0,0 +1,193 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::json::{self, Value};
+use crate::scanner::{PortScanner, SystemScanner};
+use crate::{UNKNOWN_PID, audit, config, get_port_processes, process, risk};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+type Output = Arc<Mutex<io::Stdout>>;
+
+/// Serve newline-delimited JSON-RPC 2.0 on stdin/stdout until stdin closes.
+///
+/// Methods: `list`, `kill {pid}`, `watch {interval_ms?}` and `unwatch`.
+/// While watching, a `changed` notification carrying the full process list
+/// is sent whenever it differs from the previous poll. With `safe`, `kill`
+/// only reports the process it would have killed.
+pub fn serve_stdio(safe: bool) -> io::Result<()> {
+    let out: Output = Arc::new(Mutex::new(io::stdout()));
+    let mut watcher: Option<Arc<AtomicBool>> = None;
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = match json::parse(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                send(&out, &error(Value::Null, PARSE_ERROR, &e))?;
+                continue;
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            send(
+                &out,
+                &error(id.unwrap_or(Value::Null), INVALID_REQUEST, "missing method"),
+            )?;
+            continue;
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "list" => Ok(process_list()),
+            "kill" => kill(&params, safe),
+            "watch" => {
+                stop(&mut watcher);
+                let interval = params
+                    .get("interval_ms")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(1000);
+                watcher = Some(watch(out.clone(), Duration::from_millis(interval.max(100))));
+                Ok(Value::Bool(true))
+            }
+            "unwatch" => {
+                stop(&mut watcher);
+                Ok(Value::Bool(true))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        };
+
+        // Requests without an id are notifications and get no response.
+        let Some(id) = id else { continue };
+        let response = match result {
+            Ok(result) => {
+                Value::object([("jsonrpc", "2.0".into()), ("id", id), ("result", result)])
+            }
+            Err((code, message)) => error(id, code, &message),
+        };
+        send(&out, &response)?;
+    }
+
+    stop(&mut watcher);
+    Ok(())
+}
+
+fn process_list() -> Value {
+    Value::Array(get_port_processes().iter().map(Value::from).collect())
+}
+
+fn kill(params: &Value, safe: bool) -> Result<Value, (i64, String)> {
+    let pid = params
+        .get("pid")
+        .and_then(Value::as_u64)
+        .and_then(|pid| u32::try_from(pid).ok())
+        .ok_or((INVALID_PARAMS, "kill needs a numeric 'pid'".to_string()))?;
+    let config = config::load().map_err(|e| (SERVER_ERROR, e))?;
+    kill_listed(
+        pid,
+        safe,
+        &config,
+        &SystemScanner::default(),
+        &process::System,
+    )
+}
+
+/// Kill `pid` for a client, but only when it holds one of the ports
+/// `scanner` lists and none of its rows is protected: the server must not
+/// become a way to kill arbitrary processes.
+pub fn kill_listed(
+    pid: u32,
+    safe: bool,
+    config: &config::Config,
+    scanner: &dyn PortScanner,
+    signaller: &dyn process::Signaller,
+) -> Result<Value, (i64, String)> {
+    let (processes, _) = scanner
+        .scan()
+        .map_err(|e| (SERVER_ERROR, format!("failed to list ports: {}", e)))?;
+    let rows: Vec<_> = processes
+        .iter()
+        .filter(|p| p.pid == pid && p.pid != UNKNOWN_PID)
+        .collect();
+    let Some(first) = rows.first() else {
+        return Err((
+            SERVER_ERROR,
+            format!("PID {} does not hold a listed port; not killing it", pid),
+        ));
+    };
+    if let Some(refusal) = rows.iter().find_map(|p| risk::kill_refusal(p, config)) {
+        return Err((SERVER_ERROR, refusal));
+    }
+    if safe {
+        return Ok(Value::object([
+            ("pid", pid.into()),
+            ("killed", false.into()),
+            ("dry_run", true.into()),
+        ]));
+    }
+
+    audit::kill(signaller, first, "serve")
+        .map(|_| Value::object([("pid", pid.into()), ("killed", true.into())]))
+        .map_err(|e| (SERVER_ERROR, format!("failed to kill PID {}: {}", pid, e)))
+}
+
+fn watch(out: Output, interval: Duration) -> Arc<AtomicBool> {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let flag = stopped.clone();
+
+    thread::spawn(move || {
+        let mut last: Option<Value> = None;
+        while !flag.load(Ordering::Relaxed) {
+            let current = process_list();
+            if last.as_ref() != Some(&current) {
+                let notification = Value::object([
+                    ("jsonrpc", "2.0".into()),
+                    ("method", "changed".into()),
+                    ("params", Value::object([("processes", current.clone())])),
+                ]);
+                if send(&out, &notification).is_err() {
+                    break;
+                }
+                last = Some(current);
+            }
+            thread::sleep(interval);
+        }
+    });
+
+    stopped
+}
+
+fn stop(watcher: &mut Option<Arc<AtomicBool>>) {
+    if let Some(flag) = watcher.take() {
+        flag.store(true, Ordering::Relaxed);
+    }
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    Value::object([
+        ("jsonrpc", "2.0".into()),
+        ("id", id),
+        (
+            "error",
+            Value::object([("code", code.into()), ("message", message.into())]),
+        ),
+    ])
+}
+
+fn send(out: &Output, message: &Value) -> io::Result<()> {
+    let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(out, "{}", message)?;
+    out.flush()
+}
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::io;
+
+use crate::config::{self, Config};
+use crate::{PortProcess, UNKNOWN_PID, get_port_processes, graceful};
+
+/// `rip rules test`: print what each of the config's rules would do to the
+/// listeners there are now. Nothing is hidden, signalled or written.
+pub fn test() -> io::Result<()> {
+    let config = config::load_or_exit();
+    let processes = get_port_processes();
+    for line in report(&config, &processes) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Each rule as the config spells it, followed by one indented line per
+/// listener it matches, saying what it does to it.
+pub fn report(config: &Config, processes: &[PortProcess]) -> Vec<String> {
+    // A process's TCP and UDP, or IPv4 and IPv6, rows are one listener here.
+    let mut seen = HashSet::new();
+    let processes: Vec<&PortProcess> = processes
+        .iter()
+        .filter(|p| seen.insert((p.pid, p.port)))
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut rule = |heading: String, effects: Vec<String>| {
+        lines.push(heading);
+        if effects.is_empty() {
+            lines.push(format!("  {}", tr!("matches nothing")));
+        }
+        lines.extend(effects.into_iter().map(|effect| format!("  {}", effect)));
+    };
+
+    let lists = [
+        ("ignore", &config.ignore),
+        ("protect", &config.protect),
+        ("never_kill", &config.never_kill),
+    ];
+    for (list, entries) in lists {
+        for entry in entries {
+            let effects = processes
+                .iter()
+                .filter(|p| config::matches(entry, p))
+                .map(|p| {
+                    let (name, pid, port) = (&p.name, p.pid_label(), p.port);
+                    match list {
+                        "ignore" => tr!("hides {} (PID: {}) on :{}", name, pid, port),
+                        "protect" => tr!(
+                            "asks for a capital Y before killing {} (PID: {}) on :{}",
+                            name,
+                            pid,
+                            port
+                        ),
+                        _ => tr!("refuses to kill {} (PID: {}) on :{}", name, pid, port),
+                    }
+                })
+                .collect();
+            rule(format!("{} {:?}", list, entry), effects);
+        }
+    }
+    for (program, signal) in &config.graceful {
+        let effects = processes
+            .iter()
+            .filter(|p| p.pid != UNKNOWN_PID)
+            .filter(|p| {
+                graceful::entry_for(config, p.pid, &p.name).map(|(server, _)| server)
+                    == Some(program.as_str())
+            })
+            .map(|p| {
+                tr!(
+                    "stops {} (PID: {}) on :{} with SIG{}",
+                    p.name,
+                    p.pid,
+                    p.port,
+                    signal
+                )
+            })
+            .collect();
+        rule(format!("[graceful] {:?} = {:?}", program, signal), effects);
+    }
+
+    if lines.is_empty() {
+        lines.push(tr!(
+            "The config has no rules: no ignore, protect or never_kill entries and no [graceful] table"
+        ));
+    }
+    lines
+}
@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+use std::io;
+use std::ops::RangeInclusive;
+use std::process::Command;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::backend::{self, Backend};
+use crate::{PortProcess, conflict, enrich, lsof, portscan};
+
+/// Something that lists the sockets in use and which process holds each.
+/// rip's backends implement it, and so can anything else that knows, such
+/// as a fixed list for tests.
+pub trait PortScanner {
+    /// Short name, as `--backend` and the title bar write it.
+    fn name(&self) -> &'static str;
+
+    /// One row per port. `Err` only when the scanner cannot run at all.
+    fn scan(&self) -> io::Result<(Vec<PortProcess>, ScanStats)>;
+
+    /// Whether work the last scan started in the background is still
+    /// running; once it is done, another scan includes what it found.
+    fn busy(&self) -> bool {
+        false
+    }
+}
+
+/// How often the background bind probe runs at most: each pass briefly
+/// holds every free port in the range, which a service starting just then
+/// could trip over.
+const PROBE_EVERY: Duration = Duration::from_secs(30);
+
+/// What rip itself scans with: the selected backend, falling back to the
+/// others, plus rows for ports whose owner rip may not see. When no backend
+/// can run, or one saw sockets without learning their owners (no
+/// privileges) that nothing else could name, it probes `fallback_range`
+/// for occupied ports, whose owners stay unknown. Never fails; what went
+/// wrong ends up in `ScanStats::failure`.
+pub struct SystemScanner {
+    pub fallback_range: RangeInclusive<u16>,
+    /// Set for `in_background`.
+    probe: Option<Mutex<Probe>>,
+}
+
+/// The background bind probe: what its last pass found, and the pass
+/// running now.
+#[derive(Default)]
+struct Probe {
+    found: Vec<PortProcess>,
+    running: Option<Receiver<Vec<PortProcess>>>,
+    finished: Option<Instant>,
+}
+
+impl Probe {
+    fn collect(&mut self) {
+        if let Some(found) = self.running.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.found = found;
+            self.running = None;
+            self.finished = Some(Instant::now());
+        }
+    }
+}
+
+impl Default for SystemScanner {
+    fn default() -> Self {
+        SystemScanner {
+            fallback_range: portscan::DEFAULT_RANGE,
+            probe: None,
+        }
+    }
+}
+
+impl SystemScanner {
+    /// For a UI that must not stall: the bind probe runs on a thread, at
+    /// most every `PROBE_EVERY`, and what it finds joins the scans after
+    /// it. `busy` says when a pass has finished.
+    pub fn in_background(fallback_range: RangeInclusive<u16>) -> Self {
+        SystemScanner {
+            fallback_range,
+            probe: Some(Mutex::new(Probe::default())),
+        }
+    }
+
+    /// Occupied ports in `fallback_range`, skipping those `known` already
+    /// holds: probed now, or the background probe's last finds.
+    fn probe(&self, known: HashSet<(u16, String)>) -> Vec<PortProcess> {
+        let Some(probe) = &self.probe else {
+            return portscan::occupied_ports(&self.fallback_range, &known);
+        };
+        let mut probe = probe.lock().unwrap_or_else(|e| e.into_inner());
+        probe.collect();
+        let due = probe.finished.is_none_or(|at| at.elapsed() >= PROBE_EVERY);
+        if probe.running.is_none() && due {
+            let (tx, rx) = mpsc::channel();
+            let range = self.fallback_range.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(portscan::occupied_ports(&range, &known));
+            });
+            probe.running = Some(rx);
+        }
+        probe.found.clone()
+    }
+}
+
+impl PortScanner for SystemScanner {
+    fn name(&self) -> &'static str {
+        backend::selected().0.name()
+    }
+
+    fn scan(&self) -> io::Result<(Vec<PortProcess>, ScanStats)> {
+        let (mut processes, mut stats) = backend::scan();
+        let probe_only = stats.backend == "bind probe";
+        if !probe_only {
+            stats.hidden = backend::add_hidden(&mut processes);
+        }
+        if probe_only || stats.unowned > 0 && stats.hidden == 0 {
+            let known = processes
+                .iter()
+                .map(|p| (p.port, p.protocol.clone()))
+                .collect();
+            let started = Instant::now();
+            let found = self.probe(known);
+            stats.command += started.elapsed();
+            let before = processes.len();
+            for row in found {
+                if !processes
+                    .iter()
+                    .any(|p| p.port == row.port && p.protocol == row.protocol)
+                {
+                    processes.push(row);
+                }
+            }
+            if !probe_only {
+                stats.hidden = processes.len() - before;
+            }
+            processes.sort_by_key(|p| (p.port, conflict::is_wildcard(&p.address)));
+        }
+        Ok((processes, stats))
+    }
+
+    fn busy(&self) -> bool {
+        self.probe.as_ref().is_some_and(|probe| {
+            let mut probe = probe.lock().unwrap_or_else(|e| e.into_inner());
+            probe.collect();
+            probe.running.is_some()
+        })
+    }
+}
+
+/// One backend on its own, without fallbacks; the bind probe checks every
+/// port.
+impl PortScanner for Backend {
+    fn name(&self) -> &'static str {
+        Backend::name(*self)
+    }
+
+    fn scan(&self) -> io::Result<(Vec<PortProcess>, ScanStats)> {
+        Backend::scan(*self, &portscan::DEFAULT_RANGE)
+    }
+}
+
+/// Every listener, as `SystemScanner` finds them.
+pub fn get_port_processes() -> Vec<PortProcess> {
+    SystemScanner::default()
+        .scan()
+        .map(|(processes, _)| processes)
+        .unwrap_or_default()
+}
+
+/// Where one scan's time went, for the debug overlay.
+#[derive(Clone, Debug, Default)]
+pub struct ScanStats {
+    pub backend: &'static str,
+    pub command: Duration,
+    pub parse: Duration,
+    pub enrich: Duration,
+    /// Set when the backend failed in a way worth reporting.
+    pub failure: Option<String>,
+    /// Rows added for ports whose owner rip is not allowed to see.
+    pub hidden: usize,
+    /// Sockets the backend saw without learning which process holds them,
+    /// as an unprivileged ss, netstat or `/proc` walk does.
+    pub unowned: usize,
+    pub raw: RawScan,
+}
+
+/// Exactly what a backend command produced, kept so failures can be
+/// reported with the input that caused them.
+#[derive(Clone, Debug, Default)]
+pub struct RawScan {
+    pub command: String,
+    pub status: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Probe `range` for occupied ports; the owners stay unknown.
+pub fn scan_bind_probe(range: &RangeInclusive<u16>) -> (Vec<PortProcess>, ScanStats) {
+    let started = Instant::now();
+    let processes = portscan::occupied_ports(range, &HashSet::new());
+    let stats = ScanStats {
+        backend: "bind probe",
+        command: started.elapsed(),
+        ..ScanStats::default()
+    };
+    (processes, stats)
+}
+
+/// List listeners with lsof, invoked the way `rip bench` found fastest on
+/// this machine. `Err` only when lsof cannot be run at all.
+pub fn scan_lsof() -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    scan_lsof_with(lsof::selected())
+}
+
+pub fn scan_lsof_with(variant: &lsof::Variant) -> io::Result<(Vec<PortProcess>, ScanStats)> {
+    let started = Instant::now();
+    let mut outputs = Vec::new();
+    for args in variant.passes {
+        outputs.push((args, Command::new("lsof").args(*args).output()?));
+    }
+    let command = started.elapsed();
+    let started = Instant::now();
+
+    let mut rows = Vec::new();
+    let mut unparsed = 0;
+    // lsof exits 1 with empty stderr when nothing matches; only complaints
+    // on stderr or output we could not read count as failures.
+    let mut failure = None;
+    let mut raw = RawScan::default();
+
+    for (args, output) in &outputs {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if failure.is_none() && !output.status.success() && !stderr.trim().is_empty() {
+            failure = stderr.lines().next().map(str::to_string);
+        }
+        if !raw.command.is_empty() {
+            raw.command.push_str("; ");
+            raw.status.push_str("; ");
+        }
+        raw.command.push_str(&format!("lsof {}", args.join(" ")));
+        raw.status.push_str(&output.status.to_string());
+        raw.stdout.push_str(&stdout);
+        raw.stderr.push_str(&stderr);
+        parse_lsof(&stdout, &mut rows, &mut unparsed);
+    }
+
+    let mut processes = backend::one_row_per_port(rows);
+    processes.sort_by_key(|p| (p.port, conflict::is_wildcard(&p.address)));
+    let parse = started.elapsed();
+
+    let started = Instant::now();
+    enrich::enrich(&mut processes);
+
+    if failure.is_none() && unparsed > 0 {
+        failure = Some(tr!("{} lines of lsof output could not be parsed", unparsed));
+    }
+
+    let stats = ScanStats {
+        backend: "lsof",
+        command,
+        parse,
+        enrich: started.elapsed(),
+        failure,
+        raw,
+        ..ScanStats::default()
+    };
+    Ok((processes, stats))
+}
+
+/// Add the sockets in one lsof invocation's output to `rows`, counting the
+/// lines that could not be read in `unparsed`.
+pub fn parse_lsof(stdout: &str, rows: &mut Vec<PortProcess>, unparsed: &mut usize) {
+    for line in stdout.lines().skip(1) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 9 {
+            *unparsed += 1;
+            continue;
+        }
+
+        let name = parts[0].to_string();
+        let user = parts[2].to_string();
+        let pid: u32 = match parts[1].parse() {
+            Ok(p) => p,
+            Err(_) => {
+                *unparsed += 1;
+                continue;
+            }
+        };
+
+        let listening = !parts[8].contains("->");
+
+        let protocol = if parts[4].contains("TCP") || parts[7].contains("TCP") {
+            "TCP".to_string()
+        } else if parts[4].contains("UDP") || parts[7].contains("UDP") {
+            "UDP".to_string()
+        } else {
+            "???".to_string()
+        };
+
+        let addr_field = parts[8].split("->").next().unwrap_or(parts[8]);
+        let (address, port) = match addr_field.rsplit_once(':') {
+            Some((host, port_str)) => (host.to_string(), port_str.parse().unwrap_or(0)),
+            None => (String::new(), 0),
+        };
+
+        if port > 0 {
+            rows.push(PortProcess {
+                pid,
+                port,
+                protocol,
+                name,
+                address,
+                listening,
+                user: Some(user),
+                nice: None,
+                connections: None,
+                context: None,
+                tag: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::clock::utc_timestamp;
+use crate::json::{self, Value};
+use crate::{PortProcess, UNKNOWN_PID};
+
+/// Format version written in the header line.
+const VERSION: u64 = 1;
+
+/// Tags `enrich` can put on a row; recordings store them by name.
+const TAGS: &[&str] = &["jetbrains", "vscode", "lsp", "debugger"];
+
+/// Something that happened during a recorded session.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// The rows on screen after a refresh.
+    Snapshot(Vec<PortProcess>),
+    /// A key pressed, with the (pid, port) of the row selected at the time.
+    Key(String, Option<(u32, u16)>),
+    /// A status line shown to the user.
+    Message(String),
+}
+
+/// Writes a session as JSON lines: a header object, then one
+/// `[seconds, kind, data]` array per event, the shape asciinema uses for
+/// terminal output but holding rip's data and the user's actions instead.
+/// Every line is flushed, so a recording survives rip being killed.
+pub struct Recorder {
+    file: File,
+    started: Instant,
+    last_message: Option<String>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Recorder> {
+        let mut file = File::create(path)?;
+        let header = Value::object([
+            ("version", VERSION.into()),
+            ("rip", env!("CARGO_PKG_VERSION").into()),
+            ("started", utc_timestamp().into()),
+        ]);
+        writeln!(file, "{}", header)?;
+        Ok(Recorder {
+            file,
+            started: Instant::now(),
+            last_message: None,
+        })
+    }
+
+    pub fn snapshot(&mut self, processes: &[PortProcess]) -> io::Result<()> {
+        let rows = processes.iter().map(Value::from).collect();
+        self.write("snapshot", Value::Array(rows))
+    }
+
+    pub fn key(&mut self, key: &str, selected: Option<&PortProcess>) -> io::Result<()> {
+        let mut fields = vec![("key", Value::from(key))];
+        if let Some(process) = selected {
+            fields.push(("pid", process.pid.into()));
+            fields.push(("port", process.port.into()));
+        }
+        self.write("key", Value::object(fields))
+    }
+
+    /// Record `message` if it differs from the last one recorded.
+    pub fn message(&mut self, message: Option<&String>) -> io::Result<()> {
+        if message == self.last_message.as_ref() {
+            return Ok(());
+        }
+        self.last_message = message.cloned();
+        match message {
+            Some(message) => self.write("message", message.as_str().into()),
+            None => Ok(()),
+        }
+    }
+
+    fn write(&mut self, kind: &str, data: Value) -> io::Result<()> {
+        let secs = (self.started.elapsed().as_millis() as f64) / 1000.0;
+        let line = Value::Array(vec![Value::Number(secs), kind.into(), data]);
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()
+    }
+}
+
+/// A loaded recording and the event being looked at.
+pub struct Replay {
+    pub path: PathBuf,
+    /// When the recording started, as written in its header.
+    pub started: String,
+    pub events: Vec<(f64, Event)>,
+    pub position: usize,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let contents = fs::read_to_string(path)?;
+        let invalid = |line: usize, e: String| {
+            io::Error::new(io::ErrorKind::InvalidData, tr!("line {}: {}", line, e))
+        };
+
+        let mut lines = contents.lines().enumerate().filter(|(_, l)| !l.is_empty());
+        let header = match lines.next() {
+            Some((_, line)) => json::parse(line).map_err(|e| invalid(1, e))?,
+            None => return Err(invalid(1, tr!("empty recording"))),
+        };
+        let version = header.get("version").and_then(Value::as_u64);
+        if version != Some(VERSION) {
+            return Err(invalid(1, tr!("not a rip recording (version {})", VERSION)));
+        }
+
+        let mut events = Vec::new();
+        for (i, line) in lines {
+            let value = json::parse(line).map_err(|e| invalid(i + 1, e))?;
+            let event = parse_event(&value).ok_or_else(|| invalid(i + 1, tr!("bad event")))?;
+            events.push(event);
+        }
+        Ok(Replay {
+            path: path.to_path_buf(),
+            started: header
+                .get("started")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            events,
+            position: 0,
+        })
+    }
+
+    /// The rows on screen at the current event: the latest snapshot at or
+    /// before it.
+    pub fn snapshot(&self) -> &[PortProcess] {
+        self.events[..=self.position.min(self.events.len().saturating_sub(1))]
+            .iter()
+            .rev()
+            .find_map(|(_, event)| match event {
+                Event::Snapshot(rows) => Some(rows.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn current(&self) -> Option<&(f64, Event)> {
+        self.events.get(self.position)
+    }
+
+    /// Move one event forward or back; false at either end.
+    pub fn step(&mut self, forward: bool) -> bool {
+        if forward && self.position + 1 < self.events.len() {
+            self.position += 1;
+        } else if !forward && self.position > 0 {
+            self.position -= 1;
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+fn parse_event(value: &Value) -> Option<(f64, Event)> {
+    let Value::Array(fields) = value else {
+        return None;
+    };
+    let [Value::Number(secs), Value::String(kind), data] = fields.as_slice() else {
+        return None;
+    };
+    let event = match kind.as_str() {
+        "snapshot" => {
+            let Value::Array(rows) = data else {
+                return None;
+            };
+            Event::Snapshot(rows.iter().map(parse_process).collect::<Option<_>>()?)
+        }
+        "key" => {
+            let key = data.get("key")?.as_str()?.to_string();
+            let pid = data.get("pid").and_then(Value::as_u64);
+            let port = data.get("port").and_then(Value::as_u64);
+            let selected = pid.zip(port).map(|(pid, port)| (pid as u32, port as u16));
+            Event::Key(key, selected)
+        }
+        "message" => Event::Message(data.as_str()?.to_string()),
+        _ => return None,
+    };
+    Some((*secs, event))
+}
+
+/// The inverse of `Value::from(&PortProcess)`.
+pub fn parse_process(value: &Value) -> Option<PortProcess> {
+    let text = |key| value.get(key).and_then(Value::as_str).map(str::to_string);
+    Some(PortProcess {
+        pid: match value.get("pid")? {
+            Value::Null => UNKNOWN_PID,
+            pid => pid.as_u64()? as u32,
+        },
+        port: value.get("port")?.as_u64()? as u16,
+        protocol: text("protocol")?,
+        name: text("name")?,
+        address: text("address")?,
+        listening: true,
+        user: text("user"),
+        nice: match value.get("nice") {
+            Some(Value::Number(n)) => Some(*n as i32),
+            _ => None,
+        },
+        connections: value
+            .get("connections")
+            .and_then(Value::as_u64)
+            .map(|n| n as usize),
+        context: text("context"),
+        tag: text("tag").and_then(|tag| TAGS.iter().copied().find(|t| *t == tag)),
+    })
+}
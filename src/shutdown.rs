@@ -0,0 +1,63 @@
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// The signal that asked rip to stop, or 0.
+#[cfg(unix)]
+static CAUGHT: AtomicI32 = AtomicI32::new(0);
+
+/// The signals caught, with their names as rip writes signals.
+#[cfg(unix)]
+const SIGNALS: &[(libc::c_int, &str)] = &[
+    (libc::SIGTERM, "TERM"),
+    (libc::SIGHUP, "HUP"),
+    (libc::SIGQUIT, "QUIT"),
+];
+
+/// Catch SIGTERM, SIGHUP and SIGQUIT so the TUI can leave the way `q`
+/// does, putting the terminal back and writing out what it keeps, instead
+/// of dying in raw mode on the alternate screen when a tab is closed or a
+/// tmux session killed. A second signal is not caught, so a rip that is
+/// stuck can still be stopped.
+#[cfg(unix)]
+pub fn install() {
+    for (signal, _) in SIGNALS {
+        // SAFETY: the handler only touches an atomic and calls
+        // async-signal-safe functions.
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(*signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+/// Note the signal, then wake the event loop: crossterm turns SIGWINCH into
+/// a resize event, and its wait for input retries on any other signal.
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    CAUGHT.store(signal, Ordering::SeqCst);
+    // SAFETY: getpid and kill are async-signal-safe.
+    unsafe {
+        libc::kill(libc::getpid(), libc::SIGWINCH);
+    }
+}
+
+/// The signal caught since `install`, if any, with its name.
+#[cfg(unix)]
+pub fn caught() -> Option<(i32, &'static str)> {
+    let caught = CAUGHT.load(Ordering::SeqCst);
+    SIGNALS
+        .iter()
+        .find(|(signal, _)| *signal == caught)
+        .map(|(signal, name)| (*signal, *name))
+}
+
+#[cfg(not(unix))]
+pub fn caught() -> Option<(i32, &'static str)> {
+    None
+}
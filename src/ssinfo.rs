@@ -0,0 +1,169 @@
+use std::process::Command;
+
+/// How many of a port's sockets the detail pane describes; a busy server
+/// has one per accepted connection.
+const MAX_SOCKETS: usize = 5;
+
+/// Congestion control modules, which `ss -i` prints as a bare word.
+const CONGESTION: [&str; 18] = [
+    "bbr",
+    "bbr2",
+    "bbr3",
+    "bic",
+    "cdg",
+    "cubic",
+    "dctcp",
+    "highspeed",
+    "htcp",
+    "hybla",
+    "illinois",
+    "lp",
+    "nv",
+    "reno",
+    "scalable",
+    "vegas",
+    "veno",
+    "westwood",
+];
+
+/// What the kernel knows about one socket beyond its address: the fields
+/// `ss -e -m -i -o` adds to a line.
+#[derive(Debug, Default)]
+pub struct SocketInfo {
+    pub state: String,
+    pub local: String,
+    pub peer: String,
+    pub recv_q: u64,
+    /// For a listener, the accept backlog.
+    pub send_q: u64,
+    /// `skmem:(r,rb,t,tb,...)`: receive and send queue memory and limits.
+    pub memory: Option<Memory>,
+    pub congestion: Option<String>,
+    pub cwnd: Option<u64>,
+    /// Smoothed round-trip time in milliseconds.
+    pub rtt: Option<String>,
+    /// `retrans:current/total`.
+    pub retrans: Option<String>,
+    pub timer: Option<Timer>,
+}
+
+/// `timer:(keepalive,23sec,0)`: which timer runs, when it fires and how
+/// many retransmits or probes it has sent.
+#[derive(Debug, Default)]
+pub struct Timer {
+    pub name: String,
+    pub expires: String,
+    pub retransmits: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Memory {
+    pub receive: u64,
+    pub receive_buffer: u64,
+    pub send: u64,
+    pub send_buffer: u64,
+}
+
+/// The sockets of `pid` on local port `port` (its listeners and, for TCP,
+/// the connections it accepted), from `ss -tuapn -e -m -i -o`. Linux only;
+/// empty when ss is missing. Also returns how many more there were than
+/// `MAX_SOCKETS`.
+pub fn sockets(pid: u32, port: u16) -> (Vec<SocketInfo>, usize) {
+    let sport = format!(":{}", port);
+    let Ok(output) = Command::new("ss")
+        .args(["-H", "-tuapn", "-e", "-m", "-i", "-o", "sport", "=", &sport])
+        .output()
+    else {
+        return (Vec::new(), 0);
+    };
+
+    let owner = format!("pid={},", pid);
+    let mut sockets: Vec<SocketInfo> = join_continuations(&String::from_utf8_lossy(&output.stdout))
+        .iter()
+        .filter(|line| line.contains(&owner))
+        .filter_map(|line| parse_line(line))
+        .collect();
+    // Listeners first.
+    sockets.sort_by_key(|s| !matches!(s.state.as_str(), "LISTEN" | "UNCONN"));
+    let more = sockets.len().saturating_sub(MAX_SOCKETS);
+    sockets.truncate(MAX_SOCKETS);
+    (sockets, more)
+}
+
+/// `-i` and `-m` put their fields on an indented second line.
+fn join_continuations(output: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in output.lines() {
+        match lines.last_mut() {
+            Some(last) if line.starts_with(char::is_whitespace) => {
+                last.push(' ');
+                last.push_str(line.trim());
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// `tcp LISTEN 0 5 0.0.0.0:4111 0.0.0.0:* users:(...) ino:415357 sk:d
+/// cgroup:/ <-> skmem:(r0,rb131072,...) bbr cwnd:10`
+fn parse_line(line: &str) -> Option<SocketInfo> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [_, state, recv_q, send_q, local, peer, rest @ ..] = fields.as_slice() else {
+        return None;
+    };
+    let mut info = SocketInfo {
+        state: state.to_string(),
+        local: local.to_string(),
+        peer: peer.to_string(),
+        recv_q: recv_q.parse().ok()?,
+        send_q: send_q.parse().ok()?,
+        ..SocketInfo::default()
+    };
+    for field in rest {
+        if CONGESTION.contains(field) {
+            info.congestion = Some(field.to_string());
+            continue;
+        }
+        let Some((key, value)) = field.split_once(':') else {
+            continue;
+        };
+        match key {
+            "skmem" => info.memory = parse_skmem(value),
+            "cwnd" => info.cwnd = value.parse().ok(),
+            "rtt" => info.rtt = value.split('/').next().map(str::to_string),
+            "retrans" => info.retrans = Some(value.to_string()),
+            "timer" => info.timer = parse_timer(value),
+            _ => {}
+        }
+    }
+    Some(info)
+}
+
+/// `(keepalive,23sec,0)`
+fn parse_timer(value: &str) -> Option<Timer> {
+    let mut parts = value.trim_matches(['(', ')']).split(',');
+    Some(Timer {
+        name: parts.next()?.to_string(),
+        expires: parts.next()?.to_string(),
+        retransmits: parts.next().unwrap_or("0").to_string(),
+    })
+}
+
+/// `(r0,rb131072,t0,tb16384,f0,w0,o0,bl0,d0)`
+fn parse_skmem(value: &str) -> Option<Memory> {
+    let mut memory = Memory::default();
+    for item in value.trim_matches(['(', ')']).split(',') {
+        let split = item.find(|c: char| c.is_ascii_digit())?;
+        let (key, number) = item.split_at(split);
+        let number = number.parse().ok()?;
+        match key {
+            "r" => memory.receive = number,
+            "rb" => memory.receive_buffer = number,
+            "t" => memory.send = number,
+            "tb" => memory.send_buffer = number,
+            _ => {}
+        }
+    }
+    Some(memory)
+}
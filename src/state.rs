@@ -0,0 +1,53 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Where rip keeps what it has learned about this machine:
+/// `$XDG_STATE_HOME/rip/state`, or `~/.local/state/rip/state`.
+pub fn path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(base.join("rip").join("state"))
+}
+
+/// `key=value` lines, in file order. A missing file is empty state.
+fn load() -> Vec<(String, String)> {
+    let Some(contents) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// The value recorded for `key`, if any.
+pub fn get(key: &str) -> Option<String> {
+    load()
+        .into_iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+/// Record `key=value`, replacing any previous value. Returns the file path.
+pub fn set(key: &str, value: &str) -> io::Result<PathBuf> {
+    let path = path().ok_or_else(|| io::Error::other(tr!("cannot find a home directory")))?;
+    let mut entries = load();
+    entries.retain(|(k, _)| k != key);
+    entries.push((key.to_string(), value.to_string()));
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let contents: String = entries
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect();
+    fs::write(&path, contents)?;
+    Ok(path)
+}
@@ -0,0 +1,46 @@
+/// OS services that listen on every machine of their platform and are
+/// rarely what anyone is looking for; hidden from the list until `v` (or
+/// `hide_system = false`) shows them.
+#[cfg(target_os = "macos")]
+const SERVICES: &[&str] = &[
+    "rapportd",
+    "ControlCenter",
+    "sharingd",
+    "identityservicesd",
+    "remoted",
+    "AirPlayXPCHelper",
+    "WiFiAgent",
+    "UserEventAgent",
+    "mDNSResponder",
+];
+#[cfg(target_os = "linux")]
+const SERVICES: &[&str] = &[
+    "systemd-resolved",
+    "systemd-networkd",
+    "systemd-timesyncd",
+    "avahi-daemon",
+    "cupsd",
+    "cups-browsed",
+    "chronyd",
+    "rpcbind",
+];
+#[cfg(windows)]
+const SERVICES: &[&str] = &[
+    "System",
+    "svchost.exe",
+    "lsass.exe",
+    "wininit.exe",
+    "services.exe",
+    "spoolsv.exe",
+];
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+const SERVICES: &[&str] = &[];
+
+/// Whether `name` is one of this platform's OS services. lsof cuts names
+/// to nine characters and `/proc` to fifteen, so a cut-off name that long
+/// matches too.
+pub fn is_os_service(name: &str) -> bool {
+    SERVICES
+        .iter()
+        .any(|service| *service == name || (name.len() >= 9 && service.starts_with(name)))
+}
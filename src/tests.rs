@@ -0,0 +1,1355 @@
+//! The TUI's state machine driven against a fake system: no lsof, no root
+//! and no real process is ever signalled. Then the parsers behind it, fed
+//! command output captured from real systems.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io;
+use std::rc::Rc;
+
+use crate::menu::Item;
+use crate::process::Signaller;
+use crate::scanner::{PortScanner, ScanStats};
+use crate::{App, PortProcess, cli, config};
+
+/// Above Linux's largest possible PID, so nothing rip looks up along the
+/// way (containers, working directories, risk) can match a real process.
+const PID: u32 = 5_000_000;
+
+/// The listeners the fake scanner reports, and what was sent to them.
+#[derive(Default)]
+struct Machine {
+    listeners: Vec<PortProcess>,
+    /// Every scan fails with this while set.
+    failure: Option<String>,
+    scans: usize,
+    /// Signals sent, as `(pid, signal)`.
+    sent: Vec<(u32, String)>,
+    /// Signals sent to process groups, as `(pgid, signal)`.
+    sent_to_groups: Vec<(u32, String)>,
+    /// PIDs whose signals fail, as if owned by someone else.
+    forbidden: HashSet<u32>,
+}
+
+#[derive(Clone, Default)]
+struct Fake(Rc<RefCell<Machine>>);
+
+impl PortScanner for Fake {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn scan(&self) -> io::Result<(Vec<PortProcess>, ScanStats)> {
+        let mut machine = self.0.borrow_mut();
+        machine.scans += 1;
+        if let Some(failure) = &machine.failure {
+            return Err(io::Error::other(failure.clone()));
+        }
+        let stats = ScanStats {
+            backend: "fake",
+            ..ScanStats::default()
+        };
+        Ok((machine.listeners.clone(), stats))
+    }
+}
+
+impl Signaller for Fake {
+    fn kill(&self, pid: u32) -> io::Result<()> {
+        self.signal(pid, "KILL")
+    }
+
+    fn signal(&self, pid: u32, signal: &str) -> io::Result<()> {
+        let mut machine = self.0.borrow_mut();
+        if machine.forbidden.contains(&pid) {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        }
+        machine.sent.push((pid, signal.to_string()));
+        machine.listeners.retain(|p| p.pid != pid);
+        Ok(())
+    }
+
+    fn signal_as_root(&self, pid: u32, signal: &str) -> io::Result<()> {
+        self.0.borrow_mut().forbidden.remove(&pid);
+        self.signal(pid, signal)
+    }
+
+    /// Each fake process leads a group of its own.
+    fn signal_group(&self, pgid: u32, signal: &str) -> io::Result<()> {
+        let mut machine = self.0.borrow_mut();
+        machine.sent_to_groups.push((pgid, signal.to_string()));
+        machine.listeners.retain(|p| p.pid != pgid);
+        Ok(())
+    }
+}
+
+impl Fake {
+    fn with(listeners: Vec<PortProcess>) -> Self {
+        let fake = Fake::default();
+        fake.0.borrow_mut().listeners = listeners;
+        fake
+    }
+
+    fn sent(&self) -> Vec<(u32, String)> {
+        self.0.borrow().sent.clone()
+    }
+}
+
+fn listener(pid: u32, port: u16, name: &str) -> PortProcess {
+    PortProcess {
+        pid,
+        port,
+        protocol: "TCP".to_string(),
+        name: name.to_string(),
+        address: "127.0.0.1".to_string(),
+        listening: true,
+        ..PortProcess::default()
+    }
+}
+
+fn app(fake: &Fake, config: config::Config) -> App {
+    let options = cli::TuiOptions {
+        low_power: Some(false),
+        ..cli::TuiOptions::default()
+    };
+    App::with(
+        options,
+        Ok(config),
+        Box::new(fake.clone()),
+        Box::new(fake.clone()),
+    )
+}
+
+/// Kills act on Enter, without a y/N question.
+fn unconfirmed() -> config::Config {
+    config::Config {
+        confirm_kill: false,
+        ..config::Config::default()
+    }
+}
+
+fn three() -> Fake {
+    Fake::with(vec![
+        listener(PID, 3000, "node"),
+        listener(PID + 1, 5173, "vite"),
+        listener(PID + 2, 8080, "java"),
+    ])
+}
+
+fn selected_port(app: &App) -> Option<u16> {
+    app.list_state.selected().map(|i| app.processes[i].port)
+}
+
+#[test]
+fn starts_on_the_first_row() {
+    let app = app(&three(), unconfirmed());
+    assert_eq!(app.processes.len(), 3);
+    assert_eq!(selected_port(&app), Some(3000));
+    assert_eq!(app.scan_stats.backend, "fake");
+}
+
+#[test]
+fn next_and_previous_wrap_around() {
+    let mut app = app(&three(), unconfirmed());
+    app.next();
+    assert_eq!(selected_port(&app), Some(5173));
+    app.next();
+    app.next();
+    assert_eq!(selected_port(&app), Some(3000));
+    app.previous();
+    assert_eq!(selected_port(&app), Some(8080));
+}
+
+#[test]
+fn navigating_an_empty_list_selects_nothing() {
+    let mut app = app(&Fake::default(), unconfirmed());
+    app.next();
+    app.previous();
+    assert_eq!(app.list_state.selected(), None);
+}
+
+#[test]
+fn selection_stays_on_its_socket_when_rows_appear_above_it() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.next();
+    fake.0
+        .borrow_mut()
+        .listeners
+        .insert(0, listener(PID + 3, 80, "nginx"));
+    app.refresh_processes();
+    assert_eq!(selected_port(&app), Some(5173));
+}
+
+#[test]
+fn selection_moves_up_when_the_list_shrinks_below_it() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.previous();
+    assert_eq!(selected_port(&app), Some(8080));
+    fake.0.borrow_mut().listeners.truncate(1);
+    app.refresh_processes();
+    assert_eq!(selected_port(&app), Some(3000));
+}
+
+#[test]
+fn an_empty_scan_is_believed_once_a_second_one_agrees() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    let scans = fake.0.borrow().scans;
+    fake.0.borrow_mut().listeners.clear();
+    app.refresh_processes();
+    assert_eq!(fake.0.borrow().scans, scans + 2);
+    assert!(app.processes.is_empty());
+    assert_eq!(app.list_state.selected(), None);
+}
+
+#[test]
+fn a_failing_scan_keeps_the_last_list() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    fake.0.borrow_mut().failure = Some("lsof vanished".to_string());
+    app.refresh_processes();
+    assert_eq!(app.processes.len(), 3);
+    assert!(app.stale.is_some());
+    assert!(app.message.as_deref().unwrap().contains("lsof vanished"));
+
+    fake.0.borrow_mut().failure = None;
+    app.refresh_processes();
+    assert!(app.stale.is_none());
+}
+
+#[test]
+fn kill_sends_the_configured_signal_and_rescans() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.next();
+    app.kill_selected();
+    assert_eq!(fake.sent(), vec![(PID + 1, "TERM".to_string())]);
+    assert_eq!(app.processes.len(), 2);
+    assert_eq!(selected_port(&app), Some(8080));
+    assert_eq!(
+        app.message.as_deref(),
+        Some("Sent SIGTERM to vite (PID: 5000001)")
+    );
+}
+
+#[test]
+fn kill_of_the_last_row_selects_the_new_last_one() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.previous();
+    app.kill_selected();
+    assert_eq!(selected_port(&app), Some(5173));
+}
+
+#[test]
+fn kill_reports_a_port_still_held_after_sigkill() {
+    let fake = Fake::with(vec![
+        listener(PID, 3000, "node"),
+        listener(PID + 1, 3000, "node"),
+    ]);
+    let config = config::Config {
+        signal: "KILL".to_string(),
+        ..unconfirmed()
+    };
+    let mut app = app(&fake, config);
+    app.kill_selected();
+    assert_eq!(fake.sent(), vec![(PID, "KILL".to_string())]);
+    assert_eq!(
+        app.message.as_deref(),
+        Some("Killed node (PID: 5000000), but :3000 is still held by node (PID: 5000001)")
+    );
+}
+
+#[test]
+fn a_failed_kill_leaves_the_list_alone() {
+    let fake = three();
+    fake.0.borrow_mut().forbidden.insert(PID);
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected();
+    assert_eq!(app.processes.len(), 3);
+    assert!(
+        app.message
+            .as_deref()
+            .unwrap()
+            .starts_with("Failed to kill PID 5000000")
+    );
+}
+
+#[test]
+fn kill_asks_first_when_the_config_says_so() {
+    let fake = three();
+    let mut app = app(&fake, config::Config::default());
+    app.request_kill(crate::KillAction::Kill);
+    assert!(app.pending_kill.is_some());
+    assert!(fake.sent().is_empty());
+}
+
+#[test]
+fn never_kill_is_refused_without_signalling() {
+    let fake = three();
+    let config = config::Config {
+        never_kill: vec!["node".to_string()],
+        ..unconfirmed()
+    };
+    let mut app = app(&fake, config);
+    app.request_kill(crate::KillAction::Kill);
+    assert!(app.pending_kill.is_none());
+    assert!(fake.sent().is_empty());
+    assert_eq!(app.processes.len(), 3);
+}
+
+#[test]
+fn marked_rows_are_killed_together() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.toggle_mark();
+    app.next();
+    app.toggle_mark();
+    app.request_kill(crate::KillAction::Kill);
+    assert!(app.pending_kill.is_none());
+    let pids: Vec<u32> = fake.sent().into_iter().map(|(pid, _)| pid).collect();
+    assert_eq!(pids, vec![PID, PID + 2]);
+    assert_eq!(app.processes.len(), 1);
+    assert!(app.marked.is_empty());
+}
+
+#[test]
+fn the_menu_runs_its_entry_on_the_row_it_was_opened_for() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.next();
+    app.open_menu();
+    let menu = app.menu.as_mut().unwrap();
+    menu.selected = menu
+        .items
+        .iter()
+        .position(|item| *item == Item::Reload)
+        .unwrap();
+    // A refresh while the menu is open moves the row down.
+    fake.0
+        .borrow_mut()
+        .listeners
+        .insert(0, listener(PID + 3, 80, "nginx"));
+    app.refresh_processes();
+    crate::run_menu_item(&mut app);
+    assert!(app.menu.is_none());
+    assert_eq!(fake.sent(), vec![(PID + 1, "HUP".to_string())]);
+}
+
+#[test]
+fn the_menu_offers_no_signals_for_an_unknown_owner() {
+    let fake = Fake::with(vec![listener(crate::UNKNOWN_PID, 3000, "?")]);
+    let mut app = app(&fake, unconfirmed());
+    app.open_menu();
+    let items = &app.menu.as_ref().unwrap().items;
+    assert!(!items.contains(&Item::Kill));
+    assert!(!items.contains(&Item::Protect));
+    assert!(!items.contains(&Item::NeverKill));
+    assert!(items.contains(&Item::HidePort));
+}
+
+#[test]
+fn never_kill_from_the_menu_refuses_later_kills() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.open_menu();
+    let menu = app.menu.as_mut().unwrap();
+    menu.selected = menu
+        .items
+        .iter()
+        .position(|item| *item == Item::NeverKill)
+        .unwrap();
+    crate::run_menu_item(&mut app);
+    assert_eq!(app.config.never_kill, ["node"]);
+    app.request_kill(crate::KillAction::Kill);
+    assert!(fake.sent().is_empty());
+    app.open_menu();
+    assert!(app.menu.as_ref().unwrap().items.contains(&Item::AllowKill));
+}
+
+#[test]
+fn the_settings_open_from_the_menu() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.open_menu();
+    let menu = app.menu.as_mut().unwrap();
+    assert_eq!(menu.items.last(), Some(&Item::Settings));
+    menu.selected = menu.items.len() - 1;
+    crate::run_menu_item(&mut app);
+    assert!(app.menu.is_none());
+    assert!(app.view == crate::View::Settings);
+}
+
+#[test]
+fn kubectl_is_stopped_with_sigint_like_ctrl_c() {
+    let fake = Fake::with(vec![listener(PID, 8080, "kubectl")]);
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected();
+    assert_eq!(fake.sent(), vec![(PID, "INT".to_string())]);
+}
+
+#[test]
+fn graceful_signals_match_whole_names_and_the_config_comes_first() {
+    let fake = Fake::with(vec![
+        listener(PID, 80, "nginx-exporter"),
+        listener(PID + 1, 9292, "puma"),
+    ]);
+    let config = config::Config {
+        graceful: vec![("puma".to_string(), "INT".to_string())],
+        ..unconfirmed()
+    };
+    let mut app = app(&fake, config);
+    app.kill_selected();
+    app.kill_selected();
+    assert_eq!(
+        fake.sent(),
+        vec![(PID, "TERM".to_string()), (PID + 1, "INT".to_string())]
+    );
+}
+
+#[test]
+fn the_tree_kill_signals_the_selected_process_last() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.toggle_tree();
+    let tree = app.tree.as_ref().unwrap();
+    assert!(tree.nodes.iter().any(|n| n.pid == PID && !n.ancestor));
+    app.request_kill(crate::KillAction::Tree);
+    assert!(app.tree.is_none());
+    assert_eq!(fake.sent(), vec![(PID, "TERM".to_string())]);
+    assert_eq!(
+        app.message.as_deref(),
+        Some("Killed node (PID: 5000000), which started no other processes")
+    );
+}
+
+#[test]
+fn a_group_kill_is_refused_without_a_process_group() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.request_kill(crate::KillAction::Group);
+    assert!(fake.0.borrow().sent_to_groups.is_empty());
+    assert_eq!(
+        app.message.as_deref(),
+        Some("node (PID: 5000000) has no process group rip can signal")
+    );
+}
+
+#[test]
+fn kills_are_kept_in_the_history_and_listed_newest_first() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected();
+    app.kill_selected();
+    let names: Vec<&str> = app.history.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["node", "vite"]);
+
+    app.toggle_history();
+    assert_eq!(app.history_popup, Some(0));
+    // No command line could be read for the fake process.
+    app.relaunch_selected();
+    assert!(app.history_popup.is_none());
+    assert!(
+        app.message
+            .as_deref()
+            .unwrap()
+            .starts_with("Could not relaunch vite")
+    );
+}
+
+#[test]
+fn a_victim_still_running_from_a_relaunch_is_not_relaunched_again() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected();
+    // This test's own process stands in for the relaunched copy.
+    let running = std::process::id();
+    app.history[0].relaunched = Some(running);
+    app.toggle_history();
+    app.relaunch_selected();
+    assert_eq!(
+        app.message.as_deref(),
+        Some(
+            format!(
+                "node is already relaunched as PID {}; kill that first",
+                running
+            )
+            .as_str()
+        )
+    );
+    assert_eq!(app.history_popup, Some(0));
+}
+
+#[test]
+fn rip_port_refuses_protected_processes() {
+    let fake = Fake::default();
+    let config = config::Config {
+        protect: vec![":6443".to_string()],
+        ..config::Config::default()
+    };
+    let holders = vec![
+        listener(PID, 22, "sshd"),
+        listener(PID + 1, 6443, "kube-apiserver"),
+    ];
+    assert!(!crate::free_holders(holders, &config, &fake));
+    assert!(fake.sent().is_empty());
+}
+
+#[test]
+fn only_a_real_supervisor_parent_raises_the_risk() {
+    let supervised = |parent_pid: u32, parent: &str| {
+        let table = [
+            (PID, (parent_pid, "node".to_string())),
+            (parent_pid, (1, parent.to_string())),
+        ]
+        .into_iter()
+        .collect();
+        let risks =
+            crate::risk::assess_with(&[listener(PID, 3000, "node")], &unconfirmed(), &table);
+        risks[&PID].protected
+    };
+    assert!(supervised(4242, "pm2"));
+    assert!(supervised(4242, "/usr/bin/supervisord"));
+    assert!(supervised(4242, "systemd"), "a user manager supervises");
+    assert!(!supervised(1, "systemd"), "init is everyone's parent");
+    assert!(!supervised(1, "launchd"));
+    assert!(!supervised(4242, "systemd-journald"));
+    assert!(!supervised(4242, "pm2-dev"));
+    assert!(!supervised(4242, "zsh"));
+}
+
+#[test]
+fn busy_counts_the_connections_on_every_port_of_a_process() {
+    let serving = |pid, port, connections| PortProcess {
+        connections: Some(connections),
+        ..listener(pid, port, "node")
+    };
+    let rows = [
+        serving(PID, 3000, 3),
+        serving(PID, 3001, 3),
+        serving(PID + 1, 4000, 4),
+    ];
+    let risks = crate::risk::assess_with(&rows, &unconfirmed(), &Default::default());
+    assert_eq!(risks[&PID].reasons, vec!["6 live connections"]);
+    assert!(risks[&(PID + 1)].reasons.is_empty());
+}
+
+#[test]
+fn the_server_only_kills_unprotected_listed_pids() {
+    let fake = Fake::with(vec![
+        listener(PID, 22, "sshd"),
+        listener(PID + 1, 3000, "node"),
+        listener(crate::UNKNOWN_PID, 4000, "?"),
+    ]);
+    let kill = |pid, safe| crate::rpc::kill_listed(pid, safe, &unconfirmed(), &fake, &fake);
+    for pid in [PID, PID + 2, crate::UNKNOWN_PID] {
+        assert!(kill(pid, false).is_err(), "PID {} was not refused", pid);
+    }
+    let dry_run = kill(PID + 1, true).unwrap();
+    assert_eq!(
+        dry_run.get("dry_run"),
+        Some(&crate::json::Value::Bool(true))
+    );
+    assert!(fake.sent().is_empty());
+}
+
+#[test]
+fn a_graceful_kill_is_remembered_once_the_process_is_gone() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected_gracefully();
+    assert_eq!(fake.sent(), vec![(PID, "TERM".to_string())]);
+    assert!(app.history.is_empty());
+    // The fake's PIDs never run, so the first poll finds it exited.
+    assert!(app.poll_escalation());
+    assert!(app.escalation.is_none());
+    assert_eq!(app.history.len(), 1);
+}
+
+#[test]
+fn a_refused_graceful_kill_is_not_remembered() {
+    let fake = three();
+    fake.0.borrow_mut().forbidden.insert(PID);
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected_gracefully();
+    assert!(app.escalation.is_none());
+    assert!(app.history.is_empty());
+    assert!(
+        app.message
+            .as_deref()
+            .unwrap()
+            .starts_with("Failed to stop node (PID: 5000000)")
+    );
+}
+
+#[test]
+fn a_reload_is_not_a_kill() {
+    let fake = three();
+    let mut app = app(&fake, unconfirmed());
+    app.request_kill(crate::KillAction::Signal("HUP"));
+    assert_eq!(fake.sent(), vec![(PID, "HUP".to_string())]);
+    assert!(app.history.is_empty());
+}
+
+#[test]
+fn a_refused_kill_can_be_sent_again_with_sudo() {
+    let fake = three();
+    fake.0.borrow_mut().forbidden.insert(PID);
+    let mut app = app(&fake, unconfirmed());
+    app.kill_selected();
+    assert!(fake.sent().is_empty());
+    let kill = app.pending_sudo.clone().expect("sudo is offered");
+    crate::handle_key(&mut app, crossterm::event::KeyCode::Char('y'));
+    assert_eq!(app.wants_sudo_kill.take(), Some(kill.clone()));
+    // What the main loop does once sudo has the terminal.
+    app.sudo_kill(kill.clone());
+    assert_eq!(fake.sent(), vec![(PID, kill.signal)]);
+    assert_eq!(app.processes.len(), 2);
+}
+
+#[test]
+fn connection_counts_survive_a_trip_through_json() {
+    let busy = PortProcess {
+        connections: Some(12),
+        ..listener(PID, 3000, "node")
+    };
+    let udp = PortProcess {
+        protocol: "UDP".to_string(),
+        ..listener(PID + 1, 5353, "mdns")
+    };
+    for process in [busy, udp] {
+        let parsed = crate::session::parse_process(&crate::json::Value::from(&process)).unwrap();
+        assert_eq!(parsed.connections, process.connections);
+    }
+}
+
+/// A listener on every interface, which the firewall decides about.
+fn exposed(port: u16, protocol: &str) -> PortProcess {
+    PortProcess {
+        protocol: protocol.to_string(),
+        address: "*".to_string(),
+        ..listener(PID, port, "server")
+    }
+}
+
+#[test]
+fn iptables_follows_ufw_chains_from_input() {
+    use crate::firewall::{Verdict, parse_iptables};
+
+    let rules = parse_iptables(
+        "-P INPUT DROP\n\
+         -P FORWARD DROP\n\
+         -P OUTPUT ACCEPT\n\
+         -N ufw-before-input\n\
+         -N ufw-user-input\n\
+         -A INPUT -j ufw-before-input\n\
+         -A OUTPUT -p tcp -m tcp --dport 25 -j ACCEPT\n\
+         -A FORWARD -p tcp -m tcp --dport 8080 -j ACCEPT\n\
+         -A ufw-before-input -i lo -j ACCEPT\n\
+         -A ufw-before-input -m conntrack --ctstate RELATED,ESTABLISHED -j ACCEPT\n\
+         -A ufw-before-input -j ufw-user-input\n\
+         -A ufw-user-input -p tcp -m tcp --dport 22 -j ACCEPT\n\
+         -A ufw-user-input -s 10.0.0.0/8 -p tcp -m tcp --dport 5432 -j ACCEPT\n\
+         -A ufw-user-input -p udp -m multiport --dports 60000:61000 -j ACCEPT\n",
+    );
+    let verdict = |port, protocol| rules.verdict(&exposed(port, protocol));
+    assert_eq!(verdict(22, "TCP"), Verdict::Allowed);
+    assert_eq!(verdict(60500, "UDP"), Verdict::Allowed);
+    assert_eq!(verdict(60500, "TCP"), Verdict::Blocked);
+    // Other chains, and rules for some sources only, decide nothing.
+    assert_eq!(verdict(25, "TCP"), Verdict::Blocked);
+    assert_eq!(verdict(8080, "TCP"), Verdict::Blocked);
+    assert_eq!(verdict(5432, "TCP"), Verdict::Blocked);
+}
+
+#[test]
+fn nftables_reads_input_filter_chains_and_their_jumps() {
+    use crate::firewall::{Verdict, parse_nft};
+
+    let rules = parse_nft(
+        "table inet filter {\n\
+         \tchain input {\n\
+         \t\ttype filter hook input priority filter; policy drop;\n\
+         \t\tct state established,related accept\n\
+         \t\tiifname \"lo\" accept\n\
+         \t\ttcp dport { 80, 443 } jump web\n\
+         \t\tip saddr 10.0.0.0/8 tcp dport 5432 accept\n\
+         \t\tudp dport 53 accept\n\
+         \t}\n\
+         \tchain web {\n\
+         \t\ttcp dport 443 accept\n\
+         \t}\n\
+         \tchain forward {\n\
+         \t\ttype filter hook forward priority filter; policy drop;\n\
+         \t\ttcp dport 8080 accept\n\
+         \t}\n\
+         }\n\
+         table bridge guests {\n\
+         \tchain input {\n\
+         \t\ttype filter hook input priority filter; policy accept;\n\
+         \t\ttcp dport 8080 accept\n\
+         \t}\n\
+         }\n",
+    );
+    let verdict = |port, protocol| rules.verdict(&exposed(port, protocol));
+    assert_eq!(verdict(443, "TCP"), Verdict::Allowed);
+    assert_eq!(verdict(53, "UDP"), Verdict::Allowed);
+    // Jumped to, but the chain has nothing for it.
+    assert_eq!(verdict(80, "TCP"), Verdict::Blocked);
+    assert_eq!(verdict(5432, "TCP"), Verdict::Blocked);
+    assert_eq!(verdict(8080, "TCP"), Verdict::Blocked);
+}
+
+/// `(pid, port, protocol, address, listening)` of each row.
+fn summary(rows: &[PortProcess]) -> Vec<(u32, u16, &str, &str, bool)> {
+    rows.iter()
+        .map(|p| {
+            let (protocol, address) = (p.protocol.as_str(), p.address.as_str());
+            (p.pid, p.port, protocol, address, p.listening)
+        })
+        .collect()
+}
+
+#[test]
+fn lsof_output_is_parsed_line_by_line() {
+    let (mut rows, mut unparsed) = (Vec::new(), 0);
+    crate::scanner::parse_lsof(
+        "COMMAND   PID   USER   FD   TYPE DEVICE SIZE/OFF NODE NAME\n\
+         python3   120 nobody    3u  IPv4   1137      0t0  TCP 127.0.0.1:48271 (LISTEN)\n\
+         python3  7662   root    3u  IPv6 910687      0t0  TCP [::1]:8080 (LISTEN)\n\
+         node     4242  alice   21u  IPv4  55120      0t0  TCP 127.0.0.1:3000->127.0.0.1:51234 (ESTABLISHED)\n\
+         dnsmasq   880 nobody    4u  IPv4  20311      0t0  UDP *:53\n\
+         python3   120 nobody    3u  IPv4   1137      0t0  TCP\n\
+         python3   abc   root    3u  IPv4   1137      0t0  TCP *:80 (LISTEN)\n",
+        &mut rows,
+        &mut unparsed,
+    );
+    assert_eq!(
+        summary(&rows),
+        vec![
+            (120, 48271, "TCP", "127.0.0.1", true),
+            (7662, 8080, "TCP", "[::1]", true),
+            (4242, 3000, "TCP", "127.0.0.1", false),
+            (880, 53, "UDP", "*", true),
+        ]
+    );
+    assert_eq!(rows[0].user.as_deref(), Some("nobody"));
+    // One line cut short, one with a PID that is not a number.
+    assert_eq!(unparsed, 2);
+}
+
+#[test]
+fn ss_output_gives_a_row_per_owner() {
+    let (mut rows, mut unparsed, mut unowned) = (Vec::new(), 0, 0);
+    crate::backend::parse_ss(
+        "tcp LISTEN 0 5 0.0.0.0:18082 0.0.0.0:* users:((\"python3\",pid=22565,fd=3))\n\
+         tcp LISTEN 0 128 [::1]:8080 [::]:* users:((\"node\",pid=4242,fd=21),(\"node\",pid=4243,fd=21))\n\
+         udp UNCONN 0 0 127.0.0.53%lo:53 0.0.0.0:* users:((\"systemd-resolve\",pid=880,fd=13))\n\
+         tcp LISTEN 0 128 0.0.0.0:2024 0.0.0.0:*\n\
+         tcp LISTEN 0 128 [::]:22\n\
+         tcp LISTEN 0 128 0.0.0.0:http 0.0.0.0:*\n",
+        &mut rows,
+        &mut unparsed,
+        &mut unowned,
+    );
+    assert_eq!(
+        summary(&rows),
+        vec![
+            (22565, 18082, "TCP", "*", true),
+            (4242, 8080, "TCP", "[::1]", true),
+            (4243, 8080, "TCP", "[::1]", true),
+            (880, 53, "UDP", "127.0.0.53", true),
+        ]
+    );
+    assert_eq!(rows[3].name, "systemd-resolve");
+    // Another user's socket, which ss shows without `users:`.
+    assert_eq!(unowned, 1);
+    // A line cut short and a service name where the port should be.
+    assert_eq!(unparsed, 2);
+}
+
+#[test]
+fn netstat_output_skips_headers_and_unnamed_owners() {
+    let (mut rows, mut unparsed, mut unowned) = (Vec::new(), 0, 0);
+    crate::backend::parse_netstat(
+        "Active Internet connections (only servers)\n\
+         Proto Recv-Q Send-Q Local Address           Foreign Address         State       PID/Program name\n\
+         tcp        0      0 0.0.0.0:2024            0.0.0.0:*               LISTEN      -\n\
+         tcp        0      0 127.0.0.1:48271         0.0.0.0:*               LISTEN      120/python3\n\
+         tcp6       0      0 ::1:8080                :::*                    LISTEN      4242/node\n\
+         tcp6       0      0 :::22                   :::*                    LISTEN      1/systemd\n\
+         udp        0      0 127.0.0.53:53           0.0.0.0:*                           880/systemd-resolve\n\
+         tcp        0      0 0.0.0.0:80\n\
+         tcp        0      0 0.0.0.0:bad             0.0.0.0:*               LISTEN      7/x\n",
+        &mut rows,
+        &mut unparsed,
+        &mut unowned,
+    );
+    assert_eq!(
+        summary(&rows),
+        vec![
+            (120, 48271, "TCP", "127.0.0.1", true),
+            (4242, 8080, "TCP", "[::1]", true),
+            (1, 22, "TCP", "*", true),
+            (880, 53, "UDP", "127.0.0.53", true),
+        ]
+    );
+    assert_eq!(unowned, 1);
+    assert_eq!(unparsed, 2);
+}
+
+#[test]
+fn ss_and_netstat_read_ipv6_addresses() {
+    let ss = |local: &str| {
+        let (mut rows, mut unparsed, mut unowned) = (Vec::new(), 0, 0);
+        let line = format!(
+            "tcp LISTEN 0 128 {} [::]:* users:((\"node\",pid=4242,fd=21))\n",
+            local
+        );
+        crate::backend::parse_ss(&line, &mut rows, &mut unparsed, &mut unowned);
+        rows.first().map(|p| (p.address.clone(), p.port))
+    };
+    let netstat = |local: &str| {
+        let (mut rows, mut unparsed, mut unowned) = (Vec::new(), 0, 0);
+        let line = format!("tcp6 0 0 {} :::* LISTEN 4242/node\n", local);
+        crate::backend::parse_netstat(&line, &mut rows, &mut unparsed, &mut unowned);
+        rows.first().map(|p| (p.address.clone(), p.port))
+    };
+    let expect = |address: &str, port| Some((address.to_string(), port));
+
+    let ss_cases = [
+        ("[::1]:8080", expect("[::1]", 8080)),
+        ("[::]:8080", expect("*", 8080)),
+        ("*:8080", expect("*", 8080)),
+        ("[::1]%lo:8080", expect("[::1]", 8080)),
+        ("[fe80::1%eth0]:8080", expect("[fe80::1]", 8080)),
+        (
+            "[::ffff:127.0.0.1]:8080",
+            expect("[::ffff:127.0.0.1]", 8080),
+        ),
+        ("[::1]:http", None),
+    ];
+    for (local, address) in ss_cases {
+        assert_eq!(ss(local), address, "ss {}", local);
+    }
+    let netstat_cases = [
+        ("::1:8080", expect("[::1]", 8080)),
+        (":::8080", expect("*", 8080)),
+        ("fe80::1%eth0:8080", expect("[fe80::1]", 8080)),
+        ("::ffff:127.0.0.1:8080", expect("[::ffff:127.0.0.1]", 8080)),
+        (":::http", None),
+    ];
+    for (local, address) in netstat_cases {
+        assert_eq!(netstat(local), address, "netstat {}", local);
+    }
+}
+
+/// `/proc/net` prints addresses as native-endian words; these fixtures are
+/// from a little-endian machine.
+#[cfg(target_endian = "little")]
+#[test]
+fn proc_net_addresses_and_tables_are_decoded() {
+    use crate::procfs::{count_established, parse_address, parse_table};
+
+    let address = |field| parse_address(field);
+    assert_eq!(
+        address("0100007F:1F90"),
+        Some(("127.0.0.1".to_string(), 8080))
+    );
+    assert_eq!(address("00000000:0050"), Some(("*".to_string(), 80)));
+    assert_eq!(
+        address("00000000000000000000000001000000:1F90"),
+        Some(("[::1]".to_string(), 8080))
+    );
+    assert_eq!(address("0100007F"), None);
+    assert_eq!(address("0100007G:1F90"), None);
+    assert_eq!(address("0100:1F90"), None);
+
+    let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+        \x20  0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 662 1 0000000000000000 100 0 0 10 0\n\
+        \x20  1: 0100007F:1F90 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 700 1 0000000000000000 20 4 30 10 -1\n\
+        \x20  2: 0100007F:1F90 0100007F:C351 01 00000000:00000000 00:00000000 00000000  1000        0 701 1 0000000000000000 20 4 30 10 -1\n\
+        \x20  3: 0100007F:0016 00000000:0000 0A 00000000:00000000\n";
+    let sockets = parse_table(table, "TCP");
+    assert_eq!(sockets.len(), 1);
+    let socket = &sockets[0];
+    assert_eq!(
+        (
+            socket.address.as_str(),
+            socket.port,
+            socket.uid,
+            socket.inode
+        ),
+        ("127.0.0.1", 8080, 1000, 662)
+    );
+
+    let mut counts = std::collections::HashMap::new();
+    count_established(table, &mut counts);
+    assert_eq!(counts.get(&("127.0.0.1".to_string(), 8080)), Some(&2));
+    assert_eq!(counts.len(), 1);
+}
+
+#[test]
+fn netstat_established_connections_are_counted_per_address() {
+    use crate::enrich::count_netstat_established;
+
+    let mut counts = std::collections::HashMap::new();
+    // macOS.
+    count_netstat_established(
+        "Active Internet connections (including servers)\n\
+         Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)\n\
+         tcp4       0      0  127.0.0.1.3000         127.0.0.1.50112        ESTABLISHED\n\
+         tcp4       0      0  127.0.0.1.50112        127.0.0.1.3000         ESTABLISHED\n\
+         tcp46      0      0  *.3000                 *.*                    LISTEN\n\
+         tcp6       0      0  ::1.3000               ::1.50113              ESTABLISHED\n\
+         tcp6       0      0  fe80::1%lo0.3000       fe80::1%lo0.50114      ESTABLISHED\n\
+         udp4       0      0  127.0.0.1.53           127.0.0.1.50115        ESTABLISHED\n",
+        &mut counts,
+    );
+    // Windows.
+    count_netstat_established(
+        "Active Connections\n\
+         \n\
+         \x20 Proto  Local Address          Foreign Address        State\n\
+         \x20 TCP    192.168.1.5:3000       192.168.1.9:50200      ESTABLISHED\n\
+         \x20 TCP    [::1]:3000             [::1]:50201            ESTABLISHED\n\
+         \x20 TCP    0.0.0.0:3000           0.0.0.0:0              LISTENING\n",
+        &mut counts,
+    );
+    let count = |address: &str, port| counts.get(&(address.to_string(), port)).copied();
+    assert_eq!(count("127.0.0.1", 3000), Some(1));
+    assert_eq!(count("127.0.0.1", 50112), Some(1));
+    assert_eq!(count("[::1]", 3000), Some(2));
+    assert_eq!(count("[fe80::1]", 3000), Some(1));
+    assert_eq!(count("192.168.1.5", 3000), Some(1));
+    assert_eq!(count("127.0.0.1", 53), None);
+    assert_eq!(counts.len(), 5);
+}
+
+#[test]
+fn connections_go_to_the_listener_bound_to_their_address() {
+    use crate::enrich::accepted;
+
+    let counts = [
+        (("127.0.0.1".to_string(), 3000), 2),
+        (("192.168.1.5".to_string(), 3000), 3),
+        (("[::1]".to_string(), 3000), 5),
+        (("127.0.0.1".to_string(), 4000), 7),
+    ]
+    .into_iter()
+    .collect();
+    let bound = |address: &str| PortProcess {
+        address: address.to_string(),
+        ..listener(PID, 3000, "node")
+    };
+    let listeners = vec![
+        ("127.0.0.1".to_string(), 3000),
+        ("0.0.0.0".to_string(), 3000),
+        ("[::]".to_string(), 3000),
+    ];
+    assert_eq!(accepted(&bound("127.0.0.1"), &listeners, &counts), 2);
+    assert_eq!(accepted(&bound("0.0.0.0"), &listeners, &counts), 3);
+    assert_eq!(accepted(&bound("[::]"), &listeners, &counts), 5);
+    assert_eq!(accepted(&bound("*"), &listeners, &counts), 8);
+    assert_eq!(accepted(&bound("10.0.0.1"), &listeners, &counts), 0);
+}
+
+#[test]
+fn json_round_trips_what_it_writes() {
+    use crate::json::{Value, parse};
+
+    let value = Value::object([
+        ("quote", Value::from("say \"hi\" \\ bye")),
+        ("control", Value::from("tab\there\nnewline\u{1}")),
+        ("emoji", Value::from("ports 😀 ü")),
+        ("number", Value::from(-12i64)),
+        (
+            "list",
+            Value::Array(vec![Value::Null, Value::from(true), Value::Array(vec![])]),
+        ),
+        ("empty", Value::object::<&str>([])),
+    ]);
+    assert_eq!(parse(&value.to_string()), Ok(value));
+}
+
+#[test]
+fn json_escapes_are_decoded_strictly() {
+    use crate::json::{Value, parse};
+
+    let string = |json| parse(json).map(|v| v.as_str().map(str::to_string));
+    assert_eq!(string(r#""\u00e9\/\b""#), Ok(Some("é/\u{8}".to_string())));
+    assert_eq!(string(r#""\uD83D\uDE00""#), Ok(Some("😀".to_string())));
+    assert_eq!(
+        string(r#""\u+041""#),
+        Err("invalid unicode escape at offset 2".to_string())
+    );
+    assert_eq!(
+        string(r#""\u12""#),
+        Err("invalid unicode escape at offset 2".to_string())
+    );
+    assert_eq!(
+        string(r#""\uD83D""#),
+        Err("unpaired surrogate at offset 2".to_string())
+    );
+    assert_eq!(
+        string(r#""\uD83Dx""#),
+        Err("unpaired surrogate at offset 2".to_string())
+    );
+    assert_eq!(
+        string(r#""\uDE00""#),
+        Err("unpaired surrogate at offset 2".to_string())
+    );
+    assert_eq!(
+        parse(r#""\x""#),
+        Err("invalid escape '\\x' at offset 2".to_string())
+    );
+    assert_eq!(parse(r#"["a"]"#), Ok(Value::Array(vec![Value::from("a")])));
+}
+
+#[test]
+fn json_errors_say_where() {
+    use crate::json::parse;
+
+    assert_eq!(parse("[1,]"), Err("unexpected ']' at offset 3".to_string()));
+    assert_eq!(
+        parse(r#"{"a" 1}"#),
+        Err("expected ':' at offset 5, found '1'".to_string())
+    );
+    assert_eq!(
+        parse("[1 2]"),
+        Err("expected ',' or ']' at offset 3, found '2'".to_string())
+    );
+    assert_eq!(
+        parse("[1] x"),
+        Err("trailing characters at offset 4".to_string())
+    );
+    assert_eq!(
+        parse("1.2.3"),
+        Err("invalid number at offset 0".to_string())
+    );
+    assert_eq!(parse(r#"{"a":1"#), Err("unterminated object".to_string()));
+    assert_eq!(parse(r#""abc"#), Err("unterminated string".to_string()));
+    assert_eq!(parse(""), Err("unexpected end of input".to_string()));
+}
+
+#[test]
+fn bind_conflicts_pair_addresses_of_one_family() {
+    use crate::conflict::find_bind_conflicts;
+
+    let bound = |pid, address: &str| PortProcess {
+        address: address.to_string(),
+        ..listener(pid, 3000, "node")
+    };
+    let pids = |processes: &[PortProcess]| {
+        find_bind_conflicts(processes)
+            .iter()
+            .map(|c| {
+                let pids = |side: &[PortProcess]| side.iter().map(|p| p.pid).collect::<Vec<_>>();
+                (pids(&c.wildcard), pids(&c.specific))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        pids(&[bound(1, "0.0.0.0"), bound(2, "10.0.0.5")]),
+        vec![(vec![1], vec![2])]
+    );
+    assert_eq!(pids(&[bound(1, "0.0.0.0"), bound(2, "[::1]")]), vec![]);
+    assert_eq!(pids(&[bound(1, "[::]"), bound(2, "127.0.0.1")]), vec![]);
+    assert_eq!(
+        pids(&[bound(1, "0.0.0.0"), bound(2, "[::]"), bound(3, "[::1]")]),
+        vec![(vec![2], vec![3])]
+    );
+    // lsof's `*` could be either family.
+    assert_eq!(
+        pids(&[bound(1, "*"), bound(2, "[::1]")]),
+        vec![(vec![1], vec![2])]
+    );
+    assert_eq!(pids(&[bound(1, "0.0.0.0"), bound(1, "10.0.0.5")]), vec![]);
+}
+
+#[test]
+fn only_the_rows_in_a_conflict_are_marked() {
+    let bound = |pid, port, address: &str| PortProcess {
+        address: address.to_string(),
+        ..listener(pid, port, "node")
+    };
+    let unknown = crate::UNKNOWN_PID;
+    let fake = Fake::with(vec![
+        bound(PID, 3000, "0.0.0.0"),
+        bound(PID, 4000, "127.0.0.1"),
+        bound(PID + 1, 3000, "10.0.0.5"),
+        bound(unknown, 5000, "0.0.0.0"),
+        bound(unknown, 5000, "10.0.0.6"),
+        bound(unknown, 6000, "127.0.0.1"),
+    ]);
+    let app = app(&fake, unconfirmed());
+    let marked: Vec<(u32, u16)> = app
+        .processes
+        .iter()
+        .filter(|p| app.conflict_for(p).is_some())
+        .map(|p| (p.pid, p.port))
+        .collect();
+    assert_eq!(
+        marked,
+        vec![
+            (PID, 3000),
+            (PID + 1, 3000),
+            (unknown, 5000),
+            (unknown, 5000)
+        ]
+    );
+}
+
+#[test]
+fn editors_are_tagged_by_their_whole_name() {
+    use crate::enrich::ide_tag;
+
+    let tag = |name: &str| ide_tag(&listener(PID, 3000, name));
+    assert_eq!(tag("code"), Some("vscode"));
+    assert_eq!(tag("Code Helper (Plugin)"), Some("vscode"));
+    assert_eq!(tag("Code\\x20Hel"), Some("vscode"));
+    assert_eq!(tag("code-insi"), Some("vscode"));
+    assert_eq!(tag("Cursor"), Some("vscode"));
+    assert_eq!(tag("codeium"), None);
+    assert_eq!(tag("coder"), None);
+    assert_eq!(tag("cursord"), None);
+}
+
+#[test]
+fn kubectl_port_forward_command_lines_are_read() {
+    use crate::enrich::port_forward;
+
+    let forward = |line: &str| {
+        let argv: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        port_forward(&argv)
+    };
+
+    let svc = forward("kubectl -n staging port-forward svc/api 8080:80 9090:metrics").unwrap();
+    assert_eq!(svc.target, "svc/api");
+    assert_eq!(svc.namespace.as_deref(), Some("staging"));
+    assert_eq!(
+        svc.ports,
+        vec![
+            (Some(8080), "80".to_string()),
+            (Some(9090), "metrics".to_string())
+        ]
+    );
+    assert_eq!(svc.summary(9090), "svc/api [staging] -> metrics");
+
+    let pod = forward(
+        "kubectl port-forward --namespace=dev --address 0.0.0.0 --context kind web-7d9f 5432",
+    )
+    .unwrap();
+    assert_eq!(pod.target, "pod/web-7d9f");
+    assert_eq!(pod.namespace.as_deref(), Some("dev"));
+    assert_eq!(pod.context.as_deref(), Some("kind"));
+    assert_eq!(pod.ports, vec![(Some(5432), "5432".to_string())]);
+
+    // kubectl picks the local port; the summary still names the remote.
+    let random = forward("kubectl port-forward deploy/worker :80").unwrap();
+    assert_eq!(random.namespace, None);
+    assert_eq!(random.ports, vec![(None, "80".to_string())]);
+    assert_eq!(random.summary(41234), "deploy/worker -> 80");
+
+    assert!(forward("kubectl get pods -n port-forward").is_none());
+    assert!(forward("kubectl port-forward").is_none());
+}
+
+fn parse(line: &str) -> Result<cli::Command, String> {
+    cli::parse(line.split_whitespace().map(str::to_string))
+}
+
+#[test]
+fn guard_intervals_must_be_positive_and_finite() {
+    for bad in ["inf", "nan", "0", "-1", "1e300", "soon"] {
+        let line = format!("guard 8080 --interval {}", bad);
+        assert_eq!(
+            parse(&line).err(),
+            Some(format!("invalid --interval value '{}'", bad)),
+            "{}",
+            line
+        );
+    }
+    let Ok(cli::Command::Guard(options)) = parse("guard 8080 --interval 0.5") else {
+        panic!("guard 8080 --interval 0.5 did not parse");
+    };
+    assert_eq!(options.interval, std::time::Duration::from_millis(500));
+}
+
+#[test]
+fn keepalive_grace_and_interval_must_be_positive_and_finite() {
+    for bad in ["1e300", "inf", "nan", "0", "-1"] {
+        for flag in ["--grace", "--interval"] {
+            let line = format!("keepalive --port 1 {} {} -- true", flag, bad);
+            assert_eq!(
+                parse(&line).err(),
+                Some(format!("invalid {} value '{}'", flag, bad)),
+                "{}",
+                line
+            );
+        }
+    }
+    let Ok(cli::Command::Keepalive(options)) = parse("keepalive --port 1 --grace 2.5 -- true")
+    else {
+        panic!("keepalive --grace 2.5 did not parse");
+    };
+    assert_eq!(options.grace, std::time::Duration::from_millis(2500));
+    assert_eq!(options.command, ["true"]);
+}
+
+#[test]
+fn config_strings_keep_their_quotes_and_hashes() {
+    let config = config::parse(
+        "ignore = [\"a \\\"b\\\"\", \"c#d\"] # not \"e\"\n\
+         [graceful]\n\
+         \"my.app\" = \"SIGQUIT\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.ignore, ["a \"b\"", "c#d"]);
+    assert_eq!(
+        config.graceful,
+        [("my.app".to_string(), "QUIT".to_string())]
+    );
+}
+
+#[test]
+fn config_arrays_may_span_lines() {
+    let config = config::parse(
+        "protect = [\n\
+         \x20 \"postgres\", # the database\n\
+         \x20 \":5432\",\n\
+         ]\n\
+         never_kill = []\n",
+    )
+    .unwrap();
+    assert_eq!(config.protect, ["postgres", ":5432"]);
+    assert!(config.never_kill.is_empty());
+}
+
+#[test]
+fn config_errors_name_their_line() {
+    let cases = [
+        (
+            "confirm_kill = true\nkill_everything = true\n",
+            "line 2: unknown setting 'kill_everything'",
+        ),
+        ("\n[colours]\n", "line 2: unknown table 'colours'"),
+        (
+            "# grace\ngrace_secs = soon\n",
+            "line 2: grace_secs must be a whole number of seconds",
+        ),
+        (
+            "ignore = \"node\"\n",
+            "line 1: ignore must be a list of strings",
+        ),
+        (
+            "signal = \"TERM\"\nprotect = [\"sshd\"\n",
+            "line 2: unterminated array",
+        ),
+        ("signal\n", "line 1: expected `key = value`"),
+    ];
+    for (contents, error) in cases {
+        assert_eq!(
+            config::parse(contents).err().as_deref(),
+            Some(error),
+            "{:?}",
+            contents
+        );
+    }
+}
+
+#[test]
+fn saving_rewrites_only_the_settings_that_changed() {
+    let contents = "# my rip setup\n\
+                    grace_secs = 10 # long shutdowns\n\
+                    \n\
+                    # keep these alive\n\
+                    never_kill = [\n\
+                    \x20 \"postgres\",\n\
+                    ]\n\
+                    \n\
+                    [graceful]\n\
+                    nginx = \"QUIT\"\n\
+                    \n\
+                    [theme]\n\
+                    danger = \"red\"\n";
+    let mut config = config::parse(contents).unwrap();
+    assert_eq!(config::update(contents, &config).unwrap(), contents);
+
+    config.grace_secs = 30;
+    config.never_kill.push("redis".to_string());
+    config.confirm_kill = false;
+    config
+        .graceful
+        .push(("puma".to_string(), "INT".to_string()));
+    config.palette.clear();
+    assert_eq!(
+        config::update(contents, &config).unwrap(),
+        "# my rip setup\n\
+         grace_secs = 30 # long shutdowns\n\
+         \n\
+         # keep these alive\n\
+         never_kill = [\"postgres\", \"redis\"]\n\
+         confirm_kill = false\n\
+         \n\
+         [graceful]\n\
+         nginx = \"QUIT\"\n\
+         \"puma\" = \"INT\"\n\
+         \n\
+         [theme]\n"
+    );
+    assert_eq!(
+        config::parse(&config::update(contents, &config).unwrap())
+            .unwrap()
+            .never_kill,
+        ["postgres", "redis"]
+    );
+}
+
+#[test]
+fn saving_adds_tables_the_file_does_not_have() {
+    let mut config = config::parse("colors = false\n").unwrap();
+    config
+        .graceful
+        .push(("nginx".to_string(), "QUIT".to_string()));
+    assert_eq!(
+        config::update("colors = false\n", &config).unwrap(),
+        "colors = false\n\n[graceful]\n\"nginx\" = \"QUIT\"\n"
+    );
+}
+
+#[test]
+fn rules_test_says_what_each_rule_would_do() {
+    let config = config::Config {
+        ignore: vec![":9229".to_string()],
+        protect: vec!["postgres".to_string(), "mysqld".to_string()],
+        graceful: vec![("nginx".to_string(), "QUIT".to_string())],
+        ..config::Config::default()
+    };
+    let rows = [
+        listener(PID, 9229, "node"),
+        listener(PID + 1, 5432, "postgres"),
+        PortProcess {
+            protocol: "UDP".to_string(),
+            ..listener(PID + 1, 5432, "postgres")
+        },
+        listener(PID + 2, 80, "nginx"),
+    ];
+    assert_eq!(
+        crate::rules::report(&config, &rows),
+        [
+            "ignore \":9229\"",
+            "  hides node (PID: 5000000) on :9229",
+            "protect \"postgres\"",
+            "  asks for a capital Y before killing postgres (PID: 5000001) on :5432",
+            "protect \"mysqld\"",
+            "  matches nothing",
+            "[graceful] \"nginx\" = \"QUIT\"",
+            "  stops nginx (PID: 5000002) on :80 with SIGQUIT",
+        ]
+    );
+    assert!(matches!(parse("rules test"), Ok(cli::Command::RulesTest)));
+    assert!(parse("rules run").is_err());
+}
@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+
+/// The colours the TUI draws with, by what they mean rather than what they
+/// look like, so one palette can be swapped for another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// Title bar, detail pane headings and mDNS names.
+    pub accent: Color,
+    /// The help and status bar.
+    pub status: Color,
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// Labels, process context and other secondary text.
+    pub muted: Color,
+    /// Hints that should barely show, like empty heatmap cells.
+    pub faint: Color,
+    /// Loopback-only addresses, healthy HTTP and blocked ports.
+    pub good: Color,
+    /// Marked rows and medium risk.
+    pub warning: Color,
+    /// High risk, failed checks and anything reachable from outside.
+    pub danger: Color,
+    /// Port conflicts and abstract sockets.
+    pub notice: Color,
+    /// Tags such as `[docker]`.
+    pub info: Color,
+}
+
+/// Built-in themes by name, `default` first.
+pub const BUILTIN: &[(&str, Theme)] = &[
+    ("default", Theme::DEFAULT),
+    ("light", Theme::LIGHT),
+    ("monochrome", Theme::MONOCHROME),
+];
+
+impl Theme {
+    /// For dark terminals.
+    pub const DEFAULT: Theme = Theme {
+        accent: Color::Cyan,
+        status: Color::Yellow,
+        selection_fg: Color::White,
+        selection_bg: Color::DarkGray,
+        muted: Color::Gray,
+        faint: Color::DarkGray,
+        good: Color::Green,
+        warning: Color::Yellow,
+        danger: Color::Red,
+        notice: Color::Magenta,
+        info: Color::Blue,
+    };
+
+    /// For light terminals, where yellow, cyan and light gray wash out.
+    pub const LIGHT: Theme = Theme {
+        accent: Color::Blue,
+        status: Color::Indexed(94),
+        selection_fg: Color::Black,
+        selection_bg: Color::Indexed(252),
+        muted: Color::DarkGray,
+        faint: Color::Indexed(248),
+        good: Color::Indexed(28),
+        warning: Color::Indexed(130),
+        danger: Color::Indexed(160),
+        notice: Color::Magenta,
+        info: Color::Indexed(25),
+    };
+
+    /// The terminal's own colours only; the selection is drawn in reverse
+    /// video and warnings keep their bold.
+    pub const MONOCHROME: Theme = Theme {
+        accent: Color::Reset,
+        status: Color::Reset,
+        selection_fg: Color::Reset,
+        selection_bg: Color::Reset,
+        muted: Color::Reset,
+        faint: Color::Reset,
+        good: Color::Reset,
+        warning: Color::Reset,
+        danger: Color::Reset,
+        notice: Color::Reset,
+        info: Color::Reset,
+    };
+
+    pub fn builtin(name: &str) -> Option<Theme> {
+        BUILTIN
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, theme)| *theme)
+    }
+
+    /// The highlighted row of a list.
+    pub fn selection(&self) -> Style {
+        if self.selection_bg == Color::Reset {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default()
+                .fg(self.selection_fg)
+                .bg(self.selection_bg)
+                .add_modifier(Modifier::BOLD)
+        }
+    }
+
+    /// A warning that must not be missed, like a stale list.
+    pub fn alert(&self) -> Style {
+        if self.danger == Color::Reset {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default()
+                .fg(Color::White)
+                .bg(self.danger)
+                .add_modifier(Modifier::BOLD)
+        }
+    }
+
+    fn slot_mut(&mut self, slot: &str) -> Option<&mut Color> {
+        Some(match slot {
+            "accent" => &mut self.accent,
+            "status" => &mut self.status,
+            "selection_fg" => &mut self.selection_fg,
+            "selection_bg" => &mut self.selection_bg,
+            "muted" => &mut self.muted,
+            "faint" => &mut self.faint,
+            "good" => &mut self.good,
+            "warning" => &mut self.warning,
+            "danger" => &mut self.danger,
+            "notice" => &mut self.notice,
+            "info" => &mut self.info,
+            _ => return None,
+        })
+    }
+
+    /// Set one `[theme]` entry. Returns false for an unknown slot.
+    pub fn set(&mut self, slot: &str, color: Color) -> bool {
+        match self.slot_mut(slot) {
+            Some(target) => {
+                *target = color;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A colour as the config writes it: a name (`red`, `lightblue`,
+/// `darkgray`, `reset`), a 256-colour index or `#rrggbb`.
+pub fn parse_color(value: &str) -> Option<Color> {
+    Color::from_str(value).ok()
+}
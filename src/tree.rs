@@ -0,0 +1,137 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::PortProcess;
+
+/// pid -> (parent pid, command), as `risk::process_table` reads it.
+pub type Table = HashMap<u32, (u32, String)>;
+
+/// One line of the tree popup.
+pub struct Node {
+    pub pid: u32,
+    pub name: String,
+    pub depth: usize,
+    /// Ports it listens on, from the last scan.
+    pub ports: Vec<u16>,
+    /// Above the selected process rather than started by it.
+    pub ancestor: bool,
+}
+
+/// The selected process's family: the processes it descends from, init
+/// first, then it and everything it started, each under its parent.
+pub struct Tree {
+    pub pid: u32,
+    pub name: String,
+    pub nodes: Vec<Node>,
+}
+
+impl Tree {
+    pub fn of(process: &PortProcess, processes: &[PortProcess], table: &Table) -> Self {
+        let ports = |pid: u32| {
+            let mut ports: Vec<u16> = processes
+                .iter()
+                .filter(|p| p.pid == pid)
+                .map(|p| p.port)
+                .collect();
+            ports.sort_unstable();
+            ports.dedup();
+            ports
+        };
+
+        let mut ancestors = Vec::new();
+        let mut seen = HashSet::from([process.pid]);
+        let mut parent = table.get(&process.pid).map(|(ppid, _)| *ppid);
+        while let Some(pid) = parent.filter(|pid| *pid != 0 && seen.insert(*pid)) {
+            ancestors.push(pid);
+            parent = table.get(&pid).map(|(ppid, _)| *ppid);
+        }
+        let mut nodes: Vec<Node> = ancestors
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(depth, pid)| Node {
+                pid: *pid,
+                name: name(*pid, table),
+                depth,
+                ports: ports(*pid),
+                ancestor: true,
+            })
+            .collect();
+
+        let children = children(table);
+        let top = nodes.len();
+        let mut pending = vec![(top, process.pid)];
+        while let Some((depth, pid)) = pending.pop() {
+            if depth > top && !seen.insert(pid) {
+                continue;
+            }
+            nodes.push(Node {
+                pid,
+                name: if pid == process.pid {
+                    process.name.clone()
+                } else {
+                    name(pid, table)
+                },
+                depth,
+                ports: ports(pid),
+                ancestor: false,
+            });
+            // Reversed so the stack hands them out lowest PID first.
+            for child in children.get(&pid).into_iter().flatten().rev() {
+                pending.push((depth + 1, *child));
+            }
+        }
+
+        Tree {
+            pid: process.pid,
+            name: process.name.clone(),
+            nodes,
+        }
+    }
+}
+
+/// `roots` and everything they started, deepest first, so no parent is
+/// signalled while a child it would respawn is still around.
+pub fn deepest_first(roots: impl IntoIterator<Item = u32>, table: &Table) -> Vec<u32> {
+    let children = children(table);
+    let mut seen = HashSet::new();
+    let mut order: Vec<(usize, u32)> = Vec::new();
+    let mut pending: Vec<(usize, u32)> = roots.into_iter().map(|pid| (0, pid)).collect();
+    while let Some((depth, pid)) = pending.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        order.push((depth, pid));
+        for child in children.get(&pid).into_iter().flatten() {
+            pending.push((depth + 1, *child));
+        }
+    }
+    order.sort_by_key(|(depth, _)| Reverse(*depth));
+    order.into_iter().map(|(_, pid)| pid).collect()
+}
+
+/// A process's name from the table, without the directory macOS lists
+/// commands with.
+pub fn name(pid: u32, table: &Table) -> String {
+    let Some((_, command)) = table.get(&pid) else {
+        return String::new();
+    };
+    match command.strip_prefix('/') {
+        Some(path) => path.rsplit('/').next().unwrap_or(path).to_string(),
+        None => command.clone(),
+    }
+}
+
+fn children(table: &Table) -> HashMap<u32, Vec<u32>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (pid, (ppid, _)) in table {
+        // The kernel's swapper (0) is its own parent on some systems.
+        if pid != ppid {
+            children.entry(*ppid).or_default().push(*pid);
+        }
+    }
+    for pids in children.values_mut() {
+        pids.sort_unstable();
+    }
+    children
+}
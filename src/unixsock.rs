@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::process::Command;
+
+/// `__SO_ACCEPTCON` in `/proc/net/unix`: the socket is listening.
+const ACCEPTING: u32 = 0x10000;
+
+/// A bound Unix-domain socket or named pipe and the processes holding it.
+#[derive(Clone, Debug)]
+pub struct UnixSocket {
+    /// Filesystem path, `@name` for Linux's abstract namespace, which has
+    /// no file and so never shows up in `ls` or most lsof output, or
+    /// `\\.\pipe\name` for a Windows named pipe.
+    pub path: String,
+    /// "stream", "dgram", "seqpacket", "fifo", "pipe" (Windows), or "socket"
+    /// where the type is unknown (lsof).
+    pub kind: &'static str,
+    /// (pid, process name), lowest PID first: usually the parent that
+    /// created the socket before forking workers.
+    pub owners: Vec<(u32, String)>,
+}
+
+impl UnixSocket {
+    pub fn is_abstract(&self) -> bool {
+        self.path.starts_with('@')
+    }
+}
+
+/// Listening Unix sockets, bound datagram sockets and named pipes, sorted by
+/// path. Reads `/proc` on Linux and falls back to `lsof -U` elsewhere, which
+/// only sees sockets with a filesystem path. On Windows, lists named pipes.
+pub fn list() -> Vec<UnixSocket> {
+    let mut sockets = if cfg!(windows) {
+        windows_pipes()
+    } else {
+        match fs::read_to_string("/proc/net/unix") {
+            Ok(table) => from_proc(&table),
+            Err(_) => from_lsof(),
+        }
+    };
+    sockets.sort_by(|a, b| a.path.cmp(&b.path));
+    sockets
+}
+
+fn from_proc(table: &str) -> Vec<UnixSocket> {
+    // inode -> (path, kind)
+    let mut bound: HashMap<u64, (String, &'static str)> = HashMap::new();
+    for line in table.lines().skip(1) {
+        // Num RefCount Protocol Flags Type St Inode [Path]
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, _, _, flags, kind, state, inode, path, ..] = fields.as_slice() else {
+            continue;
+        };
+        let flags = u32::from_str_radix(flags, 16).unwrap_or(0);
+        let kind = match *kind {
+            "0001" => "stream",
+            "0002" => "dgram",
+            "0005" => "seqpacket",
+            _ => continue,
+        };
+        // Accepted connections carry the listener's path too; keep only the
+        // listener itself and unconnected datagram sockets.
+        let serving = flags & ACCEPTING != 0 || (kind == "dgram" && *state == "01");
+        if let (true, Ok(inode)) = (serving, inode.parse()) {
+            bound.insert(inode, (path.to_string(), kind));
+        }
+    }
+
+    let mut owners: HashMap<u64, Vec<(u32, String)>> = HashMap::new();
+    let mut fifos: BTreeMap<String, Vec<(u32, String)>> = BTreeMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    for pid in entries.filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u32>().ok()) {
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        let name = fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default();
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|inode| inode.parse().ok())
+                .filter(|inode| bound.contains_key(inode))
+            {
+                add_owner(owners.entry(inode).or_default(), pid, &name);
+            } else if is_fifo(&target) {
+                add_owner(fifos.entry(target.into_owned()).or_default(), pid, &name);
+            }
+        }
+    }
+
+    let sockets = bound.into_iter().map(|(inode, (path, kind))| UnixSocket {
+        path,
+        kind,
+        owners: owners.remove(&inode).unwrap_or_default(),
+    });
+    let pipes = fifos.into_iter().map(|(path, owners)| UnixSocket {
+        path,
+        kind: "fifo",
+        owners,
+    });
+    sockets.chain(pipes).collect()
+}
+
+/// A named pipe open in some process. Only regular files, directories,
+/// devices, FIFOs and sockets appear as fd targets with a path, and sockets
+/// show as `socket:[inode]`, so anything else outside `/dev` is a FIFO.
+fn is_fifo(target: &str) -> bool {
+    target.starts_with('/')
+        && !target.starts_with("/dev/")
+        && fs::metadata(target).is_ok_and(|meta| !meta.is_file() && !meta.is_dir())
+}
+
+fn add_owner(owners: &mut Vec<(u32, String)>, pid: u32, name: &str) {
+    if !owners.iter().any(|(owner, _)| *owner == pid) {
+        owners.push((pid, name.to_string()));
+        owners.sort();
+    }
+}
+
+/// `lsof -U` lists every Unix socket fd; only those with a path are bound
+/// (connected ones show the peer as `->0x...`).
+fn from_lsof() -> Vec<UnixSocket> {
+    let Ok(output) = Command::new("lsof")
+        .args(["-nP", "-U", "-F", "pcn"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut by_path: BTreeMap<String, Vec<(u32, String)>> = BTreeMap::new();
+    let (mut pid, mut name) = (None, String::new());
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let (field, value) = line.split_at(line.len().min(1));
+        match field {
+            "p" => pid = value.parse().ok(),
+            "c" => name = value.to_string(),
+            "n" if value.starts_with('/') => {
+                if let Some(pid) = pid {
+                    add_owner(by_path.entry(value.to_string()).or_default(), pid, &name);
+                }
+            }
+            _ => {}
+        }
+    }
+    by_path
+        .into_iter()
+        .map(|(path, owners)| UnixSocket {
+            path,
+            kind: "socket",
+            owners,
+        })
+        .collect()
+}
+
+/// Windows named pipes. Their owners come from Sysinternals `handle`, which
+/// reads handle tables without connecting (a client connection would take
+/// up one of the pipe's instances); it needs an elevated prompt. Without it
+/// only the pipe names are listed, from the `\\.\pipe\` directory.
+fn windows_pipes() -> Vec<UnixSocket> {
+    for program in ["handle64", "handle"] {
+        let Ok(output) = Command::new(program)
+            .args(["-accepteula", "-nobanner", "-a", "NamedPipe"])
+            .output()
+        else {
+            continue;
+        };
+        let pipes = parse_handle(&String::from_utf8_lossy(&output.stdout));
+        if !pipes.is_empty() {
+            return pipes;
+        }
+    }
+
+    let Ok(output) = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "[System.IO.Directory]::GetFiles('\\\\.\\pipe\\')",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|path| UnixSocket {
+            path: path.to_string(),
+            kind: "pipe",
+            owners: Vec::new(),
+        })
+        .collect()
+}
+
+/// `svchost.exe  pid: 1234  type: File  1A4: \Device\NamedPipe\lsass`
+fn parse_handle(output: &str) -> Vec<UnixSocket> {
+    let mut by_pipe: BTreeMap<String, Vec<(u32, String)>> = BTreeMap::new();
+    for line in output.lines() {
+        let Some((name, rest)) = line.split_once(" pid: ") else {
+            continue;
+        };
+        let Some(pid) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|pid| pid.parse().ok())
+        else {
+            continue;
+        };
+        let Some((_, pipe)) = rest.split_once("\\Device\\NamedPipe\\") else {
+            continue;
+        };
+        let path = format!("\\\\.\\pipe\\{}", pipe.trim());
+        add_owner(by_pipe.entry(path).or_default(), pid, name.trim());
+    }
+    by_pipe
+        .into_iter()
+        .map(|(path, owners)| UnixSocket {
+            path,
+            kind: "pipe",
+            owners,
+        })
+        .collect()
+}
@@ -0,0 +1,231 @@
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::PortProcess;
+
+const SSDP_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(239, 255, 255, 250), 1900);
+const SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+const WAN_SERVICES: &[&str] = &[
+    "urn:schemas-upnp-org:service:WANIPConnection:2",
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1",
+];
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_MAPPINGS: u32 = 256;
+
+/// A port forwarded from the internet by the gateway.
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub external_port: u16,
+    pub internal_port: u16,
+    pub internal_client: String,
+    pub protocol: String,
+}
+
+impl Mapping {
+    /// Whether this forward lands on `process` on this machine.
+    pub fn targets(&self, process: &PortProcess, local_ip: Option<IpAddr>) -> bool {
+        self.internal_port == process.port
+            && self.protocol.eq_ignore_ascii_case(&process.protocol)
+            && (local_ip.is_some_and(|ip| ip.to_string() == self.internal_client)
+                || process.address == self.internal_client)
+    }
+}
+
+/// Find the UPnP Internet Gateway Device and list its port mappings.
+///
+/// NAT-PMP/PCP gateways are not queried: those protocols can create and
+/// refresh mappings but offer no way to enumerate existing ones.
+pub fn port_mappings() -> Result<Vec<Mapping>, String> {
+    let location = discover_gateway()?;
+    let description = http_request(&location, "GET", &[], "")?;
+    let (service, control_url) = find_wan_service(&location, &description)
+        .ok_or_else(|| tr!("gateway does not expose a WAN connection service"))?;
+
+    let mut mappings = Vec::new();
+    for index in 0..MAX_MAPPINGS {
+        match mapping_entry(&control_url, service, index) {
+            Some(mapping) => mappings.push(mapping),
+            None => break,
+        }
+    }
+    Ok(mappings)
+}
+
+fn discover_gateway() -> Result<String, String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|e| e.to_string())?;
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: 1\r\nST: {}\r\n\r\n",
+        SEARCH_TARGET
+    );
+    socket
+        .send_to(request.as_bytes(), SSDP_ADDR)
+        .map_err(|e| e.to_string())?;
+
+    let deadline = Instant::now() + DISCOVERY_TIMEOUT;
+    let mut buf = [0u8; 2048];
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        socket
+            .set_read_timeout(Some(remaining.max(Duration::from_millis(1))))
+            .map_err(|e| e.to_string())?;
+        let Ok((n, _)) = socket.recv_from(&mut buf) else {
+            break;
+        };
+        let response = String::from_utf8_lossy(&buf[..n]);
+        if let Some(location) = header(&response, "location") {
+            return Ok(location.to_string());
+        }
+    }
+    Err(tr!("no UPnP gateway answered"))
+}
+
+fn find_wan_service(location: &str, description: &str) -> Option<(&'static str, String)> {
+    WAN_SERVICES.iter().find_map(|service| {
+        let at = description.find(&format!("<serviceType>{}</serviceType>", service))?;
+        let control = tag(&description[at..], "controlURL")?;
+        Some((*service, resolve_url(location, control)))
+    })
+}
+
+fn mapping_entry(control_url: &str, service: &str, index: u32) -> Option<Mapping> {
+    let body = format!(
+        "<?xml version=\"1.0\"?><s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body><u:GetGenericPortMappingEntry xmlns:u=\"{}\"><NewPortMappingIndex>{}</NewPortMappingIndex></u:GetGenericPortMappingEntry></s:Body></s:Envelope>",
+        service, index
+    );
+    let action = format!("\"{}#GetGenericPortMappingEntry\"", service);
+    let response = http_request(
+        control_url,
+        "POST",
+        &[
+            ("Content-Type", "text/xml; charset=\"utf-8\""),
+            ("SOAPAction", &action),
+        ],
+        &body,
+    )
+    .ok()?;
+
+    Some(Mapping {
+        external_port: tag(&response, "NewExternalPort")?.parse().ok()?,
+        internal_port: tag(&response, "NewInternalPort")?.parse().ok()?,
+        internal_client: tag(&response, "NewInternalClient")?.to_string(),
+        protocol: tag(&response, "NewProtocol")?.to_string(),
+    })
+}
+
+/// Text content of the first `<name>` element (ignoring namespace prefixes).
+fn tag<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = xml.find(&format!("{}>", name))? + name.len() + 1;
+    let len = xml[open..].find("</")?;
+    Some(xml[open..open + len].trim())
+}
+
+fn header<'a>(response: &'a str, name: &str) -> Option<&'a str> {
+    response.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then_some(value.trim())
+    })
+}
+
+/// Split `http://host:port/path` into (`host:port`, `/path`).
+fn split_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix("http://")?;
+    match rest.find('/') {
+        Some(i) => Some((&rest[..i], &rest[i..])),
+        None => Some((rest, "/")),
+    }
+}
+
+fn resolve_url(base: &str, url: &str) -> String {
+    if url.starts_with("http://") {
+        return url.to_string();
+    }
+    let host = split_url(base).map(|(host, _)| host).unwrap_or_default();
+    let path = if url.starts_with('/') {
+        url.to_string()
+    } else {
+        format!("/{}", url)
+    };
+    format!("http://{}{}", host, path)
+}
+
+fn http_request(
+    url: &str,
+    method: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> Result<String, String> {
+    let (host, path) = split_url(url).ok_or_else(|| format!("unsupported URL {}", url))?;
+    let addr: SocketAddr = host
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {}", host))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, HTTP_TIMEOUT).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(HTTP_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        path,
+        host,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+    let split = find(&raw, b"\r\n\r\n").ok_or("malformed HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    if !head
+        .split_whitespace()
+        .nth(1)
+        .is_some_and(|code| code.starts_with('2'))
+    {
+        return Err(head.lines().next().unwrap_or_default().to_string());
+    }
+    let body =
+        if header(&head, "transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+            dechunk(body)
+        } else {
+            body.to_vec()
+        };
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Some(line_end) = find(body, b"\r\n") {
+        let size = std::str::from_utf8(&body[..line_end])
+            .ok()
+            .and_then(|s| usize::from_str_radix(s.split(';').next()?.trim(), 16).ok());
+        let data = &body[line_end + 2..];
+        match size {
+            Some(size) if size > 0 && data.len() >= size => {
+                out.extend_from_slice(&data[..size]);
+                body = data[size..].strip_prefix(b"\r\n").unwrap_or(&data[size..]);
+            }
+            _ => break,
+        }
+    }
+    out
+}
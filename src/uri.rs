@@ -0,0 +1,117 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The URI scheme rip handles.
+pub const SCHEME: &str = "rip";
+
+/// The port a `rip://kill?port=PORT` link asks to free. Only `kill` is
+/// understood; other actions and parameters are refused rather than
+/// guessed at, since the link may come from any web page.
+pub fn parse(uri: &str) -> Result<u16, String> {
+    let invalid = || tr!("not a rip://kill?port=PORT link: {}", uri);
+    let rest = uri
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .ok_or_else(invalid)?;
+    let rest = rest.trim_start_matches('/');
+    let (action, query) = rest.split_once('?').ok_or_else(invalid)?;
+    if action.trim_end_matches('/') != "kill" {
+        return Err(tr!("unknown rip:// action '{}'", action));
+    }
+    let mut port = None;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        match pair.split_once('=') {
+            Some(("port", value)) => port = Some(crate::cli::parse_port(value)?),
+            _ => return Err(tr!("unexpected rip:// parameter '{}'", pair)),
+        }
+    }
+    port.ok_or_else(invalid)
+}
+
+/// Make this rip binary the handler for `rip://` links, opening in a
+/// terminal. Returns what was registered, to tell the user.
+pub fn register() -> io::Result<String> {
+    let exe = std::env::current_exe()?;
+    if cfg!(windows) {
+        register_windows(&exe.display().to_string())
+    } else if cfg!(target_os = "macos") {
+        Err(io::Error::other(tr!(
+            "macOS only hands links to app bundles; wrap `rip handle-uri` in one (e.g. with Automator) to use rip:// links"
+        )))
+    } else {
+        register_xdg(&exe.display().to_string())
+    }
+}
+
+/// A desktop entry claiming `x-scheme-handler/rip`, set as its default.
+fn register_xdg(exe: &str) -> io::Result<String> {
+    let dir = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| io::Error::other(tr!("cannot find a home directory")))?
+        .join("applications");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("rip-uri.desktop");
+    fs::write(
+        &path,
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=rip\n\
+             Comment=Kill processes on ports\n\
+             Exec=\"{}\" handle-uri %u\n\
+             Terminal=true\n\
+             NoDisplay=true\n\
+             MimeType=x-scheme-handler/{};\n",
+            exe, SCHEME
+        ),
+    )?;
+    run(&[
+        "xdg-mime",
+        "default",
+        "rip-uri.desktop",
+        &format!("x-scheme-handler/{}", SCHEME),
+    ])?;
+    Ok(path.display().to_string())
+}
+
+/// The per-user `HKCU\Software\Classes\rip` key, which needs no
+/// administrator rights.
+fn register_windows(exe: &str) -> io::Result<String> {
+    let key = format!("HKCU\\Software\\Classes\\{}", SCHEME);
+    let command = format!("\"{}\" handle-uri \"%1\"", exe);
+    run(&["reg", "add", &key, "/ve", "/d", "URL:rip", "/f"])?;
+    run(&["reg", "add", &key, "/v", "URL Protocol", "/d", "", "/f"])?;
+    run(&[
+        "reg",
+        "add",
+        &format!("{}\\shell\\open\\command", key),
+        "/ve",
+        "/d",
+        &command,
+        "/f",
+    ])?;
+    Ok(key)
+}
+
+fn run(argv: &[&str]) -> io::Result<()> {
+    let output = Command::new(argv[0])
+        .args(&argv[1..])
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other(tr!("{} not found on PATH", argv[0])),
+            _ => e,
+        })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(tr!(
+            "{} failed: {}",
+            argv[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
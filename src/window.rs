@@ -0,0 +1,137 @@
+use std::io;
+use std::process::Command;
+
+use crate::enrich::parent_pid;
+use crate::json::{self, Value};
+
+/// How far up the process tree to look for a window: listeners are often
+/// helper processes of the GUI app that owns the window.
+const MAX_ANCESTORS: usize = 4;
+
+/// A top-level window belonging to a process.
+pub struct Window {
+    pub title: String,
+    pid: u32,
+    /// Window-manager specific handle (X11 window id), when there is one.
+    id: Option<String>,
+}
+
+/// Find the window of `pid` or of its closest ancestor that has one.
+pub fn find(pid: u32) -> Option<Window> {
+    let mut current = pid;
+    for _ in 0..=MAX_ANCESTORS {
+        if let Some(window) = window_of(current) {
+            return Some(window);
+        }
+        current = parent_pid(current).filter(|ppid| *ppid > 1)?;
+    }
+    None
+}
+
+pub fn focus(window: &Window) -> io::Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args([
+                "-e",
+                &format!(
+                    "tell application \"System Events\" to set frontmost of (first process whose unix id is {}) to true",
+                    window.pid
+                ),
+            ])
+            .status()?
+    } else if is_sway() {
+        Command::new("swaymsg")
+            .arg(format!("[pid={}] focus", window.pid))
+            .status()?
+    } else {
+        let id = window
+            .id
+            .as_deref()
+            .ok_or_else(|| io::Error::other(tr!("no window id")))?;
+        Command::new("wmctrl").args(["-ia", id]).status()?
+    };
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(tr!(
+            "focus failed with status: {}",
+            status
+        )))
+    }
+}
+
+fn is_sway() -> bool {
+    std::env::var_os("SWAYSOCK").is_some()
+}
+
+fn window_of(pid: u32) -> Option<Window> {
+    if cfg!(target_os = "macos") {
+        macos_window(pid)
+    } else if is_sway() {
+        sway_window(pid)
+    } else {
+        x11_window(pid)
+    }
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn macos_window(pid: u32) -> Option<Window> {
+    let script = format!(
+        "tell application \"System Events\" to tell (first process whose unix id is {}) to if (count of windows) > 0 then get name of front window",
+        pid
+    );
+    let title = output("osascript", &["-e", &script]).filter(|t| !t.is_empty())?;
+    Some(Window {
+        title,
+        pid,
+        id: None,
+    })
+}
+
+/// `wmctrl -lp` lines look like `0x03a00003  0 12345  host  Window Title`.
+fn x11_window(pid: u32) -> Option<Window> {
+    let listing = output("wmctrl", &["-lp"])?;
+    listing.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let id = fields.next()?;
+        let _desktop = fields.next()?;
+        let owner: u32 = fields.next()?.parse().ok()?;
+        let _host = fields.next()?;
+        (owner == pid).then(|| Window {
+            title: fields.collect::<Vec<_>>().join(" "),
+            pid,
+            id: Some(id.to_string()),
+        })
+    })
+}
+
+fn sway_window(pid: u32) -> Option<Window> {
+    let tree = json::parse(&output("swaymsg", &["-t", "get_tree", "-r"])?).ok()?;
+    find_sway_node(&tree, pid).map(|title| Window {
+        title,
+        pid,
+        id: None,
+    })
+}
+
+fn find_sway_node(node: &Value, pid: u32) -> Option<String> {
+    if node.get("pid").and_then(Value::as_u64) == Some(u64::from(pid)) {
+        return node.get("name").and_then(Value::as_str).map(str::to_string);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| match node.get(key) {
+            Some(Value::Array(children)) => Some(children),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|child| find_sway_node(child, pid))
+}
@@ -0,0 +1,182 @@
+use std::fs;
+use std::io;
+use std::process::Command;
+
+use crate::{PortProcess, UNKNOWN_PID, container, risk, tree};
+
+/// How to free a port that more than one process stands behind.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    /// Signal only the selected process.
+    ChildOnly,
+    /// Signal every process holding the port and everything they started,
+    /// children before their parents.
+    Tree,
+    /// Have systemd stop the unit the process runs in, so it is not
+    /// restarted behind rip's back.
+    SupervisorStop,
+    /// Stop the container the process runs in.
+    ContainerStop,
+}
+
+impl Strategy {
+    pub fn label(self) -> String {
+        match self {
+            Strategy::ChildOnly => tr!("Only this process"),
+            Strategy::Tree => tr!("The whole process tree"),
+            Strategy::SupervisorStop => tr!("Stop it through its supervisor"),
+            Strategy::ContainerStop => tr!("Stop its container"),
+        }
+    }
+}
+
+/// One thing a strategy does, in the order it does them.
+pub enum Step {
+    Signal {
+        pid: u32,
+        name: String,
+        signal: String,
+    },
+    Run(Vec<String>),
+}
+
+impl Step {
+    pub fn describe(&self) -> String {
+        match self {
+            Step::Signal { pid, name, signal } => tr!("SIG{} {} (PID: {})", signal, name, pid),
+            Step::Run(argv) => tr!("run `{}`", argv.join(" ")),
+        }
+    }
+}
+
+/// The kill wizard for one row: the strategies that apply to it, and which
+/// one is highlighted. `reviewing` is set once one has been picked and its
+/// steps are shown for confirmation.
+pub struct Wizard {
+    pub port: u16,
+    pub choices: Vec<(Strategy, Vec<Step>)>,
+    pub selected: usize,
+    pub reviewing: bool,
+}
+
+impl Wizard {
+    /// Move the highlight, wrapping around; ignored while reviewing.
+    pub fn move_selection(&mut self, down: bool) {
+        if self.reviewing {
+            return;
+        }
+        let count = self.choices.len();
+        self.selected = if down {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+    }
+
+    pub fn chosen(&self) -> &(Strategy, Vec<Step>) {
+        &self.choices[self.selected]
+    }
+}
+
+/// The wizard for `selected`, or `None` when a plain kill is unambiguous:
+/// it alone holds the port, no supervisor would restart it and it does not
+/// run in a container. For a port proxy stopping the container comes
+/// first. `signal_for` is the signal a kill sends to a process, by PID and
+/// name.
+pub fn plan(
+    selected: &PortProcess,
+    processes: &[PortProcess],
+    signal_for: impl Fn(u32, &str) -> String,
+) -> Option<Wizard> {
+    let holders: Vec<&PortProcess> = processes
+        .iter()
+        .filter(|p| p.port == selected.port && p.protocol == selected.protocol)
+        .filter(|p| p.pid != UNKNOWN_PID)
+        .collect();
+    let only = Step::Signal {
+        pid: selected.pid,
+        name: selected.name.clone(),
+        signal: signal_for(selected.pid, &selected.name),
+    };
+    let mut choices = vec![(Strategy::ChildOnly, vec![only])];
+
+    let tree = tree_steps(&holders, &signal_for);
+    if tree.len() > 1 {
+        choices.push((Strategy::Tree, tree));
+    }
+    if let Some(argv) = systemd_stop(selected.pid) {
+        choices.push((Strategy::SupervisorStop, vec![Step::Run(argv)]));
+    }
+    if let Some(container) = container::of_listener(selected) {
+        let argv = vec![
+            container.runtime.to_string(),
+            "stop".to_string(),
+            container.name,
+        ];
+        let stop = (Strategy::ContainerStop, vec![Step::Run(argv)]);
+        // Killing a port proxy leaves the container running, and Docker
+        // starts a new proxy; stopping the container is what frees the port.
+        if container::is_port_proxy(&selected.name) {
+            choices.insert(0, stop);
+        } else {
+            choices.push(stop);
+        }
+    }
+
+    (choices.len() > 1).then_some(Wizard {
+        port: selected.port,
+        choices,
+        selected: 0,
+        reviewing: false,
+    })
+}
+
+/// Every holder and its descendants, deepest first.
+fn tree_steps(holders: &[&PortProcess], signal_for: impl Fn(u32, &str) -> String) -> Vec<Step> {
+    let table = risk::process_table();
+    tree::deepest_first(holders.iter().map(|p| p.pid), &table)
+        .into_iter()
+        .map(|pid| {
+            let name = holders
+                .iter()
+                .find(|p| p.pid == pid)
+                .map(|p| p.name.clone())
+                .unwrap_or_else(|| tree::name(pid, &table));
+            Step::Signal {
+                pid,
+                signal: signal_for(pid, &name),
+                name,
+            }
+        })
+        .collect()
+}
+
+/// `systemctl [--user] stop UNIT` for a process in a systemd service, read
+/// from `/proc/PID/cgroup`. A login session's `user@UID.service` is not
+/// offered: stopping it would log the user out.
+fn systemd_stop(pid: u32) -> Option<Vec<String>> {
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let path = cgroup.lines().find_map(|line| line.strip_prefix("0::"))?;
+    let unit = path.rsplit('/').find(|s| s.ends_with(".service"))?;
+    if unit.starts_with("user@") {
+        return None;
+    }
+    let mut argv = vec!["systemctl".to_string()];
+    if path.contains("/user@") {
+        argv.push("--user".to_string());
+    }
+    argv.extend(["stop".to_string(), unit.to_string()]);
+    Some(argv)
+}
+
+/// Run a step's command, failing with its stderr.
+pub fn run(argv: &[String]) -> io::Result<()> {
+    let output = Command::new(&argv[0]).args(&argv[1..]).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}